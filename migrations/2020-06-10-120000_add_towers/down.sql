@@ -0,0 +1,10 @@
+DELETE FROM buildings
+    WHERE building_type IN ('fountain', 'sprinkler');
+
+DELETE FROM pg_enum
+    WHERE pg_enum.enumtypid = 'building_type'::regtype::oid
+    AND pg_enum.enumlabel = 'sprinkler';
+
+DELETE FROM pg_enum
+    WHERE pg_enum.enumtypid = 'building_type'::regtype::oid
+    AND pg_enum.enumlabel = 'fountain';
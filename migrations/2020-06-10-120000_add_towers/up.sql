@@ -0,0 +1,2 @@
+ALTER TYPE building_type ADD VALUE 'fountain';
+ALTER TYPE building_type ADD VALUE 'sprinkler';
@@ -0,0 +1,4 @@
+ALTER TABLE villages
+    DROP COLUMN town_layout;
+
+DROP TYPE TOWN_LAYOUT_TYPE;
@@ -0,0 +1,8 @@
+CREATE TYPE TOWN_LAYOUT_TYPE AS ENUM (
+  'basic',
+  'bend',
+  'meander'
+);
+
+ALTER TABLE villages
+ADD COLUMN town_layout TOWN_LAYOUT_TYPE NOT NULL DEFAULT 'basic';
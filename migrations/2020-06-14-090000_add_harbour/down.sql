@@ -0,0 +1,6 @@
+DELETE FROM buildings
+    WHERE building_type = 'harbour';
+
+DELETE FROM pg_enum
+    WHERE pg_enum.enumtypid = 'building_type'::regtype::oid
+    AND pg_enum.enumlabel = 'harbour';
@@ -0,0 +1 @@
+ALTER TYPE building_type ADD VALUE 'harbour';
@@ -0,0 +1,3 @@
+ALTER TABLE rewards DROP COLUMN building_type;
+ALTER TABLE rewards DROP COLUMN reason;
+DROP TYPE REWARD_REASON;
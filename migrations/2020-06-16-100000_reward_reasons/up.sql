@@ -0,0 +1,9 @@
+-- Record why each reward of a visit report has been granted
+
+CREATE TYPE REWARD_REASON AS ENUM ('visitor', 'effects', 'color', 'building');
+
+ALTER TABLE rewards
+ADD COLUMN reason REWARD_REASON NOT NULL DEFAULT 'visitor';
+
+ALTER TABLE rewards
+ADD COLUMN building_type BUILDING_TYPE;
@@ -0,0 +1,7 @@
+ALTER TABLE visit_reports DROP COLUMN visitors;
+ALTER TABLE visit_reports DROP COLUMN other_village_id;
+ALTER TABLE visit_reports DROP COLUMN report_type;
+DROP TYPE VISIT_REPORT_TYPE;
+
+DROP TABLE return_trips_to_hobos;
+DROP TABLE return_trips;
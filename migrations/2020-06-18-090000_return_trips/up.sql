@@ -0,0 +1,29 @@
+-- Visitors sent by players swim back home after their visit
+
+CREATE TABLE return_trips (
+    id BIGSERIAL PRIMARY KEY,
+    departure TIMESTAMP NOT NULL,
+    arrival TIMESTAMP NOT NULL,
+    home_village_id BIGINT NOT NULL REFERENCES villages(id) ON DELETE CASCADE,
+    visited_village_id BIGINT REFERENCES villages(id) ON DELETE SET NULL
+);
+
+CREATE TABLE return_trips_to_hobos (
+    return_trip_id BIGINT NOT NULL REFERENCES return_trips(id) ON DELETE CASCADE,
+    hobo_id BIGINT NOT NULL REFERENCES hobos(id) ON DELETE CASCADE,
+    satisfied BOOLEAN NOT NULL,
+    CONSTRAINT return_trips_to_hobos_pk PRIMARY KEY (return_trip_id, hobo_id)
+);
+
+-- Reports are written for both sides of a visit
+
+CREATE TYPE VISIT_REPORT_TYPE AS ENUM ('inbound', 'outbound');
+
+ALTER TABLE visit_reports
+ADD COLUMN report_type VISIT_REPORT_TYPE NOT NULL DEFAULT 'inbound';
+
+ALTER TABLE visit_reports
+ADD COLUMN other_village_id BIGINT REFERENCES villages(id) ON DELETE SET NULL;
+
+ALTER TABLE visit_reports
+ADD COLUMN visitors BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- Resources transported by boat from one village to another
+
+CREATE TABLE shipments (
+    id BIGSERIAL PRIMARY KEY,
+    departure TIMESTAMP NOT NULL,
+    arrival TIMESTAMP NOT NULL,
+    origin_village_id BIGINT REFERENCES villages(id) ON DELETE SET NULL,
+    destination_village_id BIGINT NOT NULL REFERENCES villages(id) ON DELETE CASCADE,
+    resource_type RESOURCE_TYPE NOT NULL,
+    amount BIGINT NOT NULL CHECK (amount > 0)
+);
@@ -0,0 +1,12 @@
+-- Open offers of players to trade resources with each other.
+-- The offered resources are held back from the village until the offer is accepted or cancelled.
+
+CREATE TABLE trade_offers (
+    id BIGSERIAL PRIMARY KEY,
+    village_id BIGINT NOT NULL REFERENCES villages(id) ON DELETE CASCADE,
+    offered_resource RESOURCE_TYPE NOT NULL,
+    offered_amount BIGINT NOT NULL CHECK (offered_amount > 0),
+    requested_resource RESOURCE_TYPE NOT NULL,
+    requested_amount BIGINT NOT NULL CHECK (requested_amount > 0),
+    created TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
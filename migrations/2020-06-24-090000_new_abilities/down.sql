@@ -0,0 +1,11 @@
+DELETE FROM tasks
+    WHERE task_type IN ('cheer_ability', 'slow_ability', 'bloom_ability');
+DELETE FROM abilities
+    WHERE ability_type IN ('cheer', 'slow', 'bloom');
+
+DELETE FROM pg_enum
+    WHERE pg_enum.enumtypid = 'task_type'::regtype::oid
+    AND pg_enum.enumlabel IN ('cheer_ability', 'slow_ability', 'bloom_ability');
+DELETE FROM pg_enum
+    WHERE pg_enum.enumtypid = 'ability_type'::regtype::oid
+    AND pg_enum.enumlabel IN ('cheer', 'slow', 'bloom');
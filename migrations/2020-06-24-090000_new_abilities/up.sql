@@ -0,0 +1,6 @@
+ALTER TYPE ability_type ADD VALUE 'cheer';
+ALTER TYPE ability_type ADD VALUE 'slow';
+ALTER TYPE ability_type ADD VALUE 'bloom';
+ALTER TYPE task_type ADD VALUE 'cheer_ability';
+ALTER TYPE task_type ADD VALUE 'slow_ability';
+ALTER TYPE task_type ADD VALUE 'bloom_ability';
@@ -0,0 +1,6 @@
+DELETE FROM buildings
+    WHERE building_type = 'shack';
+
+DELETE FROM pg_enum
+    WHERE pg_enum.enumtypid = 'building_type'::regtype::oid
+    AND pg_enum.enumlabel = 'shack';
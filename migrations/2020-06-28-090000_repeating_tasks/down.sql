@@ -0,0 +1 @@
+DROP TABLE repeating_tasks;
@@ -0,0 +1,11 @@
+-- Task lists that workers perform over and over again.
+-- Only one iteration at the time is copied into the tasks table, the next one is added when the last task has started.
+
+CREATE TABLE repeating_tasks (
+    id BIGSERIAL PRIMARY KEY,
+    worker_id BIGINT NOT NULL REFERENCES workers(id) ON DELETE CASCADE,
+    task_type TASK_TYPE NOT NULL,
+    x INT NOT NULL,
+    y INT NOT NULL,
+    duration_ms BIGINT NOT NULL DEFAULT 0 CHECK (duration_ms >= 0)
+);
@@ -0,0 +1,4 @@
+-- Buildings can be upgraded, existing buildings start at level 1
+
+ALTER TABLE buildings
+ADD COLUMN level INT NOT NULL DEFAULT 1 CHECK (level >= 1);
@@ -0,0 +1,4 @@
+ALTER TABLE hobos
+DROP CONSTRAINT hobos_nest_fkey;
+ALTER TABLE hobos
+ADD CONSTRAINT hobos_nest_fkey FOREIGN KEY (nest) REFERENCES buildings(id) ON DELETE SET NULL;
@@ -0,0 +1,7 @@
+-- Hobos have to be moved to another nest or released before their nest is deleted.
+-- NO ACTION is checked at the end of the statement, so deleting a whole village still works.
+
+ALTER TABLE hobos
+DROP CONSTRAINT hobos_nest_fkey;
+ALTER TABLE hobos
+ADD CONSTRAINT hobos_nest_fkey FOREIGN KEY (nest) REFERENCES buildings(id) ON DELETE NO ACTION;
@@ -0,0 +1,2 @@
+ALTER TABLE attacks_to_hobos
+DROP COLUMN resting_spot;
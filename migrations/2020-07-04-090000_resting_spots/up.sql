@@ -0,0 +1,5 @@
+-- Each unhurried visitor rests on its own spot of the lane.
+-- Visitors already in town all share the first spot, until they leave.
+
+ALTER TABLE attacks_to_hobos
+ADD COLUMN resting_spot INT NOT NULL DEFAULT 0;
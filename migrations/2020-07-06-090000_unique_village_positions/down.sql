@@ -0,0 +1 @@
+DROP INDEX villages_position;
@@ -0,0 +1,4 @@
+-- Only one village can be placed on each position of the map.
+-- Players founding villages at the same time are told apart by this index.
+
+CREATE UNIQUE INDEX villages_position ON villages (x, y);
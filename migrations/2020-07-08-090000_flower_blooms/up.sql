@@ -0,0 +1,9 @@
+-- The bloom ability strengthens flower auras for a while.
+-- Blooms are kept with their start, so that past visits keep the aura strength they have seen.
+
+CREATE TABLE blooms (
+    id BIGSERIAL PRIMARY KEY,
+    building_id BIGINT NOT NULL REFERENCES buildings (id) ON DELETE CASCADE,
+    strength INT NOT NULL,
+    start_time TIMESTAMP NOT NULL
+);
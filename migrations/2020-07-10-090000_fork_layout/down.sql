@@ -0,0 +1,6 @@
+UPDATE villages
+    SET town_layout = 'basic'
+    WHERE town_layout = 'fork';
+DELETE FROM pg_enum
+    WHERE pg_enum.enumtypid = 'town_layout_type'::regtype::oid
+    AND pg_enum.enumlabel = 'fork';
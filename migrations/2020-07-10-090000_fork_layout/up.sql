@@ -0,0 +1,3 @@
+-- A river that forks around an island, with a resting place on each branch
+
+ALTER TYPE town_layout_type ADD VALUE 'fork';
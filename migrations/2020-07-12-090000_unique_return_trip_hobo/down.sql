@@ -0,0 +1 @@
+DROP INDEX return_trips_to_hobos_hobo;
@@ -0,0 +1,9 @@
+-- A hobo can only be on one return trip at a time.
+-- Duplicates from visits that have been finished twice are removed, the first trip is kept.
+
+DELETE FROM return_trips_to_hobos a
+USING return_trips_to_hobos b
+WHERE a.hobo_id = b.hobo_id
+AND a.return_trip_id > b.return_trip_id;
+
+CREATE UNIQUE INDEX return_trips_to_hobos_hobo ON return_trips_to_hobos (hobo_id);
@@ -158,6 +158,17 @@ impl Query {
             .map(GqlPlayer)
             .collect()
     }
+    // Object Visibility: public
+    // Returns open offers on the marketplace, newest first, skipping `offset` offers.
+    // At most 100 offers are returned, 20 if no limit is given.
+    fn trade_offers(ctx: &Context, offset: i32, limit: Option<i32>) -> Vec<GqlTradeOffer> {
+        let limit = limit.unwrap_or(20).max(0).min(100);
+        ctx.db()
+            .trade_offers(offset.max(0) as i64, limit as i64)
+            .into_iter()
+            .map(GqlTradeOffer)
+            .collect()
+    }
 }
 
 #[juniper::object(
@@ -5,6 +5,8 @@
 use super::*;
 use juniper;
 use juniper::FieldResult;
+use paddlers_shared_lib::game_mechanics::rewards::{visit_rewards, SatisfiedVisitor};
+use paddlers_shared_lib::game_mechanics::town::*;
 
 #[juniper::object (Context = Context)]
 impl GqlAttack {
@@ -25,6 +27,26 @@ impl GqlAttack {
     fn arrival(&self) -> FieldResult<GqlTimestamp> {
         datetime(&self.0.arrival)
     }
+    fn destination(&self, ctx: &Context) -> FieldResult<GqlVillage> {
+        let village = ctx
+            .db()
+            .village(self.0.destination())
+            .ok_or("Invalid destination on attack")?;
+        Ok(GqlVillage(village))
+    }
+    /// Predicted outcome of the visit with the current buildings of the visited town.
+    /// Planned buildings are added to the town as if they were built right now.
+    #[graphql(arguments(planned_buildings(description = "Hypothetical additional buildings",)))]
+    fn forecast(
+        &self,
+        ctx: &Context,
+        planned_buildings: Option<Vec<GqlPlannedBuilding>>,
+    ) -> FieldResult<GqlAttackForecast> {
+        // Only the defending side may look at the defence of the town
+        ctx.check_village_key(self.0.destination())?;
+        let planned = planned_buildings.unwrap_or_default();
+        Ok(attack_forecast(ctx.db(), &self.0, &planned))
+    }
     fn attacker(&self, ctx: &Context) -> FieldResult<Option<GqlPlayer>> {
         let db = ctx.db();
         Ok(self
@@ -37,6 +59,48 @@ impl GqlAttack {
     }
 }
 #[juniper::object (Context = Context)]
+impl GqlVisitorForecast {
+    fn hobo(&self) -> GqlHobo {
+        GqlHobo(self.0.clone())
+    }
+    fn hp_left(&self) -> i32 {
+        self.1.hp_left as i32
+    }
+    fn satisfied(&self) -> bool {
+        self.1.satisfied()
+    }
+    /// Not available for resting visitors that will stay until they are satisfied
+    fn exit_time(&self) -> Option<GqlTimestamp> {
+        self.1
+            .exit_time
+            .map(|t| GqlTimestamp::from_chrono(&t.into()))
+    }
+}
+#[juniper::object (Context = Context)]
+impl GqlRewardForecast {
+    fn resource_type(&self) -> ResourceType {
+        self.0.resource
+    }
+    fn amount(&self) -> i32 {
+        self.0.amount as i32
+    }
+    fn reason(&self) -> RewardReason {
+        self.0.reason
+    }
+    fn building_type(&self) -> Option<BuildingType> {
+        self.0.building_type
+    }
+}
+#[juniper::object (Context = Context)]
+impl GqlAttackForecast {
+    fn visitors(&self) -> &[GqlVisitorForecast] {
+        &self.visitors
+    }
+    fn rewards(&self) -> &[GqlRewardForecast] {
+        &self.rewards
+    }
+}
+#[juniper::object (Context = Context)]
 impl GqlAttackUnit {
     fn hobo(&self) -> &GqlHobo {
         &self.0
@@ -53,6 +117,10 @@ impl GqlHoboAttackInfo {
     fn satisfied(&self) -> FieldResult<Option<bool>> {
         Ok(self.0.satisfied)
     }
+    /// Where the visitor rests in town, unless it is hurried
+    fn resting_spot(&self) -> i32 {
+        self.0.resting_spot
+    }
 }
 
 #[juniper::object (Context = Context)]
@@ -66,6 +134,21 @@ impl GqlAttackReport {
     fn karma(&self) -> i32 {
         self.inner.karma as i32
     }
+    /// Inbound reports are from visitors to the town, outbound reports from visitors sent by the town
+    fn report_type(&self) -> VisitReportType {
+        self.inner.report_type
+    }
+    /// Number of visitors, including those that have not been satisfied
+    fn visitors(&self) -> i32 {
+        self.inner.visitors as i32
+    }
+    /// The village on the other side of the visit
+    fn other_village(&self, ctx: &Context) -> Option<GqlVillage> {
+        self.inner
+            .other_village_id
+            .and_then(|id| ctx.db().village(VillageKey(id)))
+            .map(GqlVillage)
+    }
     fn feathers(&self) -> i32 {
         self.resource(ResourceType::Feathers)
     }
@@ -94,6 +177,38 @@ impl GqlAttackReport {
     }
 }
 
+#[juniper::object (Context = Context)]
+impl GqlShipment {
+    fn id(&self) -> juniper::ID {
+        self.0.id.to_string().into()
+    }
+    fn resource_type(&self) -> ResourceType {
+        self.0.resource_type
+    }
+    fn amount(&self) -> i32 {
+        self.0.amount as i32
+    }
+    fn departure(&self) -> FieldResult<GqlTimestamp> {
+        datetime(&self.0.departure)
+    }
+    fn arrival(&self) -> FieldResult<GqlTimestamp> {
+        datetime(&self.0.arrival)
+    }
+    fn origin(&self, ctx: &Context) -> Option<GqlVillage> {
+        self.0
+            .origin()
+            .and_then(|v| ctx.db().village(v))
+            .map(GqlVillage)
+    }
+    fn destination(&self, ctx: &Context) -> FieldResult<GqlVillage> {
+        let village = ctx
+            .db()
+            .village(self.0.destination())
+            .ok_or("Invalid destination on shipment")?;
+        Ok(GqlVillage(village))
+    }
+}
+
 #[juniper::object (Context = Context)]
 impl GqlTask {
     fn id(&self) -> juniper::ID {
@@ -197,3 +312,69 @@ impl GqlWorker {
         self.0.exp
     }
 }
+
+/// Predicts the outcome of a visit with the same computations that the game-master uses to evaluate it.
+fn attack_forecast(db: &DbConn, atk: &Attack, planned: &[GqlPlannedBuilding]) -> GqlAttackForecast {
+    let village = atk.destination();
+    let layout = db
+        .village(village)
+        .map(|v| v.town_layout)
+        .unwrap_or_default();
+    let buildings = db.buildings(village);
+    let now: Timestamp = chrono::Utc::now().naive_utc().into();
+    let mut town = DefendingTown {
+        layout,
+        buildings: buildings
+            .iter()
+            .filter_map(DefendingBuilding::from_db)
+            .collect(),
+        blooms: db
+            .blooms(village)
+            .iter()
+            .map(|b| (b.building_id, AuraBloom::from_db(b)))
+            .collect(),
+    };
+    for (i, p) in planned.iter().enumerate() {
+        // Negative ids cannot collide with existing buildings
+        let id = -1 - i as i64;
+        let position = (p.x as usize, p.y as usize);
+        if let Some(b) = DefendingBuilding::planned(id, p.building_type, position, now) {
+            town.buildings.push(b);
+        }
+    }
+
+    let mut visitors = vec![];
+    let mut satisfied = vec![];
+    for (hobo, info) in db.attack_hobos_with_attack_info(atk) {
+        let effects = db.effects_on_hobo(hobo.key());
+        let unit = AttackingHobo {
+            hobo: &hobo,
+            attack_to_hobo: &info,
+            effects: &effects,
+            attack: atk,
+        };
+        let mut forecast = town.forecast(&unit);
+        // The game-master may already have decided on this visitor
+        match info.satisfied {
+            Some(true) => forecast.hp_left = 0,
+            Some(false) => forecast.hp_left = forecast.hp_left.max(1),
+            None => {}
+        }
+        if forecast.satisfied() {
+            satisfied.push(SatisfiedVisitor {
+                hp: hobo.hp,
+                speed: hobo.speed,
+                hurried: hobo.hurried,
+                color: hobo.color,
+                effects: effects.len(),
+            });
+        }
+        visitors.push((hobo, forecast));
+    }
+    let building_types = buildings
+        .iter()
+        .map(|b| &b.building_type)
+        .chain(planned.iter().map(|p| &p.building_type));
+    let rewards = visit_rewards(&satisfied, building_types);
+    GqlAttackForecast::authorized(visitors, rewards)
+}
@@ -20,6 +20,8 @@
 use super::*;
 use juniper;
 use juniper::FieldResult;
+use paddlers_shared_lib::game_mechanics::rewards::RewardItem;
+use paddlers_shared_lib::game_mechanics::town::VisitorForecast;
 use paddlers_shared_lib::sql_db::keys::SqlKey;
 use paddlers_shared_lib::story::story_state::StoryState;
 
@@ -33,6 +35,18 @@ pub struct GqlHobo(pub paddlers_shared_lib::models::Hobo);
 pub struct GqlAttackUnit(pub GqlHobo, pub GqlHoboAttackInfo);
 /// Additional information for a hobo that is currently attacking
 pub struct GqlHoboAttackInfo(pub paddlers_shared_lib::models::AttackToHobo);
+/// An open offer on the marketplace
+pub struct GqlTradeOffer(pub paddlers_shared_lib::models::TradeOffer);
+/// A flower building in bloom, see the bloom ability
+pub struct GqlBloom(pub paddlers_shared_lib::models::Bloom);
+
+/// A building that does not exist, used for predictions
+#[derive(juniper::GraphQLInputObject)]
+pub struct GqlPlannedBuilding {
+    pub building_type: BuildingType,
+    pub x: i32,
+    pub y: i32,
+}
 
 // Complete list of public objects with restricted fields access.
 pub struct GqlBuilding(pub paddlers_shared_lib::models::Building);
@@ -52,8 +66,20 @@ pub struct GqlAttackReport {
     pub rewards: Option<Rewards>,
     _priv: PrivacyGuard,
 }
+pub struct GqlAttackForecast {
+    pub visitors: Vec<GqlVisitorForecast>,
+    pub rewards: Vec<GqlRewardForecast>,
+    _priv: PrivacyGuard,
+}
 pub struct GqlEffect(pub paddlers_shared_lib::models::Effect, PrivacyGuard);
+pub struct GqlRewardForecast(pub RewardItem, PrivacyGuard);
+pub struct GqlShipment(pub paddlers_shared_lib::models::Shipment, PrivacyGuard);
 pub struct GqlTask(pub paddlers_shared_lib::models::Task, PrivacyGuard);
+pub struct GqlVisitorForecast(
+    pub paddlers_shared_lib::models::Hobo,
+    pub VisitorForecast,
+    PrivacyGuard,
+);
 pub struct GqlWorker(pub paddlers_shared_lib::models::Worker, PrivacyGuard);
 
 #[juniper::object (Context = Context)]
@@ -110,6 +136,10 @@ impl GqlVillage {
     fn faith(&self) -> i32 {
         self.0.faith as i32
     }
+    /// Field Visibility: public
+    fn town_layout(&self) -> TownLayout {
+        self.0.town_layout
+    }
     /// Field Visibility: user
     fn sticks(&self, ctx: &Context) -> FieldResult<i32> {
         ctx.check_village_key(self.0.key())?;
@@ -157,6 +187,40 @@ impl GqlVillage {
             .map(GqlAttack::authorized)
             .collect())
     }
+    /// Attacks sent out by this village which have not arrived yet
+    /// Field Visibility: user
+    fn outgoing_attacks(&self, ctx: &Context) -> FieldResult<Vec<GqlAttack>> {
+        ctx.check_village_key(self.0.key())?;
+        let now = chrono::Utc::now().naive_utc();
+        Ok(ctx
+            .db()
+            .outgoing_attacks(self.0.key(), now)
+            .into_iter()
+            .map(GqlAttack::authorized)
+            .collect())
+    }
+    /// Resources on their way to this village
+    /// Field Visibility: user
+    fn incoming_shipments(&self, ctx: &Context) -> FieldResult<Vec<GqlShipment>> {
+        ctx.check_village_key(self.0.key())?;
+        Ok(ctx
+            .db()
+            .incoming_shipments(self.0.key())
+            .into_iter()
+            .map(GqlShipment::authorized)
+            .collect())
+    }
+    /// Resources sent out by this village which have not arrived yet
+    /// Field Visibility: user
+    fn outgoing_shipments(&self, ctx: &Context) -> FieldResult<Vec<GqlShipment>> {
+        ctx.check_village_key(self.0.key())?;
+        Ok(ctx
+            .db()
+            .outgoing_shipments(self.0.key())
+            .into_iter()
+            .map(GqlShipment::authorized)
+            .collect())
+    }
     /// Field Visibility: public
     fn owner(&self, ctx: &Context) -> FieldResult<Option<GqlPlayer>> {
         Ok(if let Some(owner) = self.0.player_id {
@@ -219,9 +283,30 @@ impl GqlBuilding {
     fn attacks_per_cycle(&self) -> Option<i32> {
         self.0.attacks_per_cycle
     }
+    fn level(&self) -> i32 {
+        self.0.level
+    }
     fn creation(&self) -> FieldResult<GqlTimestamp> {
         datetime(&self.0.creation)
     }
+    /// Blooms on the aura of the building, including those that have faded already
+    fn blooms(&self, ctx: &Context) -> Vec<GqlBloom> {
+        ctx.db()
+            .blooms_on_building(BuildingKey(self.0.id))
+            .into_iter()
+            .map(GqlBloom)
+            .collect()
+    }
+}
+
+#[juniper::object (Context = Context)]
+impl GqlBloom {
+    fn start_time(&self) -> GqlTimestamp {
+        GqlTimestamp::from_chrono(&self.0.start_time)
+    }
+    fn strength(&self) -> i32 {
+        self.0.strength
+    }
 }
 
 #[juniper::object (Context = Context)]
@@ -305,6 +390,43 @@ impl GqlStream {
     }
 }
 
+#[juniper::object (Context = Context)]
+impl GqlTradeOffer {
+    /// Field Visibility: public
+    fn id(&self) -> juniper::ID {
+        self.0.id.to_string().into()
+    }
+    /// Field Visibility: public
+    fn offered_resource(&self) -> ResourceType {
+        self.0.offered_resource
+    }
+    /// Field Visibility: public
+    fn offered_amount(&self) -> i32 {
+        self.0.offered_amount as i32
+    }
+    /// Field Visibility: public
+    fn requested_resource(&self) -> ResourceType {
+        self.0.requested_resource
+    }
+    /// Field Visibility: public
+    fn requested_amount(&self) -> i32 {
+        self.0.requested_amount as i32
+    }
+    /// Field Visibility: public
+    fn created(&self) -> FieldResult<GqlTimestamp> {
+        datetime(&self.0.created)
+    }
+    /// Village that has posted the offer
+    /// Field Visibility: public
+    fn village(&self, ctx: &Context) -> FieldResult<GqlVillage> {
+        let village = ctx
+            .db()
+            .village(self.0.village())
+            .ok_or("Invalid village on trade offer")?;
+        Ok(GqlVillage(village))
+    }
+}
+
 /*
  * Constructors to use after authorization.
  * Secrecy model only works if these are only called properly!
@@ -319,11 +441,34 @@ impl GqlAttack {
         GqlAttack(inner, PrivacyGuard)
     }
 }
+impl GqlAttackForecast {
+    pub(super) fn authorized(
+        visitors: Vec<(paddlers_shared_lib::models::Hobo, VisitorForecast)>,
+        rewards: Vec<RewardItem>,
+    ) -> Self {
+        GqlAttackForecast {
+            visitors: visitors
+                .into_iter()
+                .map(|(hobo, forecast)| GqlVisitorForecast(hobo, forecast, PrivacyGuard))
+                .collect(),
+            rewards: rewards
+                .into_iter()
+                .map(|item| GqlRewardForecast(item, PrivacyGuard))
+                .collect(),
+            _priv: PrivacyGuard,
+        }
+    }
+}
 impl GqlEffect {
     pub(super) fn authorized(inner: paddlers_shared_lib::models::Effect) -> Self {
         GqlEffect(inner, PrivacyGuard)
     }
 }
+impl GqlShipment {
+    pub(super) fn authorized(inner: paddlers_shared_lib::models::Shipment) -> Self {
+        GqlShipment(inner, PrivacyGuard)
+    }
+}
 impl GqlTask {
     pub(super) fn authorized(inner: paddlers_shared_lib::models::Task) -> Self {
         GqlTask(inner, PrivacyGuard)
@@ -11,9 +11,11 @@ pub(crate) mod leaderboard;
 pub(crate) mod level;
 pub(crate) mod mana;
 pub(crate) mod map;
+pub(crate) mod market;
 pub(crate) mod movement;
 pub(crate) mod net_receiver;
 pub(crate) mod player_info;
+pub(crate) mod shipments;
 pub(crate) mod status_effects;
 pub(crate) mod story;
 pub(crate) mod town;
@@ -262,6 +264,9 @@ fn flush_workers(world: &World) -> PadlResult<()> {
 }
 /// Deletes all home hobo entities (lazy, requires world.maintain())
 fn flush_home_hobos(world: &World) -> PadlResult<()> {
+    for nest in (&mut world.write_storage::<town::nests::Nest>()).join() {
+        nest.forget_hobos();
+    }
     let w = world.read_storage::<units::hobos::Hobo>();
     for (entity, _marker) in (&world.entities(), &w).join() {
         world
@@ -1,3 +1,6 @@
+mod bloom;
+mod cheer;
+mod slow;
 mod welcome;
 use crate::gui::{
     gui_components::{UiBox, UiElement},
@@ -6,14 +9,17 @@ use crate::gui::{
 };
 use crate::net::graphql::query_types::parse_timestamp;
 use crate::prelude::*;
+pub use bloom::*;
+pub use cheer::*;
 use chrono::NaiveDateTime;
 use paddlers_shared_lib::prelude::*;
+pub use slow::*;
 pub use welcome::*;
 
 /// A unit can learn a limited number of Abilities. (including walking)
 /// Although this simplifies things on the technical side, this is mainly
 /// motivated from a game-design perspective. (simplicity)
-pub const MAX_ABILITIES: usize = 4;
+pub const MAX_ABILITIES: usize = 5;
 
 /// Represent the abilities a single unit instance has.
 pub struct AbilitySet {
@@ -48,7 +54,8 @@ impl AbilitySet {
             ScreenResolution::Mid => (1, 10.0, 4.0),
             ScreenResolution::Low => (1, 5.0, 2.0),
         };
-        let mut ui = UiBox::new(MAX_ABILITIES / rows, rows, padding, margin);
+        // One additional column for the repeat button
+        let mut ui = UiBox::new(MAX_ABILITIES / rows + 1, rows, padding, margin);
         for i in 0..MAX_ABILITIES {
             let a = self.abilities[i];
             let lu = self.last_used[i];
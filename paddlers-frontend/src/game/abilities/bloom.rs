@@ -0,0 +1,44 @@
+//! Bloom lets flowers around the hero blossom for a while.
+//! The auras of flowers in bloom are stronger on visitors passing by in that time.
+
+use crate::game::components::*;
+use crate::game::fight::Aura;
+use crate::game::town::{TileIndex, Town};
+use crate::logging::error::*;
+use chrono::NaiveDateTime;
+use paddlers_shared_lib::game_mechanics::town::AuraBloom;
+use paddlers_shared_lib::models::AbilityType;
+use specs::prelude::*;
+
+pub fn use_bloom_ability<'a>(
+    user: Entity,
+    user_level: i32,
+    center: TileIndex,
+    start: NaiveDateTime,
+    town: &Town,
+    position: &ReadStorage<'a, Position>,
+    buildings: &ReadStorage<'a, Building>,
+    auras: &mut WriteStorage<'a, Aura>,
+    mana: &mut WriteStorage<Mana>,
+) -> PadlResult<()> {
+    let m = mana
+        .get_mut(user)
+        .ok_or(PadlError::dev_err(PadlErrorCode::MissingComponent(
+            "Mana for worker",
+        )))?;
+
+    let a = AbilityType::Bloom;
+    let strength = a.aura_buff(user_level).unwrap_or(0);
+    m.mana -= a.mana_cost();
+
+    for (p, b, aura) in (position, buildings, auras).join() {
+        let anchor = town.resolution.tile(p.area.pos);
+        if a.affects_building(b.bt, anchor, center) {
+            aura.blooms.push(AuraBloom {
+                start: start.into(),
+                strength,
+            });
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,42 @@
+//! Cheering welcomes all visitors in an area at once.
+//! It is learned by heroes with some experience.
+//! The effect on each visitor is the same as for a welcome.
+
+use crate::game::components::*;
+use crate::game::town::{TileIndex, Town};
+use crate::logging::error::*;
+use paddlers_shared_lib::game_mechanics::town::distance2;
+use paddlers_shared_lib::models::AbilityType;
+use specs::prelude::*;
+
+pub fn use_cheer_ability<'a>(
+    user: Entity,
+    user_level: i32,
+    center: TileIndex,
+    town: &Town,
+    entities: &Entities<'a>,
+    position: &ReadStorage<'a, Position>,
+    health: &mut WriteStorage<'a, Health>,
+    status_effects: &mut WriteStorage<StatusEffects>,
+    mana: &mut WriteStorage<Mana>,
+) -> PadlResult<()> {
+    let m = mana
+        .get_mut(user)
+        .ok_or(PadlError::dev_err(PadlErrorCode::MissingComponent(
+            "Mana for worker",
+        )))?;
+
+    let a = AbilityType::Cheer;
+    let strength = a.apply(user_level).1;
+    let range = a.range();
+    m.mana -= a.mana_cost();
+
+    for (e, p, h, se) in (entities, position, health, status_effects).join() {
+        let tile = town.resolution.tile(p.area.pos);
+        if distance2(center, tile) <= range * range {
+            h.make_happy(strength as i64, e);
+            se.add_health_reduction(strength);
+        }
+    }
+    Ok(())
+}
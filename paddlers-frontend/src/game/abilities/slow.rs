@@ -0,0 +1,52 @@
+//! Slowing down a hurried visitor keeps it longer in range of auras and towers.
+
+use crate::game::components::*;
+use crate::logging::error::*;
+use chrono::NaiveDateTime;
+use paddlers_shared_lib::game_mechanics::town::defence::MIN_SPEED_PERCENT;
+use paddlers_shared_lib::models::AbilityType;
+use specs::prelude::*;
+
+pub fn use_slow_ability<'a>(
+    user: Entity,
+    user_level: i32,
+    target: Entity,
+    now: NaiveDateTime,
+    lazy: &LazyUpdate,
+    status_effects: &mut WriteStorage<StatusEffects>,
+    mana: &mut WriteStorage<Mana>,
+) -> PadlResult<()> {
+    let (se, m) = target_and_user(target, user, status_effects, mana)?;
+
+    let a = AbilityType::Slow;
+    let strength = a.apply(user_level).1;
+    let factor = (100 + strength).max(MIN_SPEED_PERCENT) as f32 / 100.0;
+
+    se.add_speed_change(strength);
+    m.mana -= a.mana_cost();
+    // Movement of the visitor may be borrowed by the caller already
+    lazy.exec_mut(move |world| {
+        if let Some(mov) = world.write_storage::<Moving>().get_mut(target) {
+            mov.change_speed(now, factor);
+        }
+    });
+
+    Ok(())
+}
+
+fn target_and_user<'s, 'm>(
+    target: Entity,
+    user: Entity,
+    status_effects: &'s mut WriteStorage<StatusEffects>,
+    mana: &'m mut WriteStorage<Mana>,
+) -> PadlResult<(&'s mut StatusEffects, &'m mut Mana)> {
+    let se = status_effects.get_mut(target).ok_or(PadlError::dev_err(
+        PadlErrorCode::MissingComponent("Status effects for hobo"),
+    ))?;
+    let m = mana
+        .get_mut(user)
+        .ok_or(PadlError::dev_err(PadlErrorCode::MissingComponent(
+            "Mana for worker",
+        )))?;
+    Ok((se, m))
+}
@@ -10,6 +10,7 @@ use specs::prelude::*;
 
 pub fn use_welcome_ability<'a>(
     user: Entity,
+    user_level: i32,
     target: Entity,
     health: &mut WriteStorage<'a, Health>,
     status_effects: &mut WriteStorage<StatusEffects>,
@@ -30,7 +31,7 @@ pub fn use_welcome_ability<'a>(
         )))?;
 
     let a = AbilityType::Welcome;
-    let strength = a.apply().1;
+    let strength = a.apply(user_level).1;
     let mana_cost = a.mana_cost();
 
     h.make_happy(strength as i64, target);
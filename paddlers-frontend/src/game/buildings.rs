@@ -1,23 +1,35 @@
 use crate::game::town::TownContext;
 use crate::game::{
     components::*,
-    fight::{Aura, Range},
+    fight::{Aura, DefenceTower, Range},
     forestry::ForestComponent,
     input::Clickable,
-    movement::Position,
+    movement::{Moving, Position},
     town::{nests::Nest, TileIndex, Town},
+    town_resources::TownResources,
+    units::workers::Worker,
 };
 use crate::gui::{
     gui_components::{ClickOutput, UiBox, UiElement},
     render::Renderable,
     sprites::*,
+    ui_state::UiState,
     utils::*,
     z::Z_BUILDINGS,
 };
+use crate::net::game_master_api::RestApiState;
+use crate::net::state::current_village;
+use crate::prelude::*;
 use chrono::NaiveDateTime;
+use paddle::quicksilver_compat::Rectangle;
 use paddle::utc_now;
+use paddlers_shared_lib::api::shop::Cost;
+use paddlers_shared_lib::game_mechanics::town::{AuraBloom, PlacementError, Tower, UnitPosition};
 use paddlers_shared_lib::prelude::*;
-use paddlers_shared_lib::{game_mechanics::attributes::Attributes, graphql_types::*};
+use paddlers_shared_lib::{
+    game_mechanics::attributes::{Attributes, LeveledBuilding},
+    graphql_types::*,
+};
 use specs::prelude::*;
 use specs::world::EntitiesRes;
 
@@ -26,6 +38,7 @@ use specs::world::EntitiesRes;
 pub struct Building {
     pub built: NaiveDateTime,
     pub bt: BuildingType,
+    pub level: i32,
 }
 
 impl Town {
@@ -44,6 +57,7 @@ impl Town {
             bt.attack_power(),
             bt.attacks_per_cycle(),
             bt.range(),
+            1,
             utc_now(),
         )
     }
@@ -57,9 +71,16 @@ impl Town {
         ap: Option<i64>,
         attacks_per_cycle: Option<i64>,
         range: Option<f32>,
+        level: i32,
         created: NaiveDateTime,
     ) -> Entity {
-        let area = self.resolution.tile_area(tile_index);
+        let (w, h) = bt.size();
+        let tile_area = self.resolution.tile_area(tile_index);
+        let area = Rectangle::new(
+            tile_area.pos,
+            (tile_area.width() * w as f32, tile_area.height() * h as f32),
+        );
+        let footprint = bt.footprint(tile_index);
         let mut builder = lazy
             .create_entity(entities)
             .with(Position::new(area.pos, area.size, Z_BUILDINGS))
@@ -67,7 +88,11 @@ impl Town {
                 RenderVariant::ImgWithImgBackground(bt.sprite(), SingleSprite::Grass),
                 building_ingame_scaling(bt),
             ))
-            .with(Building { built: created, bt })
+            .with(Building {
+                built: created,
+                bt,
+                level,
+            })
             .with(Clickable);
 
         if let Some(r) = range {
@@ -77,12 +102,17 @@ impl Town {
         // No (None) attacks per cycle && Some ap => Aura effect
         if attacks_per_cycle.is_none() && ap.is_some() {
             if let Some(r) = range {
-                builder = builder.with(Aura::new(r, ap.unwrap(), tile_index, self));
-                if r > self.distance_to_lane(tile_index) {
+                builder = builder.with(Aura::new(r, ap.unwrap(), &footprint, self));
+                if r > self.distance_to_lane(&footprint) {
                     self.total_ambience += ap.unwrap();
                 }
             }
         }
+        // Some attacks per cycle && Some ap => Tower with discrete hits
+        if let (Some(apc), Some(ap), Some(r)) = (attacks_per_cycle, ap, range) {
+            let tower = Tower::new(ap as i32, apc as u32);
+            builder = builder.with(DefenceTower::new(r, tower, &footprint, self));
+        }
 
         match bt {
             BuildingType::BundlingStation => {
@@ -98,18 +128,23 @@ impl Town {
             BuildingType::Tree => {
                 builder = builder.with(ForestComponent::new(created));
             }
-            BuildingType::SingleNest => {
-                builder = builder.with(Nest::new(1));
+            BuildingType::SingleNest | BuildingType::TripleNest => {
+                builder = builder.with(Nest::new(bt.nest_capacity()));
                 builder = builder.with(new_nest_menu());
             }
-            BuildingType::TripleNest => {
-                builder = builder.with(Nest::new(3));
-                builder = builder.with(new_nest_menu());
+            BuildingType::Shack => {
+                builder = builder.with(crate::game::town::new_shack_menu());
+            }
+            _ => {
+                if let Some(menu) = new_upgrade_menu(builder.entity, bt, level) {
+                    builder = builder.with(menu);
+                }
             }
-            _ => {}
         }
 
-        self.place_building(tile_index, bt, builder.entity);
+        if let Err(e) = self.place_building(tile_index, bt, builder.entity) {
+            println!("Warning: {} at {:?} does not fit: {}", bt, tile_index, e);
+        }
 
         let entity = builder.build();
         entity
@@ -125,8 +160,254 @@ fn building_ingame_scaling(b: BuildingType) -> f32 {
     }
 }
 
+/// Menu with a button to upgrade the building, if it has not reached its maximum level yet
+fn new_upgrade_menu(e: Entity, bt: BuildingType, level: i32) -> Option<UiMenu> {
+    let next = LeveledBuilding::new(bt, level).next_level()?;
+    Some(UiMenu::new_shop_menu().with_shop_item(
+        GameEvent::HttpUpgradeBuilding(e),
+        bt.sprite(),
+        next.price(),
+    ))
+}
+
+impl Game<'_, '_> {
+    /// Upgrades the building by one level and applies the new attributes.
+    /// Like placing new buildings, this does not wait for the response of the game master.
+    pub(crate) fn upgrade_building(&mut self, e: Entity) -> PadlResult<()> {
+        let town_world = self.town_world();
+        let mut buildings = town_world.write_storage::<Building>();
+        let building = buildings
+            .get_mut(e)
+            .ok_or_else(|| PadlError::dev_err(PadlErrorCode::MissingComponent("Building")))?;
+        let next = LeveledBuilding::new(building.bt, building.level)
+            .next_level()
+            .ok_or_else(|| {
+                PadlError::dev_err(PadlErrorCode::DevMsg("Building is at maximum level"))
+            })?;
+        let anchor = town_world
+            .read_storage::<Position>()
+            .get(e)
+            .map(|pos| pos.area.pos)
+            .ok_or_else(|| PadlError::dev_err(PadlErrorCode::MissingComponent("Position")))?;
+        let tile = town_world.read_resource::<ScreenResolution>().tile(anchor);
+
+        RestApiState::get().http_upgrade_building(tile, current_village())?;
+        town_world
+            .write_resource::<TownResources>()
+            .spend(&next.price());
+        building.level = next.level;
+
+        let mut town = self.town_mut();
+        let footprint = building.bt.footprint(tile);
+        replace_defence_components(&mut *town, town_world, e, &footprint, &footprint, &next)?;
+
+        let mut menus = town_world.write_storage::<UiMenu>();
+        match new_upgrade_menu(e, next.building_type, next.level) {
+            Some(menu) => {
+                menus.insert(e, menu)?;
+            }
+            None => {
+                menus.remove(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Deletes the building, unless deleting it affects more than the building itself.
+    /// In that case, the player has to confirm the deletion first.
+    pub(crate) fn request_building_deletion(&mut self, e: Entity) -> PadlResult<()> {
+        let town_world = self.town_world();
+        if town_world.read_storage::<Building>().get(e).is_none() {
+            return Ok(());
+        }
+        let occupied_nest = town_world
+            .read_storage::<Nest>()
+            .get(e)
+            .map_or(false, |nest| !nest.hobos().is_empty());
+        if !occupied_nest {
+            return self.delete_building(e);
+        }
+        if let Some(menu) = town_world.write_storage::<UiMenu>().get_mut(e) {
+            let confirmation = ClickOutput::Event(GameEvent::HttpDeleteBuilding(e));
+            if menu.ui.find_by_on_click(confirmation.clone()).is_none() {
+                menu.ui.add(
+                    UiElement::new(confirmation)
+                        .with_text("Delete".to_owned())
+                        .with_background_color(RED),
+                );
+            }
+        }
+        self.confirm_to_user("nest-deletion-confirmation".into())
+    }
+
+    /// Deletes the building without waiting for the response of the game master.
+    /// The game master moves hobos of a deleted nest to other nests, hence they are reloaded afterwards.
+    pub(crate) fn delete_building(&mut self, e: Entity) -> PadlResult<()> {
+        let town_world = self.town_world();
+        {
+            let mut ui_state = town_world.write_resource::<UiState>();
+            if ui_state.selected_entity == Some(e) {
+                ui_state.selected_entity = None;
+            }
+        }
+        let bt = town_world
+            .read_storage::<Building>()
+            .get(e)
+            .map(|building| building.bt)
+            .ok_or_else(|| PadlError::dev_err(PadlErrorCode::MissingComponent("Building")))?;
+        let anchor = town_world
+            .read_storage::<Position>()
+            .get(e)
+            .map(|pos| pos.area.pos)
+            .ok_or_else(|| PadlError::dev_err(PadlErrorCode::MissingComponent("Position")))?;
+        // Buildings are anchored at their top-left tile
+        let tile = town_world.read_resource::<ScreenResolution>().tile(anchor);
+
+        RestApiState::get().http_delete_building(tile, bt, current_village())?;
+
+        let mut town = self.town_mut();
+        // Account for changes in aura total
+        if let (Some(aura), Some(range)) = (
+            town_world.read_storage::<Aura>().get(e),
+            town_world.read_storage::<Range>().get(e),
+        ) {
+            if range.range > town.distance_to_lane(&bt.footprint(tile)) {
+                town.total_ambience -= aura.effect;
+            }
+        }
+        town.remove_building(tile);
+
+        let entities = town_world.entities();
+        if let Some(nest) = town_world.read_storage::<Nest>().get(e) {
+            for hobo in nest.hobos() {
+                entities
+                    .delete(*hobo)
+                    .map_err(|_| PadlError::dev_err(PadlErrorCode::EcsError("Delete hobo")))?;
+            }
+        }
+        entities.delete(e).map_err(|_| {
+            PadlError::dev_err(PadlErrorCode::DevMsg("Tried to delete wrong Generation"))
+        })?;
+        Ok(())
+    }
+
+    /// Moves the building under `start` such that the same part of it ends up under `end`.
+    /// Hobos sitting in a moved nest are taken along.
+    pub(crate) fn relocate_building(&mut self, start: Vector, end: Vector) -> PadlResult<()> {
+        let town_world = self.town_world();
+        let mut town = self.town_mut();
+        let start_tile = town.resolution.tile(start);
+        let from = match town.building_anchor(start_tile) {
+            Some(anchor) => anchor,
+            None => return Ok(()),
+        };
+        let end_tile = town.resolution.tile(end);
+        let to = match (
+            (end_tile.0 + from.0).checked_sub(start_tile.0),
+            (end_tile.1 + from.1).checked_sub(start_tile.1),
+        ) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Err(PlacementError::OutOfBounds.into()),
+        };
+        if to == from {
+            return Ok(());
+        }
+        let entity = town
+            .tile_state(from)
+            .map(|state| state.entity)
+            .ok_or_else(|| PadlError::dev_err(PadlErrorCode::NoStateForTile(from)))?;
+
+        let units: Vec<UnitPosition> = {
+            let workers = town_world.read_storage::<Worker>();
+            let positions = town_world.read_storage::<Position>();
+            let moving = town_world.read_storage::<Moving>();
+            (&workers, &positions, moving.maybe())
+                .join()
+                .map(|(worker, pos, mov)| worker.unit_position(pos, mov, &*town))
+                .collect()
+        };
+        town.move_building(from, to, &units)?;
+        RestApiState::get().http_relocate_building(from, to, current_village())?;
+
+        let new_pos = town.resolution.tile_area(to).pos;
+        let mut positions = town_world.write_storage::<Position>();
+        if let Some(pos) = positions.get_mut(entity) {
+            pos.area.pos = new_pos;
+        }
+        if let Some(nest) = town_world.read_storage::<Nest>().get(entity) {
+            for hobo in nest.hobos() {
+                if let Some(pos) = positions.get_mut(*hobo) {
+                    pos.area.pos = new_pos;
+                }
+            }
+        }
+
+        if let Some(building) = town_world.read_storage::<Building>().get(entity) {
+            let attributes = LeveledBuilding::new(building.bt, building.level);
+            replace_defence_components(
+                &mut *town,
+                town_world,
+                entity,
+                &building.bt.footprint(from),
+                &building.bt.footprint(to),
+                &attributes,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Replaces range, aura and tower of a building that moved from `old_footprint` to `footprint`
+/// and keeps the total ambience of the town in sync.
+/// Follows the same rules as in insert_building.
+fn replace_defence_components(
+    town: &mut Town,
+    world: &World,
+    e: Entity,
+    old_footprint: &[TileIndex],
+    footprint: &[TileIndex],
+    attributes: &impl Attributes,
+) -> PadlResult<()> {
+    let mut ranges = world.write_storage::<Range>();
+    let mut auras = world.write_storage::<Aura>();
+    let mut towers = world.write_storage::<DefenceTower>();
+
+    if let (Some(old_aura), Some(old_range)) = (auras.get(e), ranges.get(e)) {
+        if old_range.range > town.distance_to_lane(old_footprint) {
+            town.total_ambience -= old_aura.effect;
+        }
+    }
+    if let Some(r) = attributes.range() {
+        ranges.insert(e, Range::new(r))?;
+    }
+    match (
+        attributes.range(),
+        attributes.attack_power(),
+        attributes.attacks_per_cycle(),
+    ) {
+        (Some(r), Some(ap), None) => {
+            let mut aura = Aura::new(r, ap, footprint, town);
+            // Blooms belong to the building, wherever it is
+            if let Some(old_aura) = auras.get_mut(e) {
+                aura.blooms = std::mem::take(&mut old_aura.blooms);
+            }
+            auras.insert(e, aura)?;
+            if r > town.distance_to_lane(footprint) {
+                town.total_ambience += ap;
+            }
+        }
+        (Some(r), Some(ap), Some(apc)) => {
+            let tower = Tower::new(ap as i32, apc as u32);
+            towers.insert(e, DefenceTower::new(r, tower, footprint, town))?;
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
 fn new_nest_menu() -> UiMenu {
-    let mut menu = UiBox::new(1, 1, 1.0, 1.0);
+    // Second row is reserved for the deletion confirmation
+    let mut menu = UiBox::new(1, 2, 1.0, 1.0);
     menu.add(
         UiElement::new(ClickOutput::SendInvitation)
             .with_text("Invite".to_owned())
@@ -140,6 +421,15 @@ impl buildings_query::ResponseData {
     pub(crate) fn village_id(&self) -> VillageKey {
         VillageKey(self.village.id)
     }
+    pub(crate) fn town_layout(&self) -> TownLayout {
+        match self.village.town_layout {
+            buildings_query::TownLayout::BASIC => TownLayout::Basic,
+            buildings_query::TownLayout::BEND => TownLayout::Bend,
+            buildings_query::TownLayout::MEANDER => TownLayout::Meander,
+            buildings_query::TownLayout::FORK => TownLayout::Fork,
+            buildings_query::TownLayout::Other(_) => panic!("Unexpected TownLayout"),
+        }
+    }
     pub(crate) fn create_entities(&self, town_context: &mut TownContext) -> Vec<Entity> {
         self.village
             .buildings
@@ -165,6 +455,10 @@ impl buildings_query::BuildingsQueryVillageBuildings {
             buildings_query::BuildingType::TEMPLE => BuildingType::Temple,
             buildings_query::BuildingType::SINGLE_NEST => BuildingType::SingleNest,
             buildings_query::BuildingType::TRIPLE_NEST => BuildingType::TripleNest,
+            buildings_query::BuildingType::FOUNTAIN => BuildingType::Fountain,
+            buildings_query::BuildingType::SPRINKLER => BuildingType::Sprinkler,
+            buildings_query::BuildingType::HARBOUR => BuildingType::Harbour,
+            buildings_query::BuildingType::SHACK => BuildingType::Shack,
             buildings_query::BuildingType::Other(_) => panic!("Unexpected BuildingType"),
         };
         let created = GqlTimestamp::from_string(&self.creation)
@@ -182,8 +476,28 @@ impl buildings_query::BuildingsQueryVillageBuildings {
             maybe_ap,
             self.attacks_per_cycle,
             maybe_range,
+            self.level as i32,
             created,
         );
+        let blooms: Vec<AuraBloom> = self
+            .blooms
+            .iter()
+            .map(|b| AuraBloom {
+                start: GqlTimestamp::from_string(&b.start_time)
+                    .unwrap()
+                    .to_chrono()
+                    .into(),
+                strength: b.strength as i32,
+            })
+            .collect();
+        if !blooms.is_empty() {
+            // The aura is only inserted when the lazy updates are applied
+            lazy.exec_mut(move |world| {
+                if let Some(aura) = world.write_storage::<Aura>().get_mut(entity) {
+                    aura.blooms = blooms;
+                }
+            });
+        }
         if let Ok(id) = self.id.parse() {
             town_context
                 .world()
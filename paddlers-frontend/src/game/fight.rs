@@ -1,6 +1,14 @@
 // use paddle::quicksilver_compat::*;
-use crate::game::{game_event_manager::GameEvent, movement::Position, town::Town};
+use crate::game::{
+    game_event_manager::GameEvent,
+    movement::{Moving, Position},
+    town::{TileIndex, Town},
+};
+use crate::gui::ui_state::Now;
 use crate::prelude::ScreenResolution;
+use chrono::NaiveDateTime;
+use paddlers_shared_lib::game_mechanics::town::{AuraBloom, Tower};
+use paddlers_shared_lib::shared_types::Timestamp;
 use specs::prelude::*;
 use specs::storage::BTreeStorage;
 use specs::world::Index;
@@ -22,16 +30,36 @@ impl Range {
 pub struct Aura {
     pub affected_tiles: Vec<(usize, usize)>,
     pub effect: i64,
+    // Blooms make the effect stronger for a while
+    pub blooms: Vec<AuraBloom>,
 }
 impl Aura {
-    pub fn new(range: f32, ap: i64, tile: (usize, usize), town: &Town) -> Self {
-        let mut tiles = town.lane_in_range(tile, range);
-        if !tiles.is_sorted() {
-            tiles.sort();
-        }
+    pub fn new(range: f32, ap: i64, footprint: &[TileIndex], town: &Town) -> Self {
+        let tiles = town.lane_in_range(footprint, range);
         Aura {
             affected_tiles: tiles,
             effect: ap,
+            blooms: vec![],
+        }
+    }
+    pub fn effect_at(&self, t: Timestamp) -> i64 {
+        self.effect + AuraBloom::boost_at(&self.blooms, t) as i64
+    }
+}
+
+#[derive(Component, Debug)]
+#[storage(HashMapStorage)]
+/// Defender that hits units in range repeatedly, a limited number of times per cycle
+pub struct DefenceTower {
+    pub affected_tiles: Vec<(usize, usize)>,
+    pub tower: Tower,
+}
+impl DefenceTower {
+    pub fn new(range: f32, tower: Tower, footprint: &[TileIndex], town: &Town) -> Self {
+        let tiles = town.lane_in_range(footprint, range);
+        DefenceTower {
+            affected_tiles: tiles,
+            tower,
         }
     }
 }
@@ -43,13 +71,25 @@ pub struct Health {
     pub hp: i64,
     // Used for effects that affect the unit once per defender
     pub aura_effects: Vec<Index>,
+    // Time spent in range of each tower and hits received from it, sorted by tower index
+    pub tower_exposure: Vec<(Index, Timestamp, u32)>,
+    // Until when the tower exposure has been counted
+    pub tower_exposure_until: Option<NaiveDateTime>,
 }
 impl Health {
-    pub fn new(hp: i64, hp_left: i64, aura_effects: Vec<Index>) -> Self {
+    pub fn new(
+        hp: i64,
+        hp_left: i64,
+        aura_effects: Vec<Index>,
+        tower_exposure: Vec<(Index, Timestamp, u32)>,
+        now: NaiveDateTime,
+    ) -> Self {
         Health {
             max_hp: hp,
             hp: hp_left,
             aura_effects,
+            tower_exposure,
+            tower_exposure_until: Some(now),
         }
     }
     #[allow(dead_code)]
@@ -58,6 +98,8 @@ impl Health {
             max_hp: hp,
             hp: hp,
             aura_effects: vec![],
+            tower_exposure: vec![],
+            tower_exposure_until: None,
         }
     }
     pub fn make_happy(&mut self, amount: i64, id: Entity) {
@@ -83,12 +125,18 @@ impl<'a> System<'a> for FightSystem {
     type SystemData = (
         Entities<'a>,
         ReadStorage<'a, Aura>,
+        ReadStorage<'a, DefenceTower>,
+        ReadStorage<'a, Moving>,
         ReadStorage<'a, Position>,
         WriteStorage<'a, Health>,
         Read<'a, ScreenResolution>,
+        Read<'a, Now>,
     );
 
-    fn run(&mut self, (entities, aura, position, mut health, resolution): Self::SystemData) {
+    fn run(
+        &mut self,
+        (entities, aura, towers, moving, position, mut health, resolution, now): Self::SystemData,
+    ) {
         // It's not necessary to recalculate every frame
         self.counter = (self.counter + 1) % 30;
         if self.counter != 1 {
@@ -96,6 +144,7 @@ impl<'a> System<'a> for FightSystem {
         }
 
         let ul = resolution.unit_length();
+        let now = now.0;
 
         // This algorithm runs in O(n*m*(log(m)+log(t))
         // n attacker, m defenders, t tiles
@@ -113,12 +162,45 @@ impl<'a> System<'a> for FightSystem {
                         // log m
                         Ok(_) => { /* Aura already active*/ }
                         Err(i) => {
-                            (*h).make_happy(a.effect, hid);
+                            let effect = a.effect_at(now.into());
+                            (*h).make_happy(effect, hid);
                             (*h).aura_effects.insert(i, aid.id()); // [Theoretically O(m) but not considered above]
                         }
                     }
                 }
             }
         }
+        // Towers hit depending on the time spent in their range since arrival.
+        // The exposure starts from the shared defence model and is counted the same way from then on.
+        // Resting units (not moving) are out of reach for towers.
+        for (hid, p, h, m) in (&entities, &position, &mut health, (&moving).maybe()).join() {
+            let dt = match h.tower_exposure_until.replace(now) {
+                Some(before) if m.is_some() => Timestamp::from(now) - Timestamp::from(before),
+                _ => continue,
+            };
+            let tile = Town::find_tile(p.area.pos, ul);
+            for (tid, t) in (&entities, &towers).join() {
+                if t.affected_tiles.binary_search(&tile).is_err() {
+                    continue;
+                }
+                let i = match h.tower_exposure.binary_search_by_key(&tid.id(), |e| e.0) {
+                    Ok(i) => {
+                        h.tower_exposure[i].1 = h.tower_exposure[i].1 + dt;
+                        i
+                    }
+                    Err(i) => {
+                        h.tower_exposure
+                            .insert(i, (tid.id(), Timestamp::from_us(0), 0));
+                        i
+                    }
+                };
+                let hits = t.tower.hits(h.tower_exposure[i].1);
+                let new_hits = hits.saturating_sub(h.tower_exposure[i].2);
+                if new_hits > 0 {
+                    h.tower_exposure[i].2 = hits;
+                    h.make_happy(t.tower.attack_power as i64 * new_hits as i64, hid);
+                }
+            }
+        }
     }
 }
@@ -1,14 +1,14 @@
 //! The game event manager handles events that are generated by SPECS systems.
 
 use crate::game::{
-    components::*, player_info::PlayerInfo, story::StoryAction, units::attackers::Visitor,
-    units::attackers::*,
+    components::*, player_info::PlayerInfo, story::StoryAction, town::Town,
+    units::attackers::Visitor, units::attackers::*,
 };
 use crate::gui::input::UiView;
 use crate::gui::ui_state::Now;
 use crate::gui::ui_state::UiState;
 use crate::net::game_master_api::RestApiState;
-use crate::net::request_foreign_town;
+use crate::net::{request_client_state, request_foreign_town};
 use crate::prelude::*;
 use paddle::{Domain, NutsCheck};
 use paddlers_shared_lib::api::story::StoryStateTransition;
@@ -29,9 +29,15 @@ pub fn game_event(ev: GameEvent) {
 pub enum GameEvent {
     HoboSatisfied(Entity),
     HttpBuyProphet,
+    HttpBuyWorker,
+    HttpDeleteBuilding(Entity),
+    HttpUpgradeBuilding(Entity),
     LoadHomeVillage,
     LoadVillage(VillageKey),
     SendProphetAttack(VillageCoordinate),
+    SendProphetToRule(VillageCoordinate),
+    ShipResources(VillageKey, ResourceType),
+    SwitchHomeVillage(VillageKey),
     StoryActions(Vec<StoryAction>),
     SwitchToView(UiView),
     DisplayConfirmation(TextKey),
@@ -65,7 +71,8 @@ impl Game<'static, 'static> {
                         if v_store.get(id).is_none() {
                             // hobo currently stopped (in frontend)
                             // => Set it moving again, assuming it has been released by the game-master
-                            let moving = release_and_move_visitor(hobo, resolution, now);
+                            let layout = town_world.fetch::<Town>().layout();
+                            let moving = release_and_move_visitor(hobo, layout, resolution, now);
                             v_store.insert(id, moving)?;
                         }
                         // Tell backend that release might be required
@@ -81,12 +88,35 @@ impl Game<'static, 'static> {
                 let player: PlayerInfo = *self.player().clone();
                 crate::game::town::purchase_prophet(&player)?;
             }
+            GameEvent::HttpBuyWorker => {
+                crate::game::town::purchase_worker()?;
+            }
+            GameEvent::HttpDeleteBuilding(e) => {
+                self.delete_building(e)?;
+            }
+            GameEvent::HttpUpgradeBuilding(e) => {
+                self.upgrade_building(e)?;
+            }
             GameEvent::SendProphetAttack((x, y)) => {
                 self.send_prophet_attack((x, y))?;
                 // TODO: Only confirm if HTTP OK is returned
                 // (Probably do this after cleaning pu network and promise handling)
                 self.confirm_to_user("attack-sent".into())?;
             }
+            GameEvent::SendProphetToRule((x, y)) => {
+                self.send_prophet_to_rule((x, y))?;
+            }
+            GameEvent::ShipResources(vid, resource) => {
+                self.ship_resources(vid, resource)?;
+                self.confirm_to_user("shipment-sent".into())?;
+            }
+            GameEvent::SwitchHomeVillage(vid) => {
+                crate::net::state::set_current_village(vid);
+                self.town_context.switch_home(vid);
+                self.prepare_town_resources();
+                self.switch_view(UiView::Town);
+                request_client_state();
+            }
             GameEvent::SwitchToView(view) => {
                 self.switch_view(view);
             }
@@ -22,12 +22,15 @@ impl Level {
         );
         vec![row]
     }
-    pub fn add_exp(&mut self, n: i32) {
+    /// Returns the number of levels gained
+    pub fn add_exp(&mut self, n: i32) -> i32 {
+        let before = self.lvl;
         self.exp += n;
         while self.exp >= self.exp_to_next_lvl() {
             self.exp -= self.exp_to_next_lvl();
             self.lvl += 1;
         }
+        self.lvl - before
     }
     fn exp_to_next_lvl(&self) -> i32 {
         paddlers_shared_lib::game_mechanics::worker::hero_level_exp(self.lvl)
@@ -7,7 +7,9 @@ use crate::gui::{
     sprites::*,
     utils::*,
 };
-use paddlers_shared_lib::prelude::VillageKey;
+use crate::net::state::current_village;
+use paddlers_shared_lib::prelude::{ResourceType, VillageKey};
+use paddlers_shared_lib::strum::IntoEnumIterator;
 use specs::prelude::*;
 use specs::storage::BTreeStorage;
 
@@ -61,7 +63,7 @@ impl VillageMetaInfo {
     }
     pub fn new_village_menu(&self, owned: bool) -> UiMenu {
         let mut menu = UiMenu {
-            ui: UiBox::new(2, 2, 10.0, 2.0),
+            ui: UiBox::new(3, 2, 10.0, 2.0),
         };
         if !owned {
             menu.ui.add(
@@ -76,6 +78,31 @@ impl VillageMetaInfo {
                 .with_image(SpriteSet::Simple(SingleSprite::Prophet))
                 .with_background_color(RED),
             );
+            if self.player.is_none() {
+                menu.ui.add(
+                    UiElement::new(ClickOutput::Event(GameEvent::SendProphetToRule(
+                        self.coordinates,
+                    )))
+                    .with_text("Convert".to_owned())
+                    .with_background_color(GREEN),
+                );
+            }
+        } else {
+            menu.ui.add(
+                UiElement::new(ClickOutput::Event(GameEvent::SwitchHomeVillage(self.id)))
+                    .with_text("Switch to".to_owned())
+                    .with_background_color(LIGHT_BLUE),
+            );
+        }
+        // Anarchists do not accept shipments
+        if self.player.is_some() && self.id != current_village() {
+            for rt in ResourceType::iter() {
+                menu.ui.add(
+                    UiElement::new(ClickOutput::Event(GameEvent::ShipResources(self.id, rt)))
+                        .with_image(rt.sprite())
+                        .with_background_color(GREEN),
+                );
+            }
         }
         menu
     }
@@ -0,0 +1,263 @@
+use crate::game::Game;
+use crate::gui::utils::colors::DARK_BLUE;
+use crate::gui::z::*;
+use crate::net::game_master_api::RestApiState;
+use crate::net::graphql::query_types::trade_offers_query::TradeOffersQueryTradeOffers;
+use crate::net::graphql::TRADE_OFFERS_PAGE_SIZE;
+use crate::net::state::current_village;
+use crate::net::{request_trade_offers, trade_offers_offset, NetMsg};
+use crate::prelude::*;
+use paddle::quicksilver_compat::{Col, Rectangle, Transform};
+use paddle::Frame;
+use paddle::Window as QuicksilverWindow;
+use paddlers_shared_lib::api::market::*;
+use paddlers_shared_lib::strum::IntoEnumIterator;
+use specs::WorldExt;
+use std::marker::PhantomData;
+use stdweb::unstable::TryInto;
+use stdweb::web::event;
+use stdweb::web::html_element::{InputElement, SelectElement};
+use stdweb::web::*;
+
+/// Lists open offers of all players on the marketplace, page by page, and lets the player post new offers
+pub(crate) struct MarketFrame<'a, 'b> {
+    pane: panes::PaneHandle,
+    table: Node,
+    page_label: Element,
+    previous: Element,
+    next: Element,
+    phantom: PhantomData<(&'a (), &'b ())>,
+}
+
+impl MarketFrame<'_, '_> {
+    pub fn new(area: &Rectangle) -> PadlResult<Self> {
+        let options: String = ResourceType::iter()
+            .map(|rt| format!(r#"<option value="{0}">{0}</option>"#, rt))
+            .collect();
+        let html = format!(
+            r#"<section class="market">
+                <div class="market-form">
+                    <input id="market-offered-amount" type="number" min="1" value="10">
+                    <select id="market-offered-resource">{0}</select>
+                    <div>for</div>
+                    <input id="market-requested-amount" type="number" min="1" value="10">
+                    <select id="market-requested-resource">{0}</select>
+                    <div id="market-post" class="letter-button">Post offer</div>
+                </div>
+                <div class="market-offers"></div>
+                <div class="market-pages">
+                    <div id="market-previous" class="letter-button">Previous</div>
+                    <div id="market-page"></div>
+                    <div id="market-next" class="letter-button">Next</div>
+                </div>
+            </section>"#,
+            options
+        );
+        let pane = panes::new_styled_pane(
+            area.x() as u32,
+            area.y() as u32,
+            area.width() as u32,
+            area.height() as u32,
+            &html,
+            &[""],
+            &[("color", "white")],
+        )?;
+        let table = document()
+            .query_selector("section.market div.market-offers")
+            .ok()
+            .flatten()
+            .ok_or(PadlError::dev_err(PadlErrorCode::InvalidDom(
+                "Market table missing",
+            )))?;
+        let post = market_element("market-post")?;
+        let page_label = market_element("market-page")?;
+        let previous = market_element("market-previous")?;
+        let next = market_element("market-next")?;
+        post.add_event_listener::<event::ClickEvent, _>(move |_| {
+            let result =
+                read_offer_form().and_then(|msg| RestApiState::get().http_create_trade_offer(msg));
+            if let Err(e) = result {
+                nuts::publish(e);
+            }
+        });
+
+        previous.add_event_listener::<event::ClickEvent, _>(move |_| {
+            request_trade_offers(trade_offers_offset().saturating_sub(TRADE_OFFERS_PAGE_SIZE));
+        });
+        next.add_event_listener::<event::ClickEvent, _>(move |_| {
+            request_trade_offers(trade_offers_offset() + TRADE_OFFERS_PAGE_SIZE);
+        });
+
+        pane.hide()?;
+
+        Ok(MarketFrame {
+            pane,
+            table: table.into(),
+            page_label,
+            previous,
+            next,
+            phantom: PhantomData,
+        })
+    }
+
+    pub fn network_message(
+        &mut self,
+        _state: &mut Game<'static, 'static>,
+        msg: &NetMsg,
+    ) -> Result<(), PadlError> {
+        match msg {
+            NetMsg::TradeOffers(offset, list) => {
+                self.table.remove_all_children();
+                for offer in list {
+                    self.insert_row(offer)?;
+                }
+                self.update_pages(*offset, list.len());
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Shows the current page and only the buttons that lead to other pages
+    fn update_pages(&self, offset: usize, offers_on_page: usize) {
+        self.page_label
+            .set_text_content(&format!("Page {}", offset / TRADE_OFFERS_PAGE_SIZE + 1));
+        set_enabled(&self.previous, offset > 0);
+        // A full page means there could be more offers
+        set_enabled(&self.next, offers_on_page == TRADE_OFFERS_PAGE_SIZE);
+    }
+
+    fn insert_row(&self, offer: &TradeOffersQueryTradeOffers) -> PadlResult<()> {
+        let offered: ResourceType = (&offer.offered_resource).into();
+        let requested: ResourceType = (&offer.requested_resource).into();
+        let id = TradeOfferKey(offer.id.parse().map_err(|_| {
+            PadlError::dev_err(PadlErrorCode::InvalidGraphQLData("trade offer id"))
+        })?);
+
+        let row = document().create_element("div").unwrap();
+        row.set_attribute("class", "market-row").unwrap();
+
+        let description = document().create_element("div").unwrap();
+        description.set_text_content(&format!(
+            "{} {} for {} {}",
+            offer.offered_amount, offered, offer.requested_amount, requested
+        ));
+        row.append_child(&description);
+
+        let button = document().create_element("div").unwrap();
+        button.set_attribute("class", "letter-button").unwrap();
+        let own = offer.village.id == current_village().num();
+        button.set_text_content(if own { "Cancel" } else { "Accept" });
+        let table = self.table.clone();
+        let row_handle = row.clone();
+        button.add_event_listener::<event::ClickEvent, _>(move |_| {
+            if table.remove_child(&row_handle).is_ok() {
+                let result = if own {
+                    RestApiState::get()
+                        .http_cancel_trade_offer(TradeOfferCancellation { offer: id })
+                } else {
+                    RestApiState::get().http_accept_trade_offer(TradeOfferAcceptance {
+                        offer: id,
+                        village: current_village(),
+                    })
+                };
+                if let Err(e) = result {
+                    nuts::publish(e);
+                }
+            }
+        });
+        row.append_child(&button);
+
+        self.table.append_child(&row);
+        Ok(())
+    }
+}
+
+fn read_offer_form() -> PadlResult<TradeOfferCreation> {
+    Ok(TradeOfferCreation {
+        village: current_village(),
+        offered_resource: read_resource("market-offered-resource")?,
+        offered_amount: read_amount("market-offered-amount")?,
+        requested_resource: read_resource("market-requested-resource")?,
+        requested_amount: read_amount("market-requested-amount")?,
+    })
+}
+
+fn read_amount(id: &str) -> PadlResult<i64> {
+    let input: InputElement = form_element(id)?;
+    input
+        .raw_value()
+        .parse::<i64>()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or(PadlError::user_err(PadlErrorCode::InvalidAmount))
+}
+
+fn read_resource(id: &str) -> PadlResult<ResourceType> {
+    let select: SelectElement = form_element(id)?;
+    let value = select.value().unwrap_or_default();
+    ResourceType::iter()
+        .find(|rt| rt.to_string() == value)
+        .ok_or(PadlError::dev_err(PadlErrorCode::InvalidDom(
+            "Unknown resource in market form",
+        )))
+}
+
+fn set_enabled(button: &Element, enabled: bool) {
+    if enabled {
+        button.class_list().remove("disabled").unwrap();
+    } else {
+        button.class_list().add("disabled").unwrap();
+    }
+}
+
+fn market_element(id: &str) -> PadlResult<Element> {
+    document()
+        .get_element_by_id(id)
+        .ok_or(PadlError::dev_err(PadlErrorCode::InvalidDom(
+            "Market element missing",
+        )))
+}
+
+fn form_element<T: stdweb::InstanceOf + stdweb::ReferenceType>(id: &str) -> PadlResult<T> {
+    document()
+        .get_element_by_id(id)
+        .and_then(|el| el.try_into().ok())
+        .ok_or(PadlError::dev_err(PadlErrorCode::InvalidDom(
+            "Market form element missing",
+        )))
+}
+
+impl<'a, 'b> Frame for MarketFrame<'a, 'b> {
+    type Error = PadlError;
+    type State = Game<'a, 'b>;
+    type Graphics = QuicksilverWindow;
+
+    fn draw(
+        &mut self,
+        state: &mut Self::State,
+        window: &mut Self::Graphics,
+    ) -> Result<(), Self::Error> {
+        let ui_state = state.world.read_resource::<ViewState>();
+        let main_area = Rectangle::new(
+            (0, 0),
+            (
+                ui_state.menu_box_area.x(),
+                (window.project() * window.screen_size()).y,
+            ),
+        );
+        std::mem::drop(ui_state);
+        window.draw_ex(&main_area, Col(DARK_BLUE), Transform::IDENTITY, Z_TEXTURE);
+        Ok(())
+    }
+    fn enter(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        // Open the page that was shown the last time
+        request_trade_offers(trade_offers_offset());
+        self.pane.show()?;
+        Ok(())
+    }
+    fn leave(&mut self, _state: &mut Self::State) -> Result<(), Self::Error> {
+        self.pane.hide()?;
+        Ok(())
+    }
+}
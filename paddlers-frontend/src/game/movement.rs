@@ -1,5 +1,5 @@
 use crate::game::Now;
-use chrono::NaiveDateTime;
+use chrono::{Duration, NaiveDateTime};
 use paddle::quicksilver_compat::{about_equal, Rectangle, Vector};
 use specs::prelude::*;
 use specs::storage::BTreeStorage;
@@ -20,6 +20,8 @@ pub struct Moving {
     // Speed: f32 in pixels per second
     pub momentum: Vector,
     pub max_speed: f32,
+    /// Points where the direction changes, in the order they are reached
+    pub waypoints: Vec<Vector>,
 }
 
 #[derive(Component, Debug)]
@@ -41,6 +43,7 @@ impl<'a> System<'a> for MoveSystem {
     fn run(&mut self, (entities, t, mut vel, mut pos, mut target_pos): Self::SystemData) {
         let mut remove_from_vel = vec![];
         for (e, v, pos) in (&entities, &mut vel, &mut pos).join() {
+            v.follow_waypoints(t.0);
             let mut new_pos = v.position(t.0);
             if let Some(target) = target_pos.get(e) {
                 // test if target position is reached or crossed
@@ -81,16 +84,60 @@ impl Moving {
             start_pos: start,
             momentum: v,
             max_speed: max_speed,
+            waypoints: vec![],
+        }
+    }
+    /// Moves along the way points, at max speed, and continues in the last direction after the final way point.
+    pub fn with_waypoints(mut self, waypoints: Vec<Vector>) -> Self {
+        if let Some(first) = waypoints.first() {
+            let dir = *first - self.start_pos;
+            if dir.len() > 0.0 {
+                self.momentum = dir.normalize() * self.max_speed;
+            }
+        }
+        self.waypoints = waypoints;
+        self
+    }
+    /// Turns towards the next way point for all way points reached until t
+    pub fn follow_waypoints(&mut self, t: NaiveDateTime) {
+        let speed = self.momentum.len();
+        if speed == 0.0 {
+            return;
+        }
+        while let Some(next) = self.waypoints.first().copied() {
+            let seconds = (next - self.start_pos).len() / speed;
+            let reached = self.start_ts + Duration::microseconds((seconds * 1_000_000.0) as i64);
+            if reached > t {
+                break;
+            }
+            self.waypoints.remove(0);
+            self.start_pos = next;
+            self.start_ts = reached;
+            if let Some(after) = self.waypoints.first() {
+                let dir = *after - next;
+                if dir.len() > 0.0 {
+                    self.momentum = dir.normalize() * speed;
+                }
+            }
         }
     }
     pub fn position(&self, t: NaiveDateTime) -> Vector {
         self.start_pos
             + self.momentum * (t - self.start_ts).num_microseconds().unwrap() as f32 / 1_000_000
     }
+    /// Keeps going the same way, with the speed multiplied by the factor from t onwards
+    pub fn change_speed(&mut self, t: NaiveDateTime, factor: f32) {
+        self.follow_waypoints(t);
+        self.start_pos = self.position(t);
+        self.start_ts = t;
+        self.momentum = self.momentum * factor;
+        self.max_speed *= factor;
+    }
     pub fn stand_still(&mut self, timestamp: NaiveDateTime) {
         self.start_pos = self.position(timestamp);
         self.start_ts = timestamp;
         self.momentum = (0.0, 0.0).into();
+        self.waypoints.clear();
     }
 }
 
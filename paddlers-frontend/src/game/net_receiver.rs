@@ -1,6 +1,7 @@
 use crate::game::town::TownContext;
 use crate::game::town_resources::TownResources;
 use crate::game::units::hobos::insert_hobos;
+use crate::game::visits::attacks::OutgoingAttacks;
 use crate::game::{
     components::*, units::worker_factory::create_worker_entities, units::workers::Worker,
 };
@@ -15,11 +16,17 @@ use crate::prelude::*;
 use paddle::LoadScheduler;
 use paddlers_shared_lib::prelude::*;
 use std::convert::TryInto;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::TryRecvError;
 
 use super::*;
 use specs::prelude::*;
 
+/// Workers requested again in a row because the response did not contain a level up yet.
+/// Limited, in case the server does not grant the level up at all.
+static OUTDATED_WORKER_RESPONSES: AtomicUsize = AtomicUsize::new(0);
+const MAX_OUTDATED_WORKER_RESPONSES: usize = 100;
+
 impl LoadingState {
     pub fn update_net(&mut self, progress: &mut LoadScheduler) -> PadlResult<()> {
         match self.base.net_chan.try_recv() {
@@ -92,6 +99,9 @@ impl Game<'static, 'static> {
                     NetMsg::Leaderboard(offset, list) => {
                         paddle::share(NetMsg::Leaderboard(offset, list));
                     }
+                    NetMsg::TradeOffers(offset, list) => {
+                        paddle::share(NetMsg::TradeOffers(offset, list));
+                    }
                     NetMsg::Map(response, min, max) => {
                         if let Some(data) = response.data {
                             let streams = data
@@ -126,6 +136,15 @@ impl Game<'static, 'static> {
                     }
                     NetMsg::Workers(response, vid) => {
                         let ctx = self.maybe_town_context_mut(vid, "workers")?;
+                        // Outdated workers would undo a level up shown already, ask again until the server has it
+                        if misses_level_ups(ctx.world(), &response)
+                            && OUTDATED_WORKER_RESPONSES.fetch_add(1, Ordering::Relaxed)
+                                < MAX_OUTDATED_WORKER_RESPONSES
+                        {
+                            crate::net::request_workers_update();
+                            return Ok(());
+                        }
+                        OUTDATED_WORKER_RESPONSES.store(0, Ordering::Relaxed);
                         flush_workers(ctx.world())?;
                         ctx.world_mut().maintain();
                         load_workers_from_net_response(ctx, response);
@@ -178,6 +197,7 @@ impl<'a, 'b> Game<'a, 'b> {
                 let world = ctx.world_mut();
                 flush_buildings(world)?;
                 world.maintain();
+                ctx.town_mut().set_layout(data.town_layout());
                 data.create_entities(self.town_context.active_context_mut());
             } else {
                 return PadlErrorCode::DataForInactiveTownReceived("buildings").dev();
@@ -192,6 +212,7 @@ impl<'a, 'b> Game<'a, 'b> {
             for atk in data.village.attacks {
                 atk.create_entities(self)?;
             }
+            paddle::share(OutgoingAttacks(data.village.outgoing_attacks));
         }
         Ok(())
     }
@@ -234,6 +255,18 @@ pub fn load_workers_from_net_response(ctx: &mut TownContext, response: WorkerRes
         }
     }
 }
+/// Whether a worker in the response has a lower level than reached in the frontend.
+/// Level ups are granted by the server only when it finishes the task that led to them.
+fn misses_level_ups(world: &World, response: &WorkerResponse) -> bool {
+    let workers = world.read_storage::<Worker>();
+    let levels = world.read_storage::<Level>();
+    (&workers, &levels).join().any(|(worker, level)| {
+        response
+            .iter()
+            .filter(|w| w.id.parse() == Ok(worker.netid))
+            .any(|w| (w.level as i32) < level.lvl)
+    })
+}
 /// Home hobos (not attackers) are loaded
 pub fn load_hobos_from_net_response(
     ctx: &mut TownContext,
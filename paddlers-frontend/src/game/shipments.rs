@@ -0,0 +1,31 @@
+//! Sending resources by boat to other villages
+
+use crate::game::town_resources::TownResources;
+use crate::net::game_master_api::RestApiState;
+use crate::net::state::current_village;
+use crate::prelude::*;
+use paddlers_shared_lib::api::shipments::ShipmentOrder;
+use paddlers_shared_lib::api::shop::Price;
+use paddlers_shared_lib::prelude::*;
+
+/// Resources loaded on a boat with one click
+pub const SHIPMENT_AMOUNT: i64 = 10;
+
+impl Game<'_, '_> {
+    /// Loads resources of the current village on a boat to the destination village
+    pub fn ship_resources(&mut self, to: VillageKey, resource: ResourceType) -> PadlResult<()> {
+        let price = Price(vec![(resource, SHIPMENT_AMOUNT)]);
+        let home = self.town_context.home_world();
+        if !home.fetch::<TownResources>().can_afford(&price) {
+            return PadlErrorCode::NotEnoughResources.usr();
+        }
+        RestApiState::get().http_ship_resources(ShipmentOrder {
+            from: current_village(),
+            to,
+            resource,
+            amount: SHIPMENT_AMOUNT,
+        })?;
+        home.fetch_mut::<TownResources>().spend(&price);
+        Ok(())
+    }
+}
@@ -3,7 +3,7 @@ pub use crate::gui::{
     gui_components::*,
     input::Clickable,
     render::Renderable,
-    sprites::{SingleSprite, SpriteSet},
+    sprites::{SingleSprite, SpriteSet, WithSprite},
     utils::*,
 };
 use crate::net::graphql::query_types::HoboEffect;
@@ -16,6 +16,7 @@ use specs::storage::BTreeStorage;
 #[storage(BTreeStorage)]
 pub struct StatusEffects {
     health: Option<StatusEffect>,
+    speed: Option<StatusEffect>,
 }
 
 /// An effect to be displayed on a selected entity.
@@ -29,7 +30,10 @@ struct StatusEffect {
 
 impl StatusEffects {
     pub fn new() -> Self {
-        StatusEffects { health: None }
+        StatusEffects {
+            health: None,
+            speed: None,
+        }
     }
     pub fn from_gql_query(effects: &[HoboEffect]) -> PadlResult<Self> {
         let mut status = Self::new();
@@ -41,8 +45,11 @@ impl StatusEffects {
                     ))?;
                     status.add_health_reduction(strength as i32);
                 }
-                _ => {
-                    return PadlErrorCode::InvalidGraphQLData("Unknown Effect").dev();
+                HoboAttributeType::Speed => {
+                    let strength = ef.strength.ok_or(PadlError::dev_err(
+                        PadlErrorCode::InvalidGraphQLData("Speed effect without strength"),
+                    ))?;
+                    status.add_speed_change(strength as i32);
                 }
             }
         }
@@ -55,10 +62,20 @@ impl StatusEffects {
             self.health.as_mut().unwrap().value += v;
         }
     }
+    /// Speed change in percent
+    pub fn add_speed_change(&mut self, v: i32) {
+        if self.speed.is_none() {
+            self.speed = Some(StatusEffect::new_speed_change(v));
+        } else {
+            self.speed.as_mut().unwrap().value += v;
+        }
+    }
     pub fn menu_table_infos<'a>(&self) -> Vec<TableRow<'a>> {
         let mut rows = vec![];
-        if let Some(h) = &self.health {
-            rows.push(h.details());
+        for effect in &[&self.health, &self.speed] {
+            if let Some(e) = effect {
+                rows.push(e.details());
+            }
         }
         rows
     }
@@ -72,6 +89,12 @@ impl StatusEffect {
             value: init_val,
         }
     }
+    fn new_speed_change(init_val: i32) -> Self {
+        StatusEffect {
+            img: AbilityType::Slow.sprite(),
+            value: init_val,
+        }
+    }
     fn details<'a>(&self) -> TableRow<'a> {
         let text = if self.value >= 0 {
             format!("{}{}", "+", self.value)
@@ -7,10 +7,12 @@ pub mod town_input;
 pub mod town_render;
 
 mod default_shop;
+mod shack_shop;
 mod temple_shop;
 mod town_context;
 mod town_frame;
 pub use default_shop::*;
+pub(crate) use shack_shop::*;
 pub(crate) use temple_shop::*;
 pub(crate) use town_context::*;
 pub(crate) use town_frame::*;
@@ -27,6 +29,7 @@ pub type TileState = TileStateEx<specs::Entity>;
 
 pub struct Town {
     map: TownMap,
+    layout: TownLayout,
     state: TownState<specs::Entity>,
     pub resolution: ScreenResolution,
     // Could possibly be added to TownState, depends on further developments of the backend.
@@ -40,9 +43,11 @@ const Y: usize = TOWN_Y;
 
 impl Town {
     pub fn new(resolution: ScreenResolution) -> Self {
-        let map = TownMap::new(TownLayout::Basic);
+        let layout = TownLayout::Basic;
+        let map = TownMap::new(layout);
         Town {
             map: map,
+            layout,
             state: TownState::new(),
             resolution: resolution,
             total_ambience: 0,
@@ -51,6 +56,19 @@ impl Town {
         }
     }
 
+    pub fn layout(&self) -> TownLayout {
+        self.layout
+    }
+    /// Changes where the river flows through the town.
+    /// Buildings have to be inserted again afterwards.
+    pub fn set_layout(&mut self, layout: TownLayout) {
+        if self.layout != layout {
+            self.layout = layout;
+            self.map = TownMap::new(layout);
+            self.state = TownState::new();
+        }
+    }
+
     pub fn forest_size(&self) -> usize {
         self.state.forest_size
     }
@@ -66,8 +84,20 @@ impl Town {
     pub fn ambience(&self) -> i64 {
         self.total_ambience
     }
-    pub fn distance_to_lane(&self, i: TileIndex) -> f32 {
-        self.map.distance_to_lane(i)
+    /// Distance from the closest tile of a building footprint to the lane
+    pub fn distance_to_lane(&self, footprint: &[TileIndex]) -> f32 {
+        self.map.footprint_distance_to_lane(footprint)
+    }
+    /// How many unhurried visitors can be resting in the town, based on the buildings in it
+    pub fn visitor_queue_capacity(&self) -> usize {
+        let buildings =
+            self.state
+                .building_anchors()
+                .filter_map(|anchor| match &self.map[*anchor] {
+                    TileType::BUILDING(bt) => Some(bt),
+                    _ => None,
+                });
+        visitor_queue_capacity(self.layout, buildings)
     }
 
     #[allow(dead_code)]
@@ -85,13 +115,15 @@ impl Town {
         self.state.register_task_end(task).map_err(PadlError::from)
     }
 
-    pub fn get_buildable_tile(&self, pos: impl Into<Vector>) -> Option<TileIndex> {
-        let (x, y) = self.resolution.tile(pos);
-        if self.is_buildable((x, y)) {
-            Some((x, y))
-        } else {
-            None
-        }
+    /// The tile where a building of the given type would be anchored, if it can be placed at the position
+    pub fn get_buildable_tile(
+        &self,
+        pos: impl Into<Vector>,
+        bt: BuildingType,
+        units: &[UnitPosition],
+    ) -> Result<TileIndex, PlacementError> {
+        let anchor = self.resolution.tile(pos);
+        validate_building_placement(&self.map, self.layout, units, bt, anchor).map(|_| anchor)
     }
     fn tiles_in_rectified_circle(tile: TileIndex, radius: f32) -> Vec<TileIndex> {
         let r = radius.ceil() as usize;
@@ -117,27 +149,62 @@ impl Town {
         }
         tiles
     }
-    pub fn lane_in_range(&self, pos: TileIndex, range: f32) -> Vec<TileIndex> {
-        Self::tiles_in_rectified_circle(pos, range)
+    /// All tiles in range of any tile in the footprint, sorted
+    pub(crate) fn tiles_in_range_of_footprint(
+        footprint: &[TileIndex],
+        radius: f32,
+    ) -> Vec<TileIndex> {
+        let mut tiles: Vec<TileIndex> = footprint
+            .iter()
+            .flat_map(|tile| Self::tiles_in_rectified_circle(*tile, radius))
+            .collect();
+        tiles.sort();
+        tiles.dedup();
+        tiles
+    }
+    pub fn lane_in_range(&self, footprint: &[TileIndex], range: f32) -> Vec<TileIndex> {
+        Self::tiles_in_range_of_footprint(footprint, range)
             .into_iter()
             .filter(|xy| self.map[*xy] == TileType::LANE)
             .collect()
     }
 
-    pub fn place_building(&mut self, i: TileIndex, bt: BuildingType, id: specs::Entity) {
-        debug_assert!(self.is_buildable(i), "Cannot build here");
-        let tile = self.map.tile_type_mut(i);
-
-        debug_assert!(tile.is_some(), "Tile is outside of map");
-        *tile.unwrap() = TileType::BUILDING(bt);
+    pub fn place_building(
+        &mut self,
+        i: TileIndex,
+        bt: BuildingType,
+        id: specs::Entity,
+    ) -> PadlResult<()> {
+        self.map.insert_building(bt, i)?;
         let state = TileState::new_building(id, bt.capacity(), 0);
-        self.state.insert(i, state);
+        self.state.insert(&bt.footprint(i), state);
+        Ok(())
     }
+    /// Removes the building covering the tile
     pub fn remove_building(&mut self, i: TileIndex) -> specs::Entity {
-        let tile = self.map.tile_type_mut(i);
-        *tile.unwrap() = TileType::EMPTY;
+        for tile in self.state.footprint(&i) {
+            if let Some(t) = self.map.tile_type_mut(tile) {
+                *t = TileType::EMPTY;
+            }
+        }
         self.state.remove(&i).entity
     }
+    /// Moves a building to a new anchor tile, if the building fits there
+    pub fn move_building(
+        &mut self,
+        from: TileIndex,
+        to: TileIndex,
+        units: &[UnitPosition],
+    ) -> PadlResult<()> {
+        let bt = self.building_type(from)?;
+        validate_building_relocation(&self.map, &self.state, self.layout, units, bt, from, to)?;
+        let entity = self.remove_building(from);
+        self.place_building(to, bt, entity)
+    }
+    /// The tile where the building covering the given tile is anchored
+    pub fn building_anchor(&self, i: TileIndex) -> Option<TileIndex> {
+        self.state.anchor(&i)
+    }
     pub fn building_type(&self, i: TileIndex) -> PadlResult<BuildingType> {
         match self.map.tile_type(i) {
             Some(TileType::BUILDING(b)) => Ok(*b),
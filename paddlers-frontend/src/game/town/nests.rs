@@ -20,9 +20,16 @@ impl Nest {
             capacity,
         }
     }
+    pub fn hobos(&self) -> &[Entity] {
+        &self.hobos
+    }
     pub fn add_hobo(&mut self, hobo: Entity) {
         self.hobos.push(hobo);
     }
+    /// Empties the nest without deleting the hobo entities
+    pub fn forget_hobos(&mut self) {
+        self.hobos.clear();
+    }
     pub fn clear_hobos(&mut self, lazy: &LazyUpdate) -> PadlResult<()> {
         if self.hobos.len() == 0 {
             PadlErrorCode::NestEmpty.usr()
@@ -0,0 +1,21 @@
+use crate::game::{components::UiMenu, game_event_manager::*};
+use crate::gui::sprites::{SingleSprite, SpriteSet};
+use crate::net::game_master_api::RestApiState;
+use crate::net::state::current_village;
+use crate::prelude::*;
+use paddlers_shared_lib::api::shop::{Cost, WorkerPurchase};
+
+pub fn new_shack_menu() -> UiMenu {
+    UiMenu::new_shop_menu().with_shop_item(
+        GameEvent::HttpBuyWorker,
+        SpriteSet::Simple(SingleSprite::Duck),
+        UnitType::Basic.price(),
+    )
+}
+
+/// Housing limits are checked by the game master, which responds with an error if all shacks are full
+pub fn purchase_worker() -> PadlResult<()> {
+    RestApiState::get().http_buy_worker(WorkerPurchase {
+        village: current_village(),
+    })
+}
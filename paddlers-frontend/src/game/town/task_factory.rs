@@ -45,8 +45,14 @@ impl Town {
                     }
                 }
             }
-            TaskType::WelcomeAbility => {
-                if mana.map(|o| o.mana).unwrap_or(0) < AbilityType::Welcome.mana_cost() {
+            TaskType::WelcomeAbility
+            | TaskType::CheerAbility
+            | TaskType::SlowAbility
+            | TaskType::BloomAbility => {
+                let cost = AbilityType::from_task(&job.0)
+                    .map(|a| a.mana_cost())
+                    .unwrap_or(0);
+                if mana.map(|o| o.mana).unwrap_or(0) < cost {
                     return PadlErrorCode::NotEnoughMana.usr();
                 }
             }
@@ -106,7 +112,11 @@ fn raw_job_execution_tasks(job: &NewTaskDescriptor, place: TileIndex) -> Vec<Raw
         TaskType::ChopTree | TaskType::GatherSticks | TaskType::Idle | TaskType::Walk => {
             // NOP
         }
-        TaskType::CollectReward | TaskType::WelcomeAbility => {
+        TaskType::CollectReward
+        | TaskType::WelcomeAbility
+        | TaskType::CheerAbility
+        | TaskType::SlowAbility
+        | TaskType::BloomAbility => {
             tasks.push(RawTask::new(TaskType::Idle, place));
         }
         TaskType::Defend => {
@@ -48,13 +48,6 @@ impl Town {
         dx * dx + dy * dy <= range * range
     }
 
-    pub(super) fn is_buildable(&self, index: TileIndex) -> bool {
-        let maybe_tile = self.map.tile_type(index);
-        if maybe_tile.is_none() {
-            return false;
-        }
-        maybe_tile.unwrap().is_buildable()
-    }
     pub(super) fn is_walkable(&self, index: TileIndex) -> bool {
         let maybe_tile = self.map.tile_type(index);
         if maybe_tile.is_none() {
@@ -6,7 +6,7 @@ use specs::prelude::*;
 
 /// Orchestrates TownContexts to be displayed.
 ///
-/// At the moment, only the main town of the player currently logged in and one foreign town are stored.
+/// At the moment, only the currently selected town of the player logged in and one foreign town are stored.
 /// In the future, this could also hold a cache of several villages to ensure quick loading when clicking through many towns and going back to previously visited ones.
 pub struct TownContextManager {
     home_town: TownContext,
@@ -35,6 +35,15 @@ impl TownContextManager {
         let player_info = *home_data.fetch::<PlayerInfo>();
         self.foreign_town = Some(TownContext::new(resolution, player_info, v));
     }
+    /// Replace the home town with another village of the player.
+    /// The new town context starts empty and is filled once the network responses for it arrive.
+    pub fn switch_home(&mut self, v: VillageKey) {
+        let home_data = self.home_town.world();
+        let resolution = *home_data.fetch::<ScreenResolution>();
+        let player_info = *home_data.fetch::<PlayerInfo>();
+        self.home_town = TownContext::new(resolution, player_info, v);
+        self.foreign_town = None;
+    }
     /// Remove all loaded foreign towns from the view and display home again
     pub fn reset_to_home(&mut self) {
         self.foreign_town = None;
@@ -1,6 +1,6 @@
 use super::*;
 use crate::game::buildings::Building;
-use crate::game::fight::Aura;
+use crate::game::fight::{Aura, DefenceTower};
 use crate::game::visits::attacks::Attack;
 use crate::net::graphql::attacks_query::{AttacksQueryVillageAttacksUnits, HoboAttributeType};
 use paddlers_shared_lib::game_mechanics::town::*;
@@ -32,6 +32,9 @@ impl<'a> IAttackingHobo for AttackingHobo<'a> {
             .as_ref()
             .map(|t| GqlTimestamp::from_string(&t).unwrap().to_chrono().into())
     }
+    fn resting_spot(&self) -> usize {
+        self.unit.info.resting_spot as usize
+    }
     fn effects_strength(&self) -> i32 {
         self.unit
             .hobo
@@ -41,10 +44,27 @@ impl<'a> IAttackingHobo for AttackingHobo<'a> {
             .filter(|e| e.strength.is_some())
             .fold(0, |acc, e| acc + e.strength.unwrap() as i64) as i32
     }
+    fn timed_effects(&self) -> Vec<TimedEffect> {
+        self.unit
+            .hobo
+            .effects
+            .iter()
+            .filter(|e| e.attribute != HoboAttributeType::HEALTH)
+            .filter_map(|e| {
+                Some(TimedEffect {
+                    attribute: (&e.attribute).into(),
+                    strength: e.strength? as i32,
+                    start: GqlTimestamp::from_string(&e.start_time)?.to_chrono().into(),
+                })
+            })
+            .collect()
+    }
 }
 
 impl<'a, 'b> ITownLayoutMarker for Game<'a, 'b> {
-    const LAYOUT: TownLayout = TownLayout::Basic;
+    fn town_layout(&self) -> TownLayout {
+        self.town().layout()
+    }
 }
 impl<'a, 'b> IDefendingTown for Game<'a, 'b> {
     type AuraId = u32;
@@ -61,10 +81,27 @@ impl<'a, 'b> IDefendingTown for Game<'a, 'b> {
             }
             for tile in &aura.affected_tiles {
                 if tile.0 == index.0 && tile.1 == index.1 {
-                    out.push((e.id(), aura.effect as i32))
+                    out.push((e.id(), aura.effect_at(time) as i32))
                 }
             }
         }
         out
     }
+    fn towers_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, Tower)> {
+        let mut out = vec![];
+
+        let world = self.town_world();
+        let towers = world.read_component::<DefenceTower>();
+        let buildings = world.read_component::<Building>();
+        let entities = world.entities();
+        for (tower, e, b) in (&towers, &entities, &buildings).join() {
+            if time < b.built.into() {
+                continue;
+            }
+            if tower.affected_tiles.binary_search(index).is_ok() {
+                out.push((e.id(), tower.tower))
+            }
+        }
+        out
+    }
 }
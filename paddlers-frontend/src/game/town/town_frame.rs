@@ -12,6 +12,7 @@ use crate::prelude::*;
 use paddle::quicksilver_compat::graphics::Mesh;
 use paddle::quicksilver_compat::{MouseButton, Shape, Vector};
 use paddle::{Frame, Window};
+use paddlers_shared_lib::models::TownLayout;
 use specs::prelude::*;
 use std::ops::Deref;
 
@@ -20,6 +21,7 @@ pub(crate) struct TownFrame<'a, 'b> {
     town_dispatcher: Dispatcher<'a, 'b>,
     // Graphics optimization
     pub background_cache: Option<Mesh>,
+    background_layout: TownLayout,
 }
 
 impl<'a, 'b> Frame for TownFrame<'a, 'b> {
@@ -48,8 +50,9 @@ impl<'a, 'b> Frame for TownFrame<'a, 'b> {
             let tick = state.world.read_resource::<ClockTick>().0;
             let asset = &mut state.sprites;
             let town = state.town_context.town_mut();
-            if self.background_cache.is_none() {
+            if self.background_cache.is_none() || self.background_layout != town.layout() {
                 self.background_cache = Some(Mesh::new());
+                self.background_layout = town.layout();
                 town.render_background(self.background_cache.as_mut().unwrap(), asset, ul)?;
             }
             self.background_cache.as_ref().unwrap().vertices.len();
@@ -154,6 +157,7 @@ impl<'a, 'b> TownFrame<'a, 'b> {
         TownFrame {
             left_click_dispatcher,
             background_cache: None,
+            background_layout: TownLayout::default(),
             town_dispatcher,
         }
     }
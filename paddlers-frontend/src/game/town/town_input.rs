@@ -27,6 +27,7 @@ impl Town {
         mouse_pos: Vector,
         mut ui_state: WriteExpect<'a, UiState>,
         position: ReadStorage<'a, Position>,
+        moving: ReadStorage<'a, Moving>,
         netids: ReadStorage<'a, NetObj>,
         mut workers: WriteStorage<'a, Worker>,
         mut containers: WriteStorage<'a, EntityContainer>,
@@ -80,6 +81,28 @@ impl Town {
                     nuts::publish(PadlError::user_err(PadlErrorCode::NestEmpty));
                 }
             }
+            Some((ClickOutput::RepeatTasks, _)) => {
+                if let Some(worker) = workers.get_mut(menu_entity) {
+                    let start = position
+                        .get(menu_entity)
+                        .and_then(|from| {
+                            let movement = moving.get(menu_entity)?;
+                            Some(
+                                self.resolution
+                                    .next_tile_in_direction(from.area.pos, movement.momentum),
+                            )
+                        })
+                        .ok_or(PadlError::dev_err(PadlErrorCode::MissingComponent(
+                            "Position",
+                        )));
+                    start
+                        .and_then(|start| worker.toggle_task_loop(start, self))
+                        .unwrap_or_else(nuts::publish);
+                    if let Some(el) = ui_menu.ui.find_by_on_click(ClickOutput::RepeatTasks) {
+                        *el = repeat_button(worker.task_loop.is_some());
+                    }
+                }
+            }
             Some((ClickOutput::Event(e), _)) => {
                 // These events COULD be handled here since we have access to most of the game state here
                 // However, if an Evnet is modeled as a GameEvent, then the logic to handle it belongs
@@ -120,6 +143,8 @@ impl Town {
         ui_state: &mut WriteExpect<'a, UiState>,
         position: &ReadStorage<'a, Position>,
         clickable: &ReadStorage<'a, Clickable>,
+        moving: &ReadStorage<'a, Moving>,
+        workers: &WriteStorage<'a, Worker>,
         net_ids: &ReadStorage<'a, NetObj>,
         lazy: &Read<'a, LazyUpdate>,
         resources: &mut WriteExpect<'a, TownResources>,
@@ -128,7 +153,11 @@ impl Town {
         if let Some(grabbed) = ui_state.take_grabbed_item() {
             match grabbed {
                 Grabbable::NewBuilding(bt) => {
-                    if let Some(pos) = self.get_buildable_tile(mouse_pos) {
+                    let units: Vec<_> = (workers, position, moving.maybe())
+                        .join()
+                        .map(|(w, pos, mov)| w.unit_position(pos, mov, self))
+                        .collect();
+                    if let Ok(pos) = self.get_buildable_tile(mouse_pos, bt, &units) {
                         RestApiState::get()
                             .http_place_building(pos, bt, current_village())
                             .unwrap_or_else(nuts::publish);
@@ -143,17 +172,20 @@ impl Town {
                 Grabbable::Ability(a) => {
                     let target = maybe_top_hit.and_then(|e| net_ids.get(e)).map(|n| n.id);
                     match a {
-                        AbilityType::Welcome => {
+                        AbilityType::Work => {
+                            let job = TaskType::Walk; // TODO: find right job
+                            return Some((job, None));
+                        }
+                        _ if a.area_of_effect() => {
+                            return a.task().map(|job| (job, None));
+                        }
+                        _ => {
                             if target.is_some() {
-                                return Some((TaskType::WelcomeAbility, target));
+                                return a.task().map(|job| (job, target));
                             } else {
                                 return None;
                             }
                         }
-                        AbilityType::Work => {
-                            let job = TaskType::Walk; // TODO: find right job
-                            return Some((job, None));
-                        }
                     }
                 }
             }
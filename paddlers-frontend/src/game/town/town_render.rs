@@ -58,7 +58,7 @@ impl Town {
                             t,
                             Z_TEXTURE,
                         );
-                        // XXX: Hack only works for basic map
+                        // All layouts leave the town on the left border
                         if x == 0 {
                             let x = -1;
                             window.draw_ex(
@@ -68,24 +68,31 @@ impl Town {
                                 Z_TEXTURE,
                             );
                         }
-                        let grass_top_img =
-                            &sprites.index(SpriteIndex::Simple(SingleSprite::GrassTop));
-                        let h = d / grass_top_img.area().width() * grass_top_img.area().height();
-                        window.draw_ex(
-                            &Rectangle::new((d * x as f32, d * y as f32 + d - h), (d, h)),
-                            Img(grass_top_img),
-                            Transform::IDENTITY,
-                            Z_VISITOR + 1, // This should be above visitors
-                        );
-                        let grass_bot_img =
-                            &sprites.index(SpriteIndex::Simple(SingleSprite::GrassBot));
-                        let h = d / grass_bot_img.area().width() * grass_bot_img.area().height();
-                        window.draw_ex(
-                            &Rectangle::new((d * x as f32, d * y as f32), (d, h)),
-                            Img(grass_bot_img),
-                            Transform::IDENTITY,
-                            Z_TEXTURE + 1,
-                        );
+                        // River banks, only where the lane does not continue vertically
+                        if !self.is_lane((x, y + 1)) {
+                            let grass_top_img =
+                                &sprites.index(SpriteIndex::Simple(SingleSprite::GrassTop));
+                            let h =
+                                d / grass_top_img.area().width() * grass_top_img.area().height();
+                            window.draw_ex(
+                                &Rectangle::new((d * x as f32, d * y as f32 + d - h), (d, h)),
+                                Img(grass_top_img),
+                                Transform::IDENTITY,
+                                Z_VISITOR + 1, // This should be above visitors
+                            );
+                        }
+                        if y == 0 || !self.is_lane((x, y - 1)) {
+                            let grass_bot_img =
+                                &sprites.index(SpriteIndex::Simple(SingleSprite::GrassBot));
+                            let h =
+                                d / grass_bot_img.area().width() * grass_bot_img.area().height();
+                            window.draw_ex(
+                                &Rectangle::new((d * x as f32, d * y as f32), (d, h)),
+                                Img(grass_bot_img),
+                                Transform::IDENTITY,
+                                Z_TEXTURE + 1,
+                            );
+                        }
                     }
                 }
             }
@@ -93,14 +100,25 @@ impl Town {
         Ok(())
     }
 
+    fn is_lane(&self, i: TileIndex) -> bool {
+        self.map.tile_type(i) == Some(&TileType::LANE)
+    }
+
+    /// Shadows all tiles in range of the tiles covered by the area
     pub fn shadow_rectified_circle(
         resolution: ScreenResolution,
         window: &mut Window,
-        center: impl Into<Vector>,
+        area: &Rectangle,
         radius: f32,
     ) {
-        let tile = resolution.tile(center);
-        for (x, y) in Town::tiles_in_rectified_circle(tile, radius) {
+        let ul = resolution.unit_length();
+        let (x0, y0) = resolution.tile(area.pos);
+        let w = ((area.width() / ul).round() as usize).max(1);
+        let h = ((area.height() / ul).round() as usize).max(1);
+        let footprint: Vec<TileIndex> = (y0..y0 + h)
+            .flat_map(|y| (x0..x0 + w).map(move |x| (x, y)))
+            .collect();
+        for (x, y) in Town::tiles_in_range_of_footprint(&footprint, radius) {
             Self::shadow_tile(resolution, window, (x, y));
         }
     }
@@ -4,13 +4,20 @@ use paddlers_shared_lib::api::shop::*;
 
 #[derive(Default, Debug, Clone, Copy)]
 pub struct TownResources {
+    stock: ResourceAmounts,
+    /// Resources currently shipped to the town
+    incoming: ResourceAmounts,
+}
+
+#[derive(Default, Debug, Clone, Copy)]
+struct ResourceAmounts {
     feathers: i64,
     sticks: i64,
     logs: i64,
 }
 
-impl TownResources {
-    pub fn read(&self, rt: ResourceType) -> i64 {
+impl ResourceAmounts {
+    fn read(&self, rt: ResourceType) -> i64 {
         match rt {
             ResourceType::Feathers => self.feathers,
             ResourceType::Sticks => self.sticks,
@@ -24,18 +31,38 @@ impl TownResources {
             ResourceType::Logs => &mut self.logs,
         }
     }
-    pub fn update(&mut self, data: volatile_village_info_query::ResponseData) {
-        self.feathers = data.village.feathers;
-        self.sticks = data.village.sticks;
-        self.logs = data.village.logs;
-    }
-    pub fn non_zero_resources(&self) -> Vec<(ResourceType, i64)> {
+    fn non_zero(&self) -> Vec<(ResourceType, i64)> {
         use paddlers_shared_lib::strum::IntoEnumIterator;
         ResourceType::iter()
             .map(|rt| (rt, self.read(rt)))
             .filter(|t| t.1 > 0)
             .collect()
     }
+}
+
+impl TownResources {
+    pub fn read(&self, rt: ResourceType) -> i64 {
+        self.stock.read(rt)
+    }
+    fn write(&mut self, rt: ResourceType) -> &mut i64 {
+        self.stock.write(rt)
+    }
+    pub fn update(&mut self, data: volatile_village_info_query::ResponseData) {
+        self.stock.feathers = data.village.feathers;
+        self.stock.sticks = data.village.sticks;
+        self.stock.logs = data.village.logs;
+        self.incoming = ResourceAmounts::default();
+        for shipment in &data.village.incoming_shipments {
+            *self.incoming.write((&shipment.resource_type).into()) += shipment.amount;
+        }
+    }
+    pub fn non_zero_resources(&self) -> Vec<(ResourceType, i64)> {
+        self.stock.non_zero()
+    }
+    /// Sum of all resources that are on their way to the town
+    pub fn incoming_resources(&self) -> Vec<(ResourceType, i64)> {
+        self.incoming.non_zero()
+    }
     fn spend_res(&mut self, rt: ResourceType, amount: i64) {
         *self.write(rt) -= amount;
     }
@@ -19,6 +19,7 @@ use crate::prelude::*;
 use paddle::quicksilver_compat::Vector;
 use paddlers_shared_lib::game_mechanics::town::*;
 use paddlers_shared_lib::graphql_types::*;
+use paddlers_shared_lib::shared_types::Timestamp;
 use specs::prelude::*;
 
 const ATTACKER_SIZE_FACTOR_X: f32 = 0.6;
@@ -32,6 +33,8 @@ pub struct Visitor {
     pub speed: f32,
     pub arrival: NaiveDateTime,
     pub rank_offset: usize,
+    /// Where the visitor rests, unless it is hurried
+    pub resting_spot: usize,
 }
 
 #[cfg(feature = "dev_view")]
@@ -67,6 +70,7 @@ pub fn insert_duck(
         effects,
         false,
         0,
+        0,
     )
     .map(specs::EntityBuilder::build)
 }
@@ -83,6 +87,7 @@ pub fn build_new_duck_entity<'a>(
     effects: &[HoboEffect],
     hurried: bool,
     rank_offset: usize,
+    resting_spot: usize,
 ) -> PadlResult<specs::EntityBuilder<'a>> {
     let size: Vector = Vector::new(ATTACKER_SIZE_FACTOR_X * ul, ATTACKER_SIZE_FACTOR_Y * ul).into();
     let status_effects = StatusEffects::from_gql_query(effects)?;
@@ -102,6 +107,7 @@ pub fn build_new_duck_entity<'a>(
             speed,
             arrival,
             rank_offset,
+            resting_spot,
         })
         .with(hp);
 
@@ -119,6 +125,7 @@ impl AttacksQueryVillageAttacks {
             .unwrap()
             .to_chrono();
         let now = game.world.fetch::<Now>().0;
+        let layout = game.town().layout();
 
         let description = self
             .attacker
@@ -133,13 +140,16 @@ impl AttacksQueryVillageAttacks {
         for (i, unit) in self.units.into_iter().enumerate() {
             let unit_rep = AttackingHobo { unit, attack: &atk };
             let effects = game.touched_auras(&unit_rep, now.into());
+            let towers = game.tower_exposure(&unit_rep, now.into());
             let builder = unit_rep.create_entity(
                 game.town_context.home_world_mut().create_entity(),
                 now,
                 birth_time,
                 i,
                 ul,
+                layout,
                 effects,
+                towers,
             )?;
             out.push(builder.build());
         }
@@ -157,13 +167,17 @@ impl<'a> AttackingHobo<'a> {
         birth: NaiveDateTime,
         pos_rank: usize,
         ul: f32,
+        layout: TownLayout,
         auras: Vec<(<Game<'_, '_> as IDefendingTown>::AuraId, i32)>,
+        towers: Vec<(<Game<'_, '_> as IDefendingTown>::AuraId, Tower, Timestamp)>,
     ) -> PadlResult<specs::EntityBuilder<'a>> {
         let v = self.unit.hobo.speed as f32 * ul;
-        let w = TOWN_X as f32 * ul;
-        let x = w - ul * ATTACKER_SIZE_FACTOR_X;
-        let y = TOWN_LANE_Y as f32 * ul;
-        let mut pos = Vector::new(x, y) + attacker_position_rank_offset(pos_rank, ul);
+        let spot = self.resting_spot();
+        let to_rest = lane_positions(layout.path_to_rest_place(spot), ul, pos_rank);
+        let from_rest = lane_positions(layout.path_from_rest_place(spot), ul, pos_rank);
+        let rest_pos = *to_rest.last().unwrap();
+        let mut pos = to_rest[0];
+        let mut waypoints: Vec<Vector> = to_rest[1..].iter().chain(&from_rest).cloned().collect();
         let mut t0 = birth;
         let hp = self.unit.hobo.hp;
         let netid = self.unit.hobo.id.parse().expect("Parsing id");
@@ -174,20 +188,32 @@ impl<'a> AttackingHobo<'a> {
             .as_ref()
             .map(|c| c.into())
             .unwrap_or(UnitColor::Yellow);
-        let time_until_resting = self.time_until_resting().as_duration();
+        let arrival: Timestamp = birth.into();
+        let resting_distance = layout.path_to_rest_place(spot).len() as f32;
+        let time_until_resting =
+            (self.walking_time(arrival, resting_distance) - arrival).as_duration();
 
         // Simulate all interactions with buildings for the visitor which happened in the past
-        let dmg = <Game<'_, '_> as IDefendingTown>::damage(&auras) + self.effects_strength();
+        let tower_dmg = towers.iter().fold(0, |acc, (_, tower, exposure)| {
+            acc + tower.attack_power * tower.hits(*exposure) as i32
+        });
+        let dmg =
+            <Game<'_, '_> as IDefendingTown>::damage(&auras) + tower_dmg + self.effects_strength();
         let hp_left = (hp - dmg as i64).max(0);
         let aura_ids = auras.into_iter().map(|a| a.0).collect();
-        let health = Health::new(hp, hp_left, aura_ids);
+        let tower_exposure = towers
+            .into_iter()
+            .map(|(id, tower, exposure)| (id, exposure, tower.hits(exposure)))
+            .collect();
+        let health = Health::new(hp, hp_left, aura_ids, tower_exposure, now);
 
         // Adapt position for units that have been resting and were then released
         if let Some(released) = &self.unit.info.released {
             let released = GqlTimestamp::from_string(released).unwrap().to_chrono();
             if released > birth + time_until_resting {
-                pos.x = TOWN_RESTING_X as f32 * ul;
+                pos = rest_pos;
                 t0 = released;
+                waypoints = from_rest;
             }
         }
 
@@ -195,13 +221,23 @@ impl<'a> AttackingHobo<'a> {
         let can_rest = !self.unit.hobo.hurried && self.unit.info.released.is_none();
         let resting = can_rest && birth + time_until_resting <= now;
         if !resting {
-            builder = builder.with(Moving::new(t0, pos, Vector::new(-v, 0.0), v));
             if can_rest {
-                let final_pos = Vector::new(TOWN_RESTING_X as f32 * ul, pos.y);
-                builder = builder.with(TargetPosition::new(final_pos));
+                waypoints = to_rest[1..].to_vec();
+                builder = builder.with(TargetPosition::new(rest_pos));
+            }
+            let mut moving =
+                Moving::new(t0, pos, Vector::new(-v, 0.0), v).with_waypoints(waypoints);
+            // Replay speed changes caused by abilities
+            let mut speed = self.speed_at(t0.into());
+            moving.change_speed(t0, speed / self.speed());
+            for t in self.speed_changes_after(t0.into()) {
+                let new_speed = self.speed_at(t);
+                moving.change_speed(t.into(), new_speed / speed);
+                speed = new_speed;
             }
+            builder = builder.with(moving);
         } else {
-            pos.x = TOWN_RESTING_X as f32 * ul;
+            pos = rest_pos;
         }
 
         build_new_duck_entity(
@@ -216,10 +252,24 @@ impl<'a> AttackingHobo<'a> {
             &self.unit.hobo.effects,
             self.unit.hobo.hurried,
             pos_rank,
+            spot,
         )
     }
 }
 
+/// Position of a visitor when it enters a tile of the lane
+pub(crate) fn lane_position(tile: TileIndex, ul: f32, pos_rank: usize) -> Vector {
+    let x = tile.0 as f32 * ul + (1.0 - ATTACKER_SIZE_FACTOR_X) * ul;
+    let y = tile.1 as f32 * ul;
+    Vector::new(x, y) + attacker_position_rank_offset(pos_rank, ul)
+}
+fn lane_positions(tiles: &[TileIndex], ul: f32, pos_rank: usize) -> Vec<Vector> {
+    tiles
+        .iter()
+        .map(|tile| lane_position(*tile, ul, pos_rank))
+        .collect()
+}
+
 fn attacker_position_rank_offset(pr: usize, ul: f32) -> Vector {
     let y = if pr % 2 == 1 { ul * 0.5 } else { 0.0 };
     let x = ul * 0.3 * pr as f32;
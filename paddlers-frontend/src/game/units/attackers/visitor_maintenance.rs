@@ -29,6 +29,7 @@ pub fn change_duck_sprite_to_happy(r: &mut Renderable) {
 /// Set visitor moving again (Without server communication)
 pub fn release_and_move_visitor(
     visitor: &Visitor,
+    layout: TownLayout,
     resolution: ScreenResolution,
     now: Now,
 ) -> Moving {
@@ -36,11 +37,17 @@ pub fn release_and_move_visitor(
     let now = now.0;
     let speed = visitor.speed;
     let momentum = Vector::new(-speed, 0.0);
-    let x = TOWN_RESTING_X as f32 * ul;
-    let y =
-        TOWN_LANE_Y as f32 * ul + super::attacker_position_rank_offset(visitor.rank_offset, ul).y;
-    let pos = Vector::new(x, y);
-    Moving::new(now, pos, momentum, speed)
+    let pos = super::lane_position(
+        layout.rest_place(visitor.resting_spot),
+        ul,
+        visitor.rank_offset,
+    );
+    let waypoints = layout
+        .path_from_rest_place(visitor.resting_spot)
+        .iter()
+        .map(|tile| super::lane_position(*tile, ul, visitor.rank_offset))
+        .collect();
+    Moving::new(now, pos, momentum, speed).with_waypoints(waypoints)
 }
 
 impl<'a, 'b> Game<'a, 'b> {
@@ -53,19 +60,21 @@ impl<'a, 'b> Game<'a, 'b> {
         let entities = town_world.entities();
         let now = self.world.fetch::<Now>().0;
         let ul = self.world.fetch::<ScreenResolution>().unit_length();
+        let layout = self.town().layout();
 
         let mut resting_visitors = vec![];
         for (visitor, hp, e, pos) in (&visitors, &hps, &entities, &positions).join() {
             if !visitor.hurried
                 && visitor.arrival <= now
                 && hp.hp > 0
-                && pos.area.pos.x >= TOWN_RESTING_X as f32 * ul
+                && pos.area.pos.x >= layout.rest_place(visitor.resting_spot).0 as f32 * ul
             {
                 resting_visitors.push((visitor, e));
             }
         }
 
-        let to_release = resting_visitors.len().saturating_sub(MAX_VISITOR_QUEUE);
+        let capacity = self.town().visitor_queue_capacity();
+        let to_release = resting_visitors.len().saturating_sub(capacity);
         if to_release > 0 {
             let world = self.town_world();
             let now = *world.fetch::<Now>();
@@ -73,7 +82,10 @@ impl<'a, 'b> Game<'a, 'b> {
             let mut mov = world.write_component::<Moving>();
             resting_visitors.sort_by(|a, b| a.0.arrival.partial_cmp(&b.0.arrival).unwrap());
             for (visitor, e) in &resting_visitors[0..to_release] {
-                mov.insert(*e, release_and_move_visitor(visitor, resolution, now))?;
+                mov.insert(
+                    *e,
+                    release_and_move_visitor(visitor, layout, resolution, now),
+                )?;
             }
         }
         Ok(())
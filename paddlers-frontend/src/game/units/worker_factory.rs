@@ -34,7 +34,10 @@ pub fn with_unit_base<B: Builder>(
         })
         .with(Level { lvl, exp });
     if let Some(m) = mana {
-        builder = builder.with(Mana { mana: m as i32 });
+        builder = builder.with(Mana {
+            mana: m as i32,
+            max_mana: hero_max_mana(lvl),
+        });
     }
     builder
 }
@@ -54,9 +57,9 @@ pub fn with_abilities<B: Builder>(
     abilities: AbilitySet,
     resolution: ScreenResolution,
 ) -> B {
-    builder.with(UiMenu {
-        ui: abilities.construct_ui_box(resolution),
-    })
+    let mut ui = abilities.construct_ui_box(resolution);
+    ui.add(repeat_button(false));
+    builder.with(UiMenu { ui })
 }
 
 pub fn with_basic_worker<B: Builder>(builder: B, color: UnitColor) -> B {
@@ -83,6 +86,7 @@ pub fn with_worker<B: Builder, T: IntoIterator<Item = WorkerTask>>(
     builder.with(Worker {
         tasks: worker_tasks,
         netid: netid,
+        task_loop: None,
     })
 }
 
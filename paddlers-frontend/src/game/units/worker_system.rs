@@ -1,6 +1,7 @@
 use crate::game::{
-    abilities::use_welcome_ability,
+    abilities::*,
     components::*,
+    fight::Aura,
     movement::Moving,
     town::{TileIndex, Town},
     units::workers::*,
@@ -36,6 +37,9 @@ impl<'a> System<'a> for WorkerSystem {
         WriteStorage<'a, Mana>,
         WriteStorage<'a, Level>,
         ReadStorage<'a, Renderable>,
+        ReadStorage<'a, Position>,
+        ReadStorage<'a, Building>,
+        WriteStorage<'a, Aura>,
         WriteExpect<'a, Town>,
         ReadExpect<'a, Now>,
     );
@@ -55,6 +59,9 @@ impl<'a> System<'a> for WorkerSystem {
             mut mana,
             mut levels,
             rend,
+            position,
+            buildings,
+            mut auras,
             mut town,
             now,
         ): Self::SystemData,
@@ -95,21 +102,62 @@ impl<'a> System<'a> for WorkerSystem {
                             task.position,
                         );
                     }
-                    TaskType::WelcomeAbility => {
+                    TaskType::WelcomeAbility
+                    | TaskType::CheerAbility
+                    | TaskType::SlowAbility
+                    | TaskType::BloomAbility => {
                         mov.stand_still(task.start_time);
                         anim.direction = Direction::Undirected;
-                        let err = use_welcome_ability(
-                            e,
-                            task.target.expect("Welcoming required target"),
-                            &mut health,
-                            &mut status_effects,
-                            &mut mana,
-                        );
+                        let level = levels.get(e).map(|l| l.lvl).unwrap_or(1);
+                        let ability =
+                            AbilityType::from_task(&task.task_type).expect("Ability task");
+                        let err = match ability {
+                            AbilityType::Welcome => use_welcome_ability(
+                                e,
+                                level,
+                                task.target.expect("Welcoming required target"),
+                                &mut health,
+                                &mut status_effects,
+                                &mut mana,
+                            ),
+                            AbilityType::Cheer => use_cheer_ability(
+                                e,
+                                level,
+                                task.position,
+                                &town,
+                                &entities,
+                                &position,
+                                &mut health,
+                                &mut status_effects,
+                                &mut mana,
+                            ),
+                            AbilityType::Slow => use_slow_ability(
+                                e,
+                                level,
+                                task.target.expect("Slowing down required target"),
+                                task.start_time,
+                                &lazy,
+                                &mut status_effects,
+                                &mut mana,
+                            ),
+                            AbilityType::Bloom => use_bloom_ability(
+                                e,
+                                level,
+                                task.position,
+                                task.start_time,
+                                &town,
+                                &position,
+                                &buildings,
+                                &mut auras,
+                                &mut mana,
+                            ),
+                            AbilityType::Work => unreachable!(),
+                        };
                         if let Err(e) = err {
                             nuts::publish(e);
                         } else {
                             let ui = ui_menus.get_mut(e).expect("Ui menu vanished");
-                            update_cooldown(&mut *ui, AbilityType::Welcome, now.0);
+                            update_cooldown(&mut *ui, ability, now.0);
                         }
                     }
                     TaskType::CollectReward => {
@@ -149,8 +197,11 @@ fn collect_reward(
         "No experience pool given to add to",
     )))?;
     if let Some(reward) = bt.reward_exp() {
-        level.add_exp(reward);
+        if level.add_exp(reward) > 0 {
+            // Stats and abilities of the new level are granted by the server when it finishes the task.
+            // Workers are reloaded until the response contains the new level.
+            crate::net::request_workers_update();
+        }
     }
     Ok(())
-    // TODO: Level up
 }
@@ -1,15 +1,18 @@
 use crate::game::{
     components::*,
-    movement::Position,
+    movement::{Moving, Position},
     town::{task_factory::NewTaskDescriptor, TileIndex, Town},
 };
+use crate::gui::gui_components::{ClickOutput, UiElement};
 use crate::gui::render::Renderable;
+use crate::gui::utils::colors::LIGHT_BLUE;
 use crate::gui::z::*;
 use crate::net::game_master_api::RestApiState;
 use crate::prelude::*;
 use chrono::NaiveDateTime;
 use paddle::quicksilver_compat::*;
 use paddlers_shared_lib::api::tasks::*;
+use paddlers_shared_lib::game_mechanics::town::UnitPosition;
 use paddlers_shared_lib::prelude::WorkerKey;
 use specs::prelude::*;
 use std::collections::VecDeque;
@@ -21,8 +24,13 @@ use std::collections::VecDeque;
 pub struct Worker {
     pub tasks: VecDeque<WorkerTask>,
     pub netid: i64,
+    /// Orders recorded while the player defines a list of tasks to be repeated
+    pub task_loop: Option<Vec<(NewTaskDescriptor, TileIndex)>>,
 }
 
+/// How long a worker stays at a job before moving on, when the job is part of repeating tasks
+const REPEATED_JOB_DURATION_S: u32 = 3600;
+
 #[derive(Debug)]
 pub struct WorkerTask {
     pub task_type: TaskType,
@@ -58,6 +66,12 @@ impl Worker {
         containers: &mut WriteStorage<'a, EntityContainer>,
         mana: &ReadStorage<'a, Mana>,
     ) {
+        if self.task_loop.is_some() {
+            if let Err(e) = self.record_repeating_order(job, destination, town, containers) {
+                nuts::publish(e);
+            }
+            return;
+        }
         let msg =
             self.try_create_task_list(entity, start, destination, job, &town, containers, mana);
         match msg {
@@ -91,16 +105,69 @@ impl Worker {
         let msg = TaskList {
             worker_id: self.key(),
             tasks: tasks,
+            repeat: false,
         };
         Ok(msg)
     }
+    fn record_repeating_order<'a>(
+        &mut self,
+        job: NewTaskDescriptor,
+        destination: TileIndex,
+        town: &Town,
+        containers: &mut WriteStorage<'a, EntityContainer>,
+    ) -> PadlResult<()> {
+        if !job.0.repeatable() || job.1.is_some() {
+            return PadlErrorCode::NotRepeatable.usr();
+        }
+        town.check_task_constraints(job, destination, containers, None)?;
+        if let Some(orders) = &mut self.task_loop {
+            orders.push((job, destination));
+        }
+        Ok(())
+    }
+    /// Starts recording orders to be repeated.
+    /// If orders are already being recorded, the worker is sent to perform them in a loop,
+    /// starting at and returning to the given tile.
+    pub fn toggle_task_loop(&mut self, start: TileIndex, town: &Town) -> PadlResult<()> {
+        match self.task_loop.take() {
+            None => {
+                self.task_loop = Some(vec![]);
+            }
+            Some(orders) => {
+                if orders.is_empty() {
+                    return Ok(());
+                }
+                let mut tasks = vec![];
+                let mut from = start;
+                for (job, destination) in orders {
+                    let mut chain = town.build_task_chain(from, destination, &job)?;
+                    if job.0 != TaskType::Idle {
+                        if let Some(job_task) = chain.pop() {
+                            chain.push(job_task.with_duration(REPEATED_JOB_DURATION_S));
+                        }
+                    }
+                    tasks.append(&mut chain);
+                    from = destination;
+                }
+                tasks.append(&mut town.build_task_chain(from, start, &(TaskType::Idle, None))?);
+                RestApiState::get().http_overwrite_tasks(TaskList {
+                    worker_id: self.key(),
+                    tasks,
+                    repeat: true,
+                })?;
+            }
+        }
+        Ok(())
+    }
     /// Finds the default-task that is performed on a right click in the town area
     pub fn task_on_right_click<'a>(
         &mut self,
         click: &Vector,
         town: &Town,
     ) -> Option<(TaskType, TileIndex)> {
-        let destination = town.resolution.tile(*click); // TODO: destination is not always where it has been clicked
+        let clicked = town.resolution.tile(*click);
+        // Tasks in buildings always target the anchor tile of the building
+        let destination = town.building_anchor(clicked).unwrap_or(clicked);
         let job = town
             .available_tasks(destination)
             .into_iter()
@@ -110,11 +177,28 @@ impl Worker {
             .next()?;
         Some((job, destination))
     }
+    /// Where the worker stands and walks, for validating building placement
+    pub fn unit_position(
+        &self,
+        position: &Position,
+        moving: Option<&Moving>,
+        town: &Town,
+    ) -> UnitPosition {
+        let tile = town.resolution.tile(position.area.center());
+        let path: Vec<TileIndex> = moving
+            .into_iter()
+            .flat_map(|m| m.waypoints.iter())
+            .map(|v| town.resolution.tile(*v))
+            .chain(self.tasks.iter().map(|t| t.position))
+            .collect();
+        UnitPosition::walking(tile, path)
+    }
     fn go_idle(&mut self, idx: TileIndex) -> Result<TaskList, String> {
         let tasks = vec![RawTask::new(TaskType::Idle, idx)];
         Ok(TaskList {
             worker_id: self.key(),
             tasks: tasks,
+            repeat: false,
         })
     }
 
@@ -128,6 +212,14 @@ impl Worker {
     }
 }
 
+/// Button in the worker menu to start and finish recording repeating tasks
+pub fn repeat_button(recording: bool) -> UiElement {
+    let text = if recording { "Confirm" } else { "Repeat" };
+    UiElement::new(ClickOutput::RepeatTasks)
+        .with_text(text.to_owned())
+        .with_background_color(LIGHT_BLUE)
+}
+
 pub fn move_worker_into_building<'a>(
     containers: &mut WriteStorage<'a, EntityContainer>,
     ui_menus: &mut WriteStorage<'a, UiMenu>,
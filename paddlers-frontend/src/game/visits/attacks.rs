@@ -3,16 +3,19 @@
 use crate::gui::utils::colors::LIGHT_BLUE;
 use crate::gui::z::*;
 use crate::net::game_master_api::RestApiState;
+use crate::net::graphql::query_types::OutgoingAttack;
 use crate::net::state::current_village;
 use crate::prelude::*;
 use chrono::NaiveDateTime;
 use paddle::quicksilver_compat::{Col, Rectangle, Transform};
 use paddle::{utc_now, Frame, TextNode, Window};
 use paddlers_shared_lib::api::attacks::*;
+use paddlers_shared_lib::api::villages::VillageFoundation;
+use paddlers_shared_lib::prelude::AttackKey;
 use panes::new_pane;
 use specs::prelude::*;
 use stdweb::unstable::TryInto;
-use stdweb::web::{HtmlElement, IElement, INode, Node};
+use stdweb::web::{document, event, Element, HtmlElement, IElement, IEventTarget, INode, Node};
 
 #[derive(Component, Debug)]
 #[storage(HashMapStorage)]
@@ -23,6 +26,9 @@ pub struct Attack {
     dom_node: Option<TextNode>,
 }
 
+/// Visits sent out by the current village that are still travelling
+pub struct OutgoingAttacks(pub Vec<OutgoingAttack>);
+
 impl Game<'_, '_> {
     pub fn send_prophet_attack(&mut self, target: (i32, i32)) -> PadlResult<()> {
         let maybe_prophet = self.town_mut().idle_prophets.pop();
@@ -39,6 +45,20 @@ impl Game<'_, '_> {
             PadlErrorCode::NotEnoughUnits.usr()
         }
     }
+    /// Sends an idle prophet to rule over the village at the target position, or to found a new one there
+    pub fn send_prophet_to_rule(&mut self, target: (i32, i32)) -> PadlResult<()> {
+        let maybe_prophet = self.town_mut().idle_prophets.pop();
+        if let Some(prophet) = maybe_prophet {
+            let msg = VillageFoundation {
+                prophet: self.hobo_key(prophet)?,
+                position: target,
+            };
+            RestApiState::get().http_found_village(msg)?;
+            Ok(())
+        } else {
+            PadlErrorCode::NotEnoughUnits.usr()
+        }
+    }
 }
 
 impl Attack {
@@ -85,6 +105,7 @@ impl Attack {
 
 pub(crate) struct VisitorFrame<'a, 'b> {
     incoming_attacks_table: HtmlElement,
+    outgoing_attacks_table: Node,
     update_dispatcher: Dispatcher<'a, 'b>,
     pane: panes::PaneHandle,
 }
@@ -96,13 +117,22 @@ impl<'a, 'b> VisitorFrame<'a, 'b> {
             y as u32,
             (w / 2.0) as u32,
             h as u32,
-            r#"<div class="attack-table"></div>"#,
+            r#"<div class="attack-table"></div><div class="attack-table"></div>"#,
         )
         .expect("Pane not set up properly");
         let table = pane
             .first_inner_node()?
             .try_into()
             .map_err(|_| PadlError::dev_err(PadlErrorCode::InvalidDom("No table in pane")))?;
+        let outgoing_table = pane
+            .first_inner_node()?
+            .next_sibling()
+            .ok_or(PadlError::dev_err(PadlErrorCode::InvalidDom(
+                "No outgoing table in pane",
+            )))?;
+        let title = document().create_element("h2").unwrap();
+        title.set_text_content("Outgoing Visitors");
+        outgoing_table.append_child(&title);
 
         let update_dispatcher = DispatcherBuilder::new()
             .with(UpdateAttackViewSystem::new(), "update_atk", &[])
@@ -110,6 +140,7 @@ impl<'a, 'b> VisitorFrame<'a, 'b> {
 
         let mut attack = VisitorFrame {
             incoming_attacks_table: table,
+            outgoing_attacks_table: outgoing_table,
             update_dispatcher,
             pane,
         };
@@ -130,6 +161,66 @@ impl<'a, 'b> VisitorFrame<'a, 'b> {
     }
 }
 
+impl<'a, 'b> VisitorFrame<'a, 'b> {
+    pub fn outgoing_attacks(
+        &mut self,
+        _state: &mut Game<'static, 'static>,
+        msg: &OutgoingAttacks,
+    ) -> Result<(), PadlError> {
+        // Rebuild the list, keeping only the title
+        while self.outgoing_attacks_table.child_nodes().len() > 1 {
+            if let Some(row) = self.outgoing_attacks_table.last_child() {
+                self.outgoing_attacks_table.remove_child(&row).unwrap();
+            }
+        }
+        for atk in &msg.0 {
+            self.add_outgoing_attack(atk)?;
+        }
+        Ok(())
+    }
+    fn add_outgoing_attack(&mut self, atk: &OutgoingAttack) -> PadlResult<()> {
+        let row = document().create_element("div").unwrap();
+        row.set_attribute("class", "attack-row").unwrap();
+
+        let description = document().create_element("div").unwrap();
+        description.set_text_content(&format!(
+            "To <{}:{}>",
+            atk.destination.x as i64, atk.destination.y as i64
+        ));
+        row.append_child(&description);
+
+        let size = document().create_element("div").unwrap();
+        size.set_text_content(&atk.units.len().to_string());
+        row.append_child(&size);
+
+        let t = (atk.arrival() - utc_now()).num_seconds().max(0);
+        let button = document().create_element("div").unwrap();
+        button.set_attribute("class", "letter-button").unwrap();
+        button.set_text_content(&format!("Recall ({}s)", t));
+        let id = AttackKey(
+            atk.id
+                .parse()
+                .map_err(|_| PadlError::dev_err(PadlErrorCode::InvalidGraphQLData("attack id")))?,
+        );
+        self.add_recall_listener(&button, id, row.clone());
+        row.append_child(&button);
+
+        self.outgoing_attacks_table.append_child(&row);
+        Ok(())
+    }
+    fn add_recall_listener(&self, button: &Element, attack: AttackKey, row: Element) {
+        let table = self.outgoing_attacks_table.clone();
+        let _handle = button.add_event_listener::<event::ClickEvent, _>(move |_| {
+            if table.remove_child(&row).is_ok() {
+                let msg = AttackRecall { attack };
+                if let Err(e) = RestApiState::get().http_recall_attack(msg) {
+                    nuts::publish(e);
+                }
+            }
+        });
+    }
+}
+
 impl<'a, 'b> Frame for VisitorFrame<'a, 'b> {
     type Error = PadlError;
     type State = Game<'a, 'b>;
@@ -2,6 +2,7 @@ use crate::gui::sprites::*;
 use crate::gui::utils::colors::LIGHT_BLUE;
 use crate::gui::z::*;
 use crate::net::game_master_api::RestApiState;
+use crate::net::graphql::query_types::reports_query::VisitReportType;
 use crate::net::NetMsg;
 use crate::prelude::*;
 use core::marker::PhantomData;
@@ -21,6 +22,9 @@ pub(crate) struct ReportFrame<'a, 'b> {
 struct Report {
     id: VisitReportKey,
     karma: i64,
+    outbound: bool,
+    visitors: i64,
+    other_village: Option<(i64, i64)>,
     feathers: i64,
     sticks: i64,
     logs: i64,
@@ -54,7 +58,7 @@ impl<'a, 'b> ReportFrame<'a, 'b> {
         letter_node.set_attribute("class", "letter").unwrap();
 
         let text_node = document().create_element("p").unwrap();
-        text_node.set_text_content(self.letter_text(&report));
+        text_node.set_text_content(&self.letter_text(&report));
         letter_node.append_child(&text_node);
 
         if report.karma > 0 {
@@ -118,7 +122,17 @@ impl<'a, 'b> ReportFrame<'a, 'b> {
         node.append_child(&img);
         node
     }
-    fn letter_text(&self, report: &Report) -> &'static str {
+    fn letter_text(&self, report: &Report) -> String {
+        if report.outbound {
+            let from = report
+                .other_village
+                .map(|(x, y)| format!(" from <{}:{}>", x, y))
+                .unwrap_or_default();
+            return format!(
+                "Your visitors are back home{}. {} out of {} had a great time.",
+                from, report.karma, report.visitors
+            );
+        }
         match report.id.0 as usize % 5 {
             0 => "Thank you, was a very enjoyable visit.",
             1 => "Cheers!",
@@ -127,6 +141,7 @@ impl<'a, 'b> ReportFrame<'a, 'b> {
             4 => "A lovely place you have there.",
             _ => unreachable!(),
         }
+        .to_owned()
     }
     fn number_or_reports(&self) -> usize {
         self.table.child_nodes().len() as usize - 1 // -1 for title
@@ -143,6 +158,15 @@ impl<'a, 'b> ReportFrame<'a, 'b> {
                         Report {
                             id: VisitReportKey(r.id.parse().unwrap()),
                             karma: r.karma,
+                            outbound: match r.report_type {
+                                VisitReportType::OUTBOUND => true,
+                                _ => false,
+                            },
+                            visitors: r.visitors,
+                            other_village: r
+                                .other_village
+                                .as_ref()
+                                .map(|v| (v.x as i64, v.y as i64)),
                             feathers: r.feathers,
                             logs: r.logs,
                             sticks: r.sticks,
@@ -53,6 +53,7 @@ pub enum ClickOutput {
     Event(GameEvent),
     SlideAction(SlideButtonAction),
     SendInvitation,
+    RepeatTasks,
 }
 #[derive(Clone, Debug)]
 /// Represents a checkable condition. Used to check it later when the state is not available inside a system, for example.
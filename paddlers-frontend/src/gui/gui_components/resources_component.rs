@@ -25,7 +25,16 @@ impl ResourcesComponent {
         Ok(())
     }
     pub fn draw(&mut self, max_area: &Rectangle, resis: &[(ResourceType, i64)]) -> PadlResult<()> {
-        self.complete_redraw(max_area, resis)?;
+        self.draw_with_incoming(max_area, resis, &[])
+    }
+    /// Draws resources together with resources that are on their way, in a separate row
+    pub fn draw_with_incoming(
+        &mut self,
+        max_area: &Rectangle,
+        resis: &[(ResourceType, i64)],
+        incoming: &[(ResourceType, i64)],
+    ) -> PadlResult<()> {
+        self.complete_redraw(max_area, resis, incoming)?;
         self.pane.show()?;
         Ok(())
     }
@@ -33,6 +42,7 @@ impl ResourcesComponent {
         &mut self,
         max_area: &Rectangle,
         resis: &[(ResourceType, i64)],
+        incoming: &[(ResourceType, i64)],
     ) -> PadlResult<()> {
         // Brute-force delete and redraw everything
         self.pane.reposition_and_resize(
@@ -43,14 +53,19 @@ impl ResourcesComponent {
         )?;
         self.parent.remove_all_children();
         for (res, n) in resis {
-            let new_node = Self::new_resource_element(*res, *n);
+            let new_node = Self::new_resource_element(*res, &n.to_string());
+            self.parent.append_child(&new_node);
+        }
+        for (res, n) in incoming {
+            let new_node = Self::new_resource_element(*res, &format!("+{}", n));
+            new_node.class_list().add("incoming").unwrap();
             self.parent.append_child(&new_node);
         }
         Ok(())
     }
-    fn new_resource_element(res: ResourceType, n: i64) -> Element {
+    fn new_resource_element(res: ResourceType, text: &str) -> Element {
         let node = document().create_element("span").unwrap();
-        let number = document().create_text_node(&n.to_string());
+        let number = document().create_text_node(text);
         let img = ImageElement::new();
 
         let i = match res.sprite() {
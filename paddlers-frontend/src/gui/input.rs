@@ -1,14 +1,10 @@
-use crate::game::fight::*;
-use crate::game::movement::Position;
 use crate::gui::input::pointer::PointerManager;
 use crate::gui::ui_state::Now;
-use crate::net::game_master_api::RestApiState;
-use crate::net::state::current_village;
 use crate::prelude::*;
 /// This module keeps the logic to read input and, in most cases,
 /// redirect it to suitable modules to handle the input
 use paddle::quicksilver_compat::*;
-use paddle::{quicksilver_compat::Vector, Window};
+use paddle::{quicksilver_compat::Vector, NutsCheck, Window};
 use paddlers_shared_lib::prelude::*;
 use specs::prelude::*;
 
@@ -17,7 +13,7 @@ pub mod hover;
 pub mod left_click;
 pub mod pointer;
 pub use self::{hover::*, left_click::*};
-use crate::gui::ui_state::UiState;
+use crate::gui::ui_state::{UiState, ViewState};
 
 #[derive(Default, Clone, Copy)]
 pub struct MouseState(pub Vector, pub Option<MouseButton>);
@@ -26,6 +22,7 @@ pub struct MouseState(pub Vector, pub Option<MouseButton>);
 pub enum UiView {
     Visitors(VisitorViewTab),
     Leaderboard,
+    Market,
     Map,
     Town,
     Dialogue,
@@ -81,43 +78,9 @@ impl crate::game::Game<'_, '_> {
                 match view {
                     UiView::Town => {
                         if let Some(e) = ui_state.selected_entity {
-                            (*ui_state).selected_entity = None;
-
                             std::mem::drop(ui_state);
-
-                            let pos_store = town_world.read_storage::<Position>();
-                            let resolution = town_world.read_resource::<ScreenResolution>();
-                            let pos = pos_store.get(e).unwrap();
-                            let tile_index = resolution.tile(pos.area.center());
-                            std::mem::drop(pos_store);
-                            std::mem::drop(resolution);
-
-                            let r = RestApiState::get()
-                                .http_delete_building(tile_index, current_village());
+                            let r = self.request_building_deletion(e);
                             self.check(r);
-
-                            // Account for changes in aura total
-                            let aura_store = town_world.read_storage::<Aura>();
-                            let aura = aura_store.get(e).map(|a| a.effect);
-                            let range_store = town_world.read_storage::<Range>();
-                            let range = range_store.get(e).map(|r| r.range);
-                            std::mem::drop(aura_store);
-                            std::mem::drop(range_store);
-                            if let Some(aura) = aura {
-                                if let Some(range) = range {
-                                    if range > self.town().distance_to_lane(tile_index) {
-                                        self.town_mut().total_ambience -= aura;
-                                    }
-                                }
-                            }
-
-                            self.town_mut().remove_building(tile_index);
-                            self.town_world_mut().delete_entity(e).unwrap_or_else(|_| {
-                                self.check(
-                                    PadlErrorCode::DevMsg("Tried to delete wrong Generation").dev(),
-                                )
-                                .unwrap()
-                            });
                         }
                     }
                     _ => {}
@@ -136,3 +99,15 @@ impl crate::game::Game<'_, '_> {
         Ok(())
     }
 }
+
+impl crate::game::Game<'static, 'static> {
+    /// Called once a drag movement has ended, with the start and end position of the whole movement.
+    /// In the town, buildings are moved by dragging them to a new place.
+    pub fn handle_drag_and_drop(&mut self, start: Vector, end: Vector) {
+        let view = *self.world.fetch::<UiView>();
+        let in_menu_area = start.overlaps_rectangle(&self.world.fetch::<ViewState>().menu_box_area);
+        if view == UiView::Town && !in_menu_area && !self.town_context.is_foreign() {
+            self.relocate_building(start, end).nuts_check();
+        }
+    }
+}
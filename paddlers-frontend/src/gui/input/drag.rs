@@ -36,6 +36,9 @@ impl<'a> System<'a> for DragSystem {
                 (UiView::Leaderboard, false) => {
                     // NOP
                 }
+                (UiView::Market, false) => {
+                    // NOP
+                }
                 (UiView::Dialogue, false) => {
                     // NOP
                 }
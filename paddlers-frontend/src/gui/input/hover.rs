@@ -32,6 +32,7 @@ impl<'a> System<'a> for HoverSystem {
             }
             UiView::Visitors(_) => {}
             UiView::Leaderboard => {}
+            UiView::Market => {}
             UiView::Dialogue => {}
         }
     }
@@ -63,6 +63,8 @@ impl<'a> System<'a> for TownLeftClickSystem {
             &mut ui_state,
             &position,
             &clickable,
+            &moving,
+            &workers,
             &net_ids,
             &lazy,
             &mut resources,
@@ -119,6 +121,7 @@ impl<'a> System<'a> for TownMenuLeftClickSystem {
         WriteExpect<'a, Town>,
         Read<'a, LazyUpdate>,
         ReadStorage<'a, Position>,
+        ReadStorage<'a, Moving>,
         ReadStorage<'a, NetObj>,
         WriteStorage<'a, EntityContainer>,
         WriteStorage<'a, UiMenu>,
@@ -136,6 +139,7 @@ impl<'a> System<'a> for TownMenuLeftClickSystem {
             mut town,
             lazy,
             position,
+            moving,
             netids,
             containers,
             mut ui_menus,
@@ -152,6 +156,7 @@ impl<'a> System<'a> for TownMenuLeftClickSystem {
                     mouse_pos,
                     ui_state,
                     position,
+                    moving,
                     netids,
                     workers,
                     containers,
@@ -17,6 +17,8 @@ pub struct PointerManager<'a, 'b> {
     buffered_click: Option<(Vector, PointerButton)>,
     dragging: bool,
     pointer_down: Option<(Vector, NaiveDateTime)>,
+    drag_start: Option<Vector>,
+    buffered_drop: Option<(Vector, Vector)>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -48,6 +50,8 @@ impl PointerManager<'_, '_> {
             buffered_click: None,
             dragging: false,
             pointer_down: None,
+            drag_start: None,
+            buffered_drop: None,
         }
     }
 
@@ -66,6 +70,10 @@ impl PointerManager<'_, '_> {
             self.drag_dispatcher.dispatch(&mut game.world);
             game.world.write_resource::<Drag>().clear();
         }
+
+        if let Some((start, end)) = self.buffered_drop.take() {
+            game.handle_drag_and_drop(start, end);
+        }
     }
 
     pub fn move_pointer(&mut self, mut world: &mut World, position: &Vector) {
@@ -92,6 +100,7 @@ impl PointerManager<'_, '_> {
         match (state, button) {
             (ButtonState::Pressed, MouseButton::Left) => {
                 self.pointer_down = Some((*pos, now));
+                self.drag_start = Some(*pos);
             }
             (ButtonState::Pressed, _) => {
                 self.queue_click(pos, PointerButton::Secondary);
@@ -106,7 +115,11 @@ impl PointerManager<'_, '_> {
                         };
                         self.queue_click(pos, key);
                     }
+                    if self.dragging {
+                        self.buffered_drop = self.drag_start.map(|start| (start, *pos));
+                    }
                     self.dragging = false;
+                    self.drag_start = None;
                     self.pointer_down = None;
                 }
             }
@@ -30,6 +30,7 @@ use crate::prelude::*;
 use crate::resolution::ScreenResolution;
 use paddle::quicksilver_compat::{Col, Rectangle, Transform, Vector};
 use paddle::Window;
+use paddlers_shared_lib::game_mechanics::attributes::{Attributes, LeveledBuilding};
 use specs::prelude::*;
 
 impl ScreenResolution {
@@ -354,6 +355,9 @@ pub fn draw_town_entity_details_table(
     let buildings = world.write_storage::<Building>();
     let mut ui_menu = world.write_storage::<UiMenu>();
     if let Some(b) = buildings.get(e) {
+        if b.bt.max_level() > 1 {
+            table.extend(level_details(b));
+        }
         if b.bt == BuildingType::Temple && ui_menu.get(e).is_some() {
             let player_info = world.read_resource::<PlayerInfo>();
             table.extend(temple_details(&player_info));
@@ -392,6 +396,26 @@ fn aura_details(aura: &Aura) -> TableRow {
     let text = format!("+{}", aura.effect);
     TableRow::TextWithImage(text, SpriteIndex::Simple(SingleSprite::Ambience))
 }
+fn level_details<'a>(building: &Building) -> Vec<TableRow<'a>> {
+    let mut rows = vec![TableRow::Text(format!(
+        "Level {}/{}",
+        building.level,
+        building.bt.max_level()
+    ))];
+    if let Some(next) = LeveledBuilding::new(building.bt, building.level).next_level() {
+        let mut text = "Next level:".to_owned();
+        if let Some(r) = next.range() {
+            text += &format!(" range {}", r);
+        }
+        match (next.attack_power(), next.attacks_per_cycle()) {
+            (Some(ap), None) => text += &format!(", +{} ambience", ap),
+            (Some(ap), Some(apc)) => text += &format!(", {} x {} per cycle", ap, apc),
+            _ => {}
+        }
+        rows.push(TableRow::Text(text));
+    }
+    rows
+}
 fn health_details(health: &Health) -> TableRow {
     let health_text = format!("Well-being {}/{}", health.max_hp - health.hp, health.max_hp);
     TableRow::TextWithImage(health_text, SpriteIndex::Simple(SingleSprite::Heart))
@@ -15,7 +15,7 @@ pub(crate) struct MenuBackgroundFrame<'a, 'b> {
 
 impl<'a, 'b> MenuBackgroundFrame<'a, 'b> {
     pub fn new() -> Self {
-        let mut ui_box = UiBox::new(4, 1, 0.0, 5.0);
+        let mut ui_box = UiBox::new(5, 1, 0.0, 5.0);
 
         let town_button =
             Self::button_render(SingleSprite::TownButton, SingleSprite::TownButtonHov);
@@ -45,6 +45,12 @@ impl<'a, 'b> MenuBackgroundFrame<'a, 'b> {
                 .with_render_variant(leaderboard_button),
         );
 
+        ui_box.add(
+            UiElement::new(GameEvent::SwitchToView(UiView::Market))
+                .with_text("Market".to_owned())
+                .with_background_color(LIGHT_BLUE),
+        );
+
         let tp = TableTextProvider::new();
         MenuBackgroundFrame {
             ui: ui_box,
@@ -58,7 +64,7 @@ impl<'a, 'b> MenuBackgroundFrame<'a, 'b> {
     }
     fn update_notifications(&mut self) {
         self.ui
-            .update_notifications(Some(vec![0, 0, self.reports_to_collect, 0]));
+            .update_notifications(Some(vec![0, 0, self.reports_to_collect, 0, 0]));
     }
     pub fn network_message(
         &mut self,
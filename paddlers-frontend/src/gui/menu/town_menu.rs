@@ -216,12 +216,11 @@ impl TownMenuFrame<'_, '_> {
     ) -> Result<(), PadlError> {
         match e {
             Signal::ResourcesUpdated => {
-                self.bank_component.draw(
+                let resources = state.town_world().fetch::<TownResources>();
+                self.bank_component.draw_with_incoming(
                     &self.resources_area,
-                    &state
-                        .town_world()
-                        .fetch::<TownResources>()
-                        .non_zero_resources(),
+                    &resources.non_zero_resources(),
+                    &resources.incoming_resources(),
                 )?;
             }
             _ => {}
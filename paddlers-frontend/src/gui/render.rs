@@ -16,6 +16,7 @@ use paddle::quicksilver_compat::graphics::Color;
 use paddle::quicksilver_compat::*;
 use paddle::FitStrategy;
 use paddle::*;
+use paddlers_shared_lib::game_mechanics::attributes::Attributes;
 use specs::prelude::*;
 
 #[derive(Component, Debug)]
@@ -153,14 +154,19 @@ pub fn render_grabbed_item(
     let center = mouse - (ul / 2.0, ul / 2.0).into();
     let max_area = Rectangle::new(center, (ul, ul));
     match item {
-        Grabbable::NewBuilding(building_type) => draw_static_image(
-            sprites,
-            window,
-            &max_area,
-            building_type.sprite().default(),
-            Z_GRABBED_ITEM,
-            FitStrategy::TopLeft,
-        )?,
+        Grabbable::NewBuilding(building_type) => {
+            // The tile under the mouse is where the building is anchored
+            let (w, h) = building_type.size();
+            let area = Rectangle::new(center, (ul * w as f32, ul * h as f32));
+            draw_static_image(
+                sprites,
+                window,
+                &area,
+                building_type.sprite().default(),
+                Z_GRABBED_ITEM,
+                FitStrategy::TopLeft,
+            )?
+        }
         Grabbable::Ability(ability) => draw_static_image(
             sprites,
             window,
@@ -231,8 +237,7 @@ impl Range {
         area: &Rectangle,
         resolution: ScreenResolution,
     ) -> PadlResult<()> {
-        // TODO Check if this aligns 100% with server. Also consider changing interface to TileIndex instead of center
-        Town::shadow_rectified_circle(resolution, window, area.center(), self.range);
+        Town::shadow_rectified_circle(resolution, window, area, self.range);
         Ok(())
     }
 }
@@ -145,6 +145,9 @@ pub enum SingleSprite {
     SingleNest,
     TripleNest,
     SittingYellowDuck,
+    Fountain,
+    Sprinkler,
+    Harbour,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -219,6 +222,10 @@ impl WithSprite for BuildingType {
             BuildingType::Temple => SpriteSet::Simple(SingleSprite::Temple),
             BuildingType::SingleNest => SpriteSet::Simple(SingleSprite::SingleNest),
             BuildingType::TripleNest => SpriteSet::Simple(SingleSprite::TripleNest),
+            BuildingType::Fountain => SpriteSet::Simple(SingleSprite::Fountain),
+            BuildingType::Sprinkler => SpriteSet::Simple(SingleSprite::Sprinkler),
+            BuildingType::Harbour => SpriteSet::Simple(SingleSprite::Harbour),
+            BuildingType::Shack => SpriteSet::Simple(SingleSprite::Shack),
         }
     }
 }
@@ -248,6 +255,9 @@ impl WithSprite for AbilityType {
         match self {
             AbilityType::Work => SpriteSet::Simple(SingleSprite::NewOrder),
             AbilityType::Welcome => SpriteSet::Simple(SingleSprite::WelcomeAbility),
+            AbilityType::Cheer => SpriteSet::Simple(SingleSprite::Heart),
+            AbilityType::Slow => SpriteSet::Simple(SingleSprite::DuckSteps),
+            AbilityType::Bloom => SpriteSet::Simple(SingleSprite::BlueFlowers),
         }
     }
 }
@@ -1,7 +1,7 @@
 use super::animation::{AnimatedObjectDef, AnimationVariantDef};
 use super::*;
 
-pub const SPRITE_PATHS_NUM: usize = 60;
+pub const SPRITE_PATHS_NUM: usize = 63;
 pub const SPRITE_PATHS: [&'static str; SPRITE_PATHS_NUM] = [
     "textures/grass.png", // 0
     "textures/water.png",
@@ -63,7 +63,9 @@ pub const SPRITE_PATHS: [&'static str; SPRITE_PATHS_NUM] = [
     "buildings/nest.png",
     "buildings/nests.png",
     "ducks/sitting_duck.png",
-    //60
+    "buildings/fountain.png", //60
+    "buildings/sprinkler.png",
+    "buildings/harbour.png",
 ];
 
 impl SingleSprite {
@@ -125,6 +127,9 @@ impl SingleSprite {
             SingleSprite::SingleNest => 57,
             SingleSprite::TripleNest => 58,
             SingleSprite::SittingYellowDuck => 59,
+            SingleSprite::Fountain => 60,
+            SingleSprite::Sprinkler => 61,
+            SingleSprite::Harbour => 62,
         }
     }
 }
@@ -1,6 +1,7 @@
 use crate::game::dialogue::DialogueFrame;
 use crate::game::leaderboard::LeaderboardFrame;
 use crate::game::map::MapFrame;
+use crate::game::market::MarketFrame;
 use crate::game::town::TownFrame;
 use crate::game::visits::{
     attacks::VisitorFrame, reports::ReportFrame, visitor_menu::VisitorMenuFrame,
@@ -42,6 +43,7 @@ pub(crate) fn load_viewer(view: UiView, resolution: ScreenResolution) -> ViewMan
         &[
             UiView::Town,
             UiView::Leaderboard,
+            UiView::Market,
             UiView::Map,
             UiView::Visitors(VisitorViewTab::IncomingAttacks),
             UiView::Visitors(VisitorViewTab::Letters),
@@ -85,12 +87,13 @@ pub(crate) fn load_viewer(view: UiView, resolution: ScreenResolution) -> ViewMan
 
     let (w, h) = resolution.main_area();
     let menu = VisitorFrame::new(0.0, 0.0, w, h).expect("Attacks loading");
-    viewer.add_frame(
+    let visitor_handler = viewer.add_frame(
         menu,
         &[UiView::Visitors(VisitorViewTab::IncomingAttacks)],
         (0, 0),
         (w as i32, h as i32),
     );
+    visitor_handler.listen(VisitorFrame::outgoing_attacks);
 
     let rect = Rectangle::new((0.0, 0.0), (w, h));
     let frame = ReportFrame::new(rect, resolution).expect("Report frame loading");
@@ -113,6 +116,17 @@ pub(crate) fn load_viewer(view: UiView, resolution: ScreenResolution) -> ViewMan
     );
     leaderboard_handler.listen(LeaderboardFrame::network_message);
 
+    /* Market */
+
+    let menu = MarketFrame::new(&rect).expect("Market loading");
+    let market_handler = viewer.add_frame(
+        menu,
+        &[UiView::Market],
+        (0, 0), // TODO
+        (0, 0), // TODO
+    );
+    market_handler.listen(MarketFrame::network_message);
+
     /* Dialogue box */
 
     let (w1, _h1) = resolution.menu_area();
@@ -1,7 +1,16 @@
 use crate::game::{
-    components::*, fight::Aura, player_info::PlayerInfo, story::entity_trigger::EntityTrigger,
-    town::nests::Nest, town::DefaultShop, town::Town, town_resources::TownResources,
-    units::attackers::Visitor, units::hobos::Hobo, units::workers::Worker, visits::attacks::Attack,
+    components::*,
+    fight::{Aura, DefenceTower},
+    player_info::PlayerInfo,
+    story::entity_trigger::EntityTrigger,
+    town::nests::Nest,
+    town::DefaultShop,
+    town::Town,
+    town_resources::TownResources,
+    units::attackers::Visitor,
+    units::hobos::Hobo,
+    units::workers::Worker,
+    visits::attacks::Attack,
 };
 use crate::gui::input::drag::Drag;
 use crate::gui::ui_state::*;
@@ -53,6 +62,7 @@ pub fn insert_town_resources(world: &mut World, player_info: PlayerInfo, town: T
 pub fn register_town_components(world: &mut World) {
     world.register::<Aura>();
     world.register::<Building>();
+    world.register::<DefenceTower>();
     world.register::<EntityContainer>();
     world.register::<ForestComponent>();
     world.register::<Health>();
@@ -5,6 +5,7 @@ use crate::game::town::{TileIndex, TileType};
 use crate::net::ajax::AjaxError;
 use crate::prelude::*;
 use crate::stdweb::unstable::TryInto;
+use paddlers_shared_lib::game_mechanics::town::PlacementError;
 use std::fmt;
 use std::sync::mpsc::SendError;
 
@@ -59,16 +60,20 @@ pub enum PadlErrorCode {
     TestError,
     // User
     BuildingFull(Option<BuildingType>),
+    CannotPlaceBuilding(PlacementError),
     ForestTooSmall(usize),
     NotEnoughResources,
     NotEnoughSupply,
     NotEnoughMana,
     NotEnoughKarma,
     NotEnoughUnits,
+    NoHousing,
+    NotRepeatable,
     NotReadyYet,
     PathBlocked,
     NoNetwork,
     NestEmpty,
+    InvalidAmount,
     // Dev only
     DevMsg(&'static str),
     MapOverflow(TileIndex),
@@ -106,6 +111,7 @@ impl fmt::Display for PadlErrorCode {
             // User
             PadlErrorCode::BuildingFull(Some(b)) => write!(f, "The {} is full.", b),
             PadlErrorCode::BuildingFull(None) => write!(f, "Building is full."),
+            PadlErrorCode::CannotPlaceBuilding(reason) => write!(f, "{}.", reason),
             PadlErrorCode::ForestTooSmall(amount) => {
                 write!(f, "Missing {} forest flora size.", amount)
             }
@@ -115,9 +121,12 @@ impl fmt::Display for PadlErrorCode {
             PadlErrorCode::NotEnoughMana => write!(f, "Not enough mana."),
             PadlErrorCode::NotEnoughKarma => write!(f, "Not enough karma."),
             PadlErrorCode::NotEnoughUnits => write!(f, "Require more units."),
+            PadlErrorCode::NotRepeatable => write!(f, "This cannot be repeated."),
+            PadlErrorCode::NoHousing => write!(f, "Build more shacks to house new workers."),
             PadlErrorCode::PathBlocked => write!(f, "The path is blocked."),
             PadlErrorCode::NoNetwork => write!(f, "Connection to server dropped."),
             PadlErrorCode::NestEmpty => write!(f, "Nobody around to invite."),
+            PadlErrorCode::InvalidAmount => write!(f, "Please enter a positive amount."),
             // Dev
             PadlErrorCode::DevMsg(msg) => write!(f, "Dev Error Msg: {}", msg),
             PadlErrorCode::MapOverflow(i) => write!(f, "Index is outside the map: {:?}", i),
@@ -261,12 +270,19 @@ impl From<JsError> for PadlError {
     }
 }
 
+impl From<PlacementError> for PadlError {
+    fn from(error: PlacementError) -> Self {
+        PadlError::user_err(PadlErrorCode::CannotPlaceBuilding(error))
+    }
+}
+
 impl From<paddlers_shared_lib::game_mechanics::town::TownError> for PadlError {
     fn from(error: paddlers_shared_lib::game_mechanics::town::TownError) -> Self {
         use paddlers_shared_lib::game_mechanics::town::TownError;
         match error {
             TownError::BuildingFull => PadlError::user_err(PadlErrorCode::BuildingFull(None)),
             TownError::NotEnoughSupply => PadlError::user_err(PadlErrorCode::NotEnoughSupply),
+            TownError::NoHousing => PadlError::user_err(PadlErrorCode::NoHousing),
             TownError::InvalidState(s) => PadlError::dev_err(PadlErrorCode::DevMsg(s)),
         }
     }
@@ -3,10 +3,12 @@ use super::{
 };
 use crate::prelude::*;
 use futures_util::future::FutureExt;
+use paddlers_shared_lib::api::market::*;
 use paddlers_shared_lib::api::reports::ReportCollect;
 use paddlers_shared_lib::api::story::StoryStateTransition;
 use paddlers_shared_lib::api::{
-    attacks::*, keys::*, shop::*, statistics::*, tasks::TaskList, PlayerInitData,
+    attacks::*, keys::*, shipments::ShipmentOrder, shop::*, statistics::*, tasks::TaskList,
+    villages::*, PlayerInitData,
 };
 use specs::prelude::*;
 use std::collections::VecDeque;
@@ -65,6 +67,7 @@ impl RestApiState {
     pub fn http_delete_building(
         &mut self,
         idx: (usize, usize),
+        bt: BuildingType,
         village: VillageKey,
     ) -> PadlResult<()> {
         let msg = BuildingDeletion {
@@ -78,6 +81,55 @@ impl RestApiState {
             &format!("{}/shop/building/delete", game_master_url()?),
             request_string,
         );
+        // Hobos of a deleted nest have moved to other nests or left
+        let update = if bt.nest_capacity() > 0 {
+            Some(NetUpdateRequest::Hobos)
+        } else {
+            None
+        };
+        self.push_promise(promise, update);
+        Ok(())
+    }
+
+    pub fn http_relocate_building(
+        &mut self,
+        from: (usize, usize),
+        to: (usize, usize),
+        village: VillageKey,
+    ) -> PadlResult<()> {
+        let msg = BuildingRelocation {
+            x: from.0,
+            y: from.1,
+            new_x: to.0,
+            new_y: to.1,
+            village,
+        };
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/shop/building/relocate", game_master_url()?),
+            request_string,
+        );
+        self.push_promise(promise, None);
+        Ok(())
+    }
+
+    pub fn http_upgrade_building(
+        &mut self,
+        idx: (usize, usize),
+        village: VillageKey,
+    ) -> PadlResult<()> {
+        let msg = BuildingUpgrade {
+            x: idx.0,
+            y: idx.1,
+            village,
+        };
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/shop/building/upgrade", game_master_url()?),
+            request_string,
+        );
         self.push_promise(promise, None);
         Ok(())
     }
@@ -95,6 +147,61 @@ impl RestApiState {
         Ok(())
     }
 
+    pub fn http_buy_worker(&mut self, msg: WorkerPurchase) -> PadlResult<()> {
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/shop/unit/worker", game_master_url()?),
+            request_string,
+        );
+        self.push_promise(promise, Some(NetUpdateRequest::Workers));
+        Ok(())
+    }
+
+    pub fn http_found_village(&mut self, msg: VillageFoundation) -> PadlResult<()> {
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/village/found", game_master_url()?),
+            request_string,
+        );
+        // The prophet is gone and the player has one more village
+        self.push_promise(promise, Some(NetUpdateRequest::PlayerInfo));
+        Ok(())
+    }
+
+    pub fn http_create_trade_offer(&mut self, msg: TradeOfferCreation) -> PadlResult<()> {
+        self.post_market_request(
+            "/market/offer/create",
+            &serde_json::to_string(&msg).unwrap(),
+        )
+    }
+
+    pub fn http_accept_trade_offer(&mut self, msg: TradeOfferAcceptance) -> PadlResult<()> {
+        self.post_market_request(
+            "/market/offer/accept",
+            &serde_json::to_string(&msg).unwrap(),
+        )
+    }
+
+    pub fn http_cancel_trade_offer(&mut self, msg: TradeOfferCancellation) -> PadlResult<()> {
+        self.post_market_request(
+            "/market/offer/cancel",
+            &serde_json::to_string(&msg).unwrap(),
+        )
+    }
+
+    /// All market operations change the open offers and the resources of the village
+    fn post_market_request(&mut self, path: &str, request_string: &str) -> PadlResult<()> {
+        let promise = ajax::send(
+            "POST",
+            &format!("{}{}", game_master_url()?, path),
+            request_string,
+        );
+        self.push_promise(promise, Some(NetUpdateRequest::TradeOffers));
+        Ok(())
+    }
+
     pub fn http_overwrite_tasks(&mut self, msg: TaskList) -> PadlResult<()> {
         let request_string = &serde_json::to_string(&msg).unwrap();
         let promise = ajax::send(
@@ -145,6 +252,17 @@ impl RestApiState {
         Ok(())
     }
 
+    pub fn http_ship_resources(&mut self, msg: ShipmentOrder) -> PadlResult<()> {
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/shipments/create", game_master_url()?),
+            request_string,
+        );
+        self.push_promise(promise, None);
+        Ok(())
+    }
+
     pub fn http_invite(&mut self, msg: InvitationDescriptor) -> PadlResult<()> {
         let request_string = &serde_json::to_string(&msg).unwrap();
         let promise = ajax::send(
@@ -156,6 +274,18 @@ impl RestApiState {
         Ok(())
     }
 
+    pub fn http_recall_attack(&mut self, msg: AttackRecall) -> PadlResult<()> {
+        let request_string = &serde_json::to_string(&msg).unwrap();
+        let promise = ajax::send(
+            "POST",
+            &format!("{}/attacks/recall", game_master_url()?),
+            request_string,
+        );
+        // Recalled visitors are available again
+        self.push_promise(promise, Some(NetUpdateRequest::Hobos));
+        Ok(())
+    }
+
     pub fn http_notify_visitor_satisfied(&mut self, msg: HoboKey) -> PadlResult<()> {
         let request_string = &serde_json::to_string(&msg).unwrap();
         let promise = ajax::send(
@@ -216,8 +346,18 @@ impl RestApiState {
                             NetUpdateRequest::WorkerTasks(unit_id) => {
                                 crate::net::request_worker_tasks_update(unit_id)
                             }
+                            NetUpdateRequest::Workers => {
+                                crate::net::request_workers_update();
+                                crate::net::request_resource_update();
+                            }
                             NetUpdateRequest::CompleteReload => crate::net::request_client_state(),
                             NetUpdateRequest::PlayerInfo => crate::net::request_player_update(),
+                            NetUpdateRequest::Hobos => crate::net::request_hobos_update(),
+                            NetUpdateRequest::TradeOffers => {
+                                // Stay on the page the player is looking at
+                                crate::net::request_trade_offers(crate::net::trade_offers_offset());
+                                crate::net::request_resource_update();
+                            }
                         }
                     }
                 }
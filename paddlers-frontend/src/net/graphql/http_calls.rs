@@ -161,6 +161,26 @@ pub(super) fn http_read_leaderboard(
     }))
 }
 
+pub(super) fn http_read_trade_offers(
+    offset: i64,
+    limit: i64,
+) -> PadlResult<impl Future<Output = PadlResult<TradeOffersResponse>>> {
+    let request_body =
+        TradeOffersQuery::build_query(trade_offers_query::Variables { offset, limit });
+    let request_string = &serde_json::to_string(&request_body)?;
+    let promise = ajax::send("POST", &graphql_url()?, request_string)?;
+    Ok(promise.map(|x| {
+        let raw_response: TradeOffersRawResponse = serde_json::from_str(&x?)?;
+        let response =
+            raw_response
+                .data
+                .ok_or(PadlError::dev_err(PadlErrorCode::InvalidGraphQLData(
+                    "trade offers",
+                )))?;
+        Ok(response.trade_offers)
+    }))
+}
+
 pub(super) fn http_read_reports(
     min_report_id: Option<i64>,
     village_id: VillageKey,
@@ -11,11 +11,16 @@ use futures::Future;
 use futures_util::future::FutureExt;
 use futures_util::try_future::TryFutureExt;
 use paddlers_shared_lib::prelude::VillageKey;
-use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::atomic::{AtomicI64, AtomicUsize, Ordering};
+
+/// Number of trade offers shown on one page of the marketplace
+pub const TRADE_OFFERS_PAGE_SIZE: usize = 20;
 
 pub struct GraphQlState {
     next_attack_id: AtomicI64,
     next_report_id: AtomicI64,
+    /// Offset of the last requested page of trade offers
+    trade_offers_offset: AtomicUsize,
 }
 
 impl GraphQlState {
@@ -23,6 +28,7 @@ impl GraphQlState {
         GraphQlState {
             next_attack_id: AtomicI64::new(0),
             next_report_id: AtomicI64::new(0),
+            trade_offers_offset: AtomicUsize::new(0),
         }
     }
 
@@ -154,6 +160,17 @@ impl GraphQlState {
             ))
         }))
     }
+    pub fn trade_offers_query(
+        &self,
+        offset: usize,
+    ) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
+        self.trade_offers_offset.store(offset, Ordering::Relaxed);
+        let fp = http_read_trade_offers(offset as i64, TRADE_OFFERS_PAGE_SIZE as i64)?;
+        Ok(fp.map(move |response| Ok(NetMsg::TradeOffers(offset, response?))))
+    }
+    pub(super) fn trade_offers_offset(&self) -> usize {
+        self.trade_offers_offset.load(Ordering::Relaxed)
+    }
     pub(super) fn reports_query(
         &'static self,
     ) -> PadlResult<impl Future<Output = PadlResult<NetMsg>>> {
@@ -25,6 +25,7 @@ pub struct AttacksQuery;
 pub type AttacksResponse = Response<attacks_query::ResponseData>;
 pub type HoboEffect = attacks_query::AttacksQueryVillageAttacksUnitsHoboEffects;
 pub type HoboAttribute = attacks_query::HoboAttributeType;
+pub type OutgoingAttack = attacks_query::AttacksQueryVillageOutgoingAttacks;
 
 impl attacks_query::AttacksQueryVillageAttacks {
     #[allow(dead_code)]
@@ -37,6 +38,12 @@ impl attacks_query::AttacksQueryVillageAttacks {
     }
 }
 
+impl OutgoingAttack {
+    pub fn arrival(&self) -> chrono::NaiveDateTime {
+        timestamp(&self.arrival).to_chrono()
+    }
+}
+
 impl Into<HoboAttributeType> for &HoboAttribute {
     fn into(self) -> HoboAttributeType {
         match self {
@@ -62,6 +69,18 @@ pub type BuildingsResponse = Response<buildings_query::ResponseData>;
 )]
 pub struct VolatileVillageInfoQuery;
 pub type VolatileVillageInfoResponse = Response<volatile_village_info_query::ResponseData>;
+impl Into<ResourceType> for &volatile_village_info_query::ResourceType {
+    fn into(self) -> ResourceType {
+        match self {
+            volatile_village_info_query::ResourceType::FEATHERS => ResourceType::Feathers,
+            volatile_village_info_query::ResourceType::STICKS => ResourceType::Sticks,
+            volatile_village_info_query::ResourceType::LOGS => ResourceType::Logs,
+            volatile_village_info_query::ResourceType::Other(_) => {
+                panic!("Unexpected resource type")
+            }
+        }
+    }
+}
 
 #[derive(GraphQLQuery)]
 #[graphql(
@@ -101,6 +120,9 @@ impl Into<AbilityType> for &VillageUnitsAbilityType {
         match self {
             VillageUnitsAbilityType::WORK => AbilityType::Work,
             VillageUnitsAbilityType::WELCOME => AbilityType::Welcome,
+            VillageUnitsAbilityType::CHEER => AbilityType::Cheer,
+            VillageUnitsAbilityType::SLOW => AbilityType::Slow,
+            VillageUnitsAbilityType::BLOOM => AbilityType::Bloom,
             VillageUnitsAbilityType::Other(_) => panic!("Unexpected ability"),
         }
     }
@@ -186,6 +208,9 @@ impl Into<TaskType> for &WorkerTaskType {
             WorkerTaskType::CHOP_TREE => TaskType::ChopTree,
             WorkerTaskType::DEFEND => TaskType::Defend,
             WorkerTaskType::WELCOME_ABILITY => TaskType::WelcomeAbility,
+            WorkerTaskType::CHEER_ABILITY => TaskType::CheerAbility,
+            WorkerTaskType::SLOW_ABILITY => TaskType::SlowAbility,
+            WorkerTaskType::BLOOM_ABILITY => TaskType::BloomAbility,
             WorkerTaskType::COLLECT_REWARD => TaskType::CollectReward,
             WorkerTaskType::Other(_) => panic!("Unexpected task type"),
         }
@@ -205,6 +230,9 @@ impl Into<TaskType> for &VillageUnitsTaskType {
             VillageUnitsTaskType::CHOP_TREE => TaskType::ChopTree,
             VillageUnitsTaskType::DEFEND => TaskType::Defend,
             VillageUnitsTaskType::WELCOME_ABILITY => TaskType::WelcomeAbility,
+            VillageUnitsTaskType::CHEER_ABILITY => TaskType::CheerAbility,
+            VillageUnitsTaskType::SLOW_ABILITY => TaskType::SlowAbility,
+            VillageUnitsTaskType::BLOOM_ABILITY => TaskType::BloomAbility,
             VillageUnitsTaskType::COLLECT_REWARD => TaskType::CollectReward,
             VillageUnitsTaskType::Other(_) => panic!("Unexpected task type"),
         }
@@ -237,6 +265,26 @@ pub struct LeaderboardQuery;
 pub type LeaderboardRawResponse = Response<leaderboard_query::ResponseData>;
 pub type LeaderboardResponse = Vec<leaderboard_query::LeaderboardQueryScoreboard>;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "api/schema.json",
+    query_path = "api/queries/trade_offers_query.graphql"
+)]
+pub struct TradeOffersQuery;
+pub type TradeOffersRawResponse = Response<trade_offers_query::ResponseData>;
+pub type TradeOffersResponse = Vec<trade_offers_query::TradeOffersQueryTradeOffers>;
+
+impl Into<ResourceType> for &trade_offers_query::ResourceType {
+    fn into(self) -> ResourceType {
+        match self {
+            trade_offers_query::ResourceType::FEATHERS => ResourceType::Feathers,
+            trade_offers_query::ResourceType::STICKS => ResourceType::Sticks,
+            trade_offers_query::ResourceType::LOGS => ResourceType::Logs,
+            trade_offers_query::ResourceType::Other(_) => panic!("Unexpected resource type"),
+        }
+    }
+}
+
 use paddlers_shared_lib::story::story_state::StoryState;
 impl Into<StoryState> for &PlayerStoryState {
     fn into(self) -> StoryState {
@@ -35,12 +35,16 @@ pub enum NetMsg {
     UpdateWorkerTasks(WorkerTasksResponse),
     Workers(WorkerResponse, VillageKey),
     Reports(ReportsResponse),
+    TradeOffers(usize, TradeOffersResponse),
 }
 
 pub enum NetUpdateRequest {
     CompleteReload,
     WorkerTasks(i64),
+    Workers,
     PlayerInfo,
+    Hobos,
+    TradeOffers,
 }
 
 struct NetState {
@@ -126,6 +130,25 @@ pub fn request_resource_update() {
         STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.resource_query());
     }
 }
+pub fn request_workers_update() {
+    unsafe {
+        STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.workers_query());
+    }
+}
+pub fn request_hobos_update() {
+    unsafe {
+        STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.hobos_query());
+    }
+}
+pub fn request_trade_offers(offset: usize) {
+    unsafe {
+        STATIC_NET_STATE.spawn(STATIC_NET_STATE.gql_state.trade_offers_query(offset));
+    }
+}
+/// Offset of the page of trade offers that has been requested last
+pub fn trade_offers_offset() -> usize {
+    unsafe { STATIC_NET_STATE.gql_state.trade_offers_offset() }
+}
 pub fn request_player_update() {
     unsafe {
         if STATIC_NET_STATE.logged_in.load(Ordering::Relaxed) {
@@ -207,6 +230,7 @@ impl std::fmt::Debug for NetMsg {
             Self::VillageInfo(_) => write!(f, "NetMsg: VillageInfo"),
             Self::UpdateWorkerTasks(_) => write!(f, "NetMsg: UpdateWorkerTasks"),
             Self::Workers(_, _) => write!(f, "NetMsg: Workers"),
+            Self::TradeOffers(_, _) => write!(f, "NetMsg: TradeOffers"),
             Self::Reports(_) => write!(f, "NetMsg: Reports"),
         }
     }
@@ -34,6 +34,11 @@ pub fn current_village() -> VillageKey {
     load_current_village().expect("Reading village too early")
 }
 
+/// Changes the village displayed as home town, all following requests for the own village refer to it
+pub fn set_current_village(vid: VillageKey) {
+    STATE.village.store(vid.num(), Ordering::Relaxed);
+}
+
 fn load_current_village() -> Option<VillageKey> {
     if let Ok(key) = crate::net::url::read_current_village_id() {
         Some(key)
@@ -18,7 +18,8 @@ impl Game<'_, '_> {
             UiView::Map => UiView::Town,
             UiView::Town => UiView::Visitors(VisitorViewTab::Letters),
             UiView::Visitors(_) => UiView::Leaderboard,
-            UiView::Leaderboard => UiView::Map,
+            UiView::Leaderboard => UiView::Market,
+            UiView::Market => UiView::Map,
             UiView::Dialogue => return,
         };
 
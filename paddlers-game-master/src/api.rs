@@ -1,14 +1,22 @@
 mod attacks;
+mod market;
 mod reports;
+mod shipments;
 mod shop;
 mod story;
+mod villages;
 
-pub(crate) use attacks::{new_invitation, visitor_satisfied_notification};
+pub(crate) use attacks::{new_invitation, recall_attack, visitor_satisfied_notification};
+pub(crate) use market::{accept_trade_offer, cancel_trade_offer, create_trade_offer};
 pub(crate) use reports::collect_report_rewards;
+pub(crate) use shipments::ship_resources;
 pub(crate) use story::story_transition;
+pub(crate) use villages::found_village;
 
 use crate::authentication::Authentication;
 use crate::game_master::attack_funnel::PlannedAttack;
+use crate::game_master::event::Event;
+use crate::game_master::town_worker::TownWorkerEventMsg;
 use crate::setup::initialize_new_player_account;
 use crate::StringErr;
 use actix_web::error::BlockingError;
@@ -18,7 +26,10 @@ use futures::Future;
 use paddlers_shared_lib::api::{
     attacks::AttackDescriptor,
     keys::{VillageKey, WorkerKey},
-    shop::{BuildingDeletion, BuildingPurchase, ProphetPurchase},
+    shop::{
+        BuildingDeletion, BuildingPurchase, BuildingRelocation, BuildingUpgrade, ProphetPurchase,
+        WorkerPurchase,
+    },
     tasks::TaskList,
     PlayerInitData,
 };
@@ -55,6 +66,34 @@ pub(crate) fn purchase_prophet(
     )
 }
 
+pub(crate) fn purchase_worker(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<WorkerPurchase>,
+    auth: Authentication,
+) -> impl Future<Item = HttpResponse, Error = ()> {
+    let village = body.village;
+    std::mem::drop(body);
+    web::block(move || {
+        let db: crate::db::DB = pool.get_ref().into();
+        check_owns_village0(&db, &auth, village).map_err(shop::WorkerPurchaseError::Invalid)?;
+        db.try_buy_worker(village)
+    })
+    .then(
+        |result: Result<(), BlockingError<shop::WorkerPurchaseError>>| match result {
+            Err(BlockingError::Error(shop::WorkerPurchaseError::Invalid(msg))) => {
+                Ok(HttpResponse::Forbidden().body(msg).into())
+            }
+            Err(BlockingError::Error(shop::WorkerPurchaseError::DbError(e))) => {
+                Ok(HttpResponse::InternalServerError()
+                    .body(e.to_string())
+                    .into())
+            }
+            Err(BlockingError::Canceled) => Ok(HttpResponse::InternalServerError().into()),
+            Ok(()) => Ok(HttpResponse::Ok().into()),
+        },
+    )
+}
+
 pub(crate) fn purchase_building(
     pool: web::Data<crate::db::Pool>,
     body: web::Json<BuildingPurchase>,
@@ -89,6 +128,7 @@ pub fn delete_building(
     pool: web::Data<crate::db::Pool>,
     body: web::Json<BuildingDeletion>,
     auth: Authentication,
+    addr: web::Data<crate::ActorAddresses>,
 ) -> impl Responder {
     let db: crate::db::DB = pool.get_ref().into();
 
@@ -99,17 +139,70 @@ pub fn delete_building(
     if let Some(building) =
         db.find_building_by_coordinates(body.x as i32, body.y as i32, body.village)
     {
-        if building.building_type.can_be_deleted() {
-            db.delete_building(&building);
-            HttpResponse::Ok().into()
-        } else {
-            HttpResponse::BadRequest().body("This building cannot be deleted".to_owned())
+        match db.try_delete_building(&building) {
+            Ok(()) => {
+                if building.building_type.resting_spots() > 0 {
+                    // Visitors that lost their spot have to leave now
+                    let event = Event::CheckRestingVisitors {
+                        village_id: body.village,
+                    };
+                    addr.town_worker
+                        .try_send(TownWorkerEventMsg(event, chrono::Utc::now()))
+                        .map_err(|e| eprintln!("Send failed: {:?}", e))
+                        .ok();
+                }
+                HttpResponse::Ok().into()
+            }
+            Err(shop::BuildingChangeError::Invalid(msg)) => HttpResponse::BadRequest().body(msg),
+            Err(shop::BuildingChangeError::DbError(e)) => {
+                HttpResponse::InternalServerError().body(e.to_string())
+            }
         }
     } else {
         HttpResponse::BadRequest().body(format!("No building at {}|{}", body.x, body.y))
     }
 }
 
+pub fn relocate_building(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<BuildingRelocation>,
+    auth: Authentication,
+) -> impl Responder {
+    let db: crate::db::DB = pool.get_ref().into();
+
+    if let Err(err) = check_owns_village(&db, &auth, body.village) {
+        return err;
+    }
+
+    match db.try_relocate_building((body.x, body.y), (body.new_x, body.new_y), body.village) {
+        Ok(()) => HttpResponse::Ok().into(),
+        Err(shop::BuildingChangeError::Invalid(msg)) => HttpResponse::BadRequest().body(msg),
+        Err(shop::BuildingChangeError::DbError(e)) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+pub fn upgrade_building(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<BuildingUpgrade>,
+    auth: Authentication,
+) -> impl Responder {
+    let db: crate::db::DB = pool.get_ref().into();
+
+    if let Err(err) = check_owns_village(&db, &auth, body.village) {
+        return err;
+    }
+
+    match db.try_upgrade_building((body.x, body.y), body.village) {
+        Ok(()) => HttpResponse::Ok().into(),
+        Err(shop::BuildingChangeError::Invalid(msg)) => HttpResponse::BadRequest().body(msg),
+        Err(shop::BuildingChangeError::DbError(e)) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
 pub(super) fn overwrite_tasks(
     pool: web::Data<crate::db::Pool>,
     body: web::Json<TaskList>,
@@ -131,6 +224,7 @@ pub(super) fn overwrite_tasks(
                 &addr.town_worker,
                 body.worker_id,
                 &validated.new_tasks,
+                &validated.repeating_tasks,
                 validated.village_id,
             );
         }
@@ -227,8 +321,8 @@ pub(crate) fn create_attack(
                 no_delay: false,
             },
         )
-        .and_then(move |pa| attack_funnel.try_send(pa).map_err(internal_server_error))
-        .map(|()| HttpResponse::Ok().into())
+        .and_then(move |pa| attack_funnel.send(pa).map_err(internal_server_error))
+        .map(attacks::attack_funnel_response)
 }
 
 fn check_owns_worker(
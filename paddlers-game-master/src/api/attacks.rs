@@ -1,12 +1,13 @@
+use super::internal_server_error;
 use crate::authentication::Authentication;
-use crate::game_master::attack_funnel::PlannedAttack;
+use crate::game_master::attack_funnel::{AttackFunnelError, PlannedAttack};
 use crate::game_master::event::Event;
-use crate::game_master::town_worker::TownWorkerEventMsg;
+use crate::game_master::town_worker::{TownWorkerCancelVisitMsg, TownWorkerEventMsg};
 use actix::prelude::*;
 use actix_web::error::BlockingError;
 use actix_web::Responder;
 use actix_web::{web, HttpResponse};
-use paddlers_shared_lib::api::attacks::InvitationDescriptor;
+use paddlers_shared_lib::api::attacks::{AttackRecall, InvitationDescriptor};
 use paddlers_shared_lib::prelude::*;
 
 pub(crate) fn visitor_satisfied_notification(
@@ -25,6 +26,7 @@ pub(crate) fn new_invitation(
     auth: Authentication,
     addr: web::Data<crate::ActorAddresses>,
 ) -> impl Future<Item = HttpResponse, Error = actix_web::Error> {
+    let attack_funnel = addr.attack_funnel.clone();
     web::block(move || {
         // Check that request is valid and forward request to actor
         let db: crate::db::DB = pool.get_ref().into();
@@ -35,21 +37,68 @@ pub(crate) fn new_invitation(
         if !db.village_owned_by(destination_village.key(), auth.user.uuid) {
             return Err("Village not owned by player".to_owned());
         }
-        let atk = PlannedAttack {
+        Ok(PlannedAttack {
             origin_village,
             destination_village,
             hobos,
             no_delay: false,
-        };
-        addr.attack_funnel
-            .try_send(atk)
-            .map_err(|e| format!("Spawning attack failed: {:?}", e))
+        })
     })
-    .then(
-        |result: Result<(), BlockingError<std::string::String>>| match result {
-            Err(BlockingError::Error(msg)) => Ok(HttpResponse::Forbidden().body(msg).into()),
-            Err(BlockingError::Canceled) => Ok(HttpResponse::InternalServerError().into()),
-            Ok(()) => Ok(HttpResponse::Ok().into()),
-        },
-    )
+    .map_err(|e: BlockingError<std::string::String>| match e {
+        BlockingError::Error(msg) => HttpResponse::Forbidden().body(msg).into(),
+        BlockingError::Canceled => internal_server_error("Canceled"),
+    })
+    .and_then(move |atk| {
+        attack_funnel
+            .send(atk)
+            .map_err(internal_server_error)
+            .map(attack_funnel_response)
+    })
+}
+
+/// Cancels an outgoing visit that is still travelling.
+/// The visitors stay in their nest and are immediately available for another visit.
+pub(crate) fn recall_attack(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<AttackRecall>,
+    auth: Authentication,
+    addr: web::Data<crate::ActorAddresses>,
+) -> HttpResponse {
+    let db: crate::db::DB = pool.get_ref().into();
+    let atk = match db.attack(body.attack) {
+        Some(atk) => atk,
+        None => return HttpResponse::BadRequest().body("Attack not found"),
+    };
+    match atk.origin() {
+        Some(origin) => {
+            if let Err(e) = super::check_owns_village(&db, &auth, origin) {
+                return e;
+            }
+        }
+        None => return HttpResponse::Forbidden().body("Attack has no origin village"),
+    }
+    // Read before deleting, the links to the hobos are removed together with the attack
+    let hobos = db.attack_hobos(atk.key()).iter().map(|h| h.key()).collect();
+    match db.delete_attack_before_arrival(atk.key()) {
+        Ok(true) => {
+            addr.town_worker
+                .do_send(TownWorkerCancelVisitMsg { attack: atk, hobos });
+            HttpResponse::Ok().into()
+        }
+        Ok(false) => HttpResponse::BadRequest().body("Attack has already arrived"),
+        Err(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
+
+/// Translates the result of the attack funnel into a response for the HTTP caller
+pub(super) fn attack_funnel_response(result: Result<AttackKey, AttackFunnelError>) -> HttpResponse {
+    match result {
+        Ok(_) => HttpResponse::Ok().into(),
+        Err(e @ AttackFunnelError::NoHoboAvailable) | Err(e @ AttackFunnelError::HobosBusy(_)) => {
+            HttpResponse::Conflict().body(e.to_string())
+        }
+        Err(e @ AttackFunnelError::DbError(_)) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
 }
@@ -0,0 +1,75 @@
+//! Game master API for trading resources with other players
+
+use crate::authentication::Authentication;
+use crate::db::DB;
+use crate::game_master::event::Event;
+use crate::game_master::town_worker::TownWorkerEventMsg;
+use crate::resource_system::MarketError;
+use actix_web::{web, HttpResponse};
+use paddlers_shared_lib::api::market::{
+    TradeOfferAcceptance, TradeOfferCancellation, TradeOfferCreation,
+};
+use paddlers_shared_lib::sql::GameDB;
+
+pub(crate) fn create_trade_offer(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<TradeOfferCreation>,
+    auth: Authentication,
+) -> HttpResponse {
+    let db: DB = pool.get_ref().into();
+    if let Err(e) = super::check_owns_village(&db, &auth, body.village) {
+        return e;
+    }
+    match db.try_post_trade_offer(&body.0) {
+        Ok(_offer) => HttpResponse::Ok().into(),
+        Err(e) => market_error_response(e),
+    }
+}
+
+pub(crate) fn accept_trade_offer(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<TradeOfferAcceptance>,
+    auth: Authentication,
+    addr: web::Data<crate::ActorAddresses>,
+) -> HttpResponse {
+    let db: DB = pool.get_ref().into();
+    if let Err(e) = super::check_owns_village(&db, &auth, body.village) {
+        return e;
+    }
+    match db.try_accept_trade_offer(body.offer, body.village) {
+        Ok(shipments) => {
+            for shipment in &shipments {
+                let (event, time) = Event::deliver_shipment_at(shipment);
+                addr.town_worker.do_send(TownWorkerEventMsg(event, time));
+            }
+            HttpResponse::Ok().into()
+        }
+        Err(e) => market_error_response(e),
+    }
+}
+
+pub(crate) fn cancel_trade_offer(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<TradeOfferCancellation>,
+    auth: Authentication,
+) -> HttpResponse {
+    let db: DB = pool.get_ref().into();
+    let offer = match db.trade_offer(body.offer) {
+        Some(offer) => offer,
+        None => return HttpResponse::BadRequest().body("Offer is no longer open"),
+    };
+    if let Err(e) = super::check_owns_village(&db, &auth, offer.village()) {
+        return e;
+    }
+    match db.try_cancel_trade_offer(body.offer) {
+        Ok(()) => HttpResponse::Ok().into(),
+        Err(e) => market_error_response(e),
+    }
+}
+
+fn market_error_response(e: MarketError) -> HttpResponse {
+    match e {
+        MarketError::Invalid(msg) => HttpResponse::BadRequest().body(msg),
+        MarketError::DbError(e) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
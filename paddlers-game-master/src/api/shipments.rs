@@ -0,0 +1,30 @@
+//! Game master API for sending resources to other villages
+
+use crate::authentication::Authentication;
+use crate::db::DB;
+use crate::game_master::event::Event;
+use crate::game_master::town_worker::TownWorkerEventMsg;
+use crate::resource_system::ShippingError;
+use actix_web::{web, HttpResponse};
+use paddlers_shared_lib::api::shipments::ShipmentOrder;
+
+pub(crate) fn ship_resources(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<ShipmentOrder>,
+    auth: Authentication,
+    addr: web::Data<crate::ActorAddresses>,
+) -> HttpResponse {
+    let db: DB = pool.get_ref().into();
+    if let Err(e) = super::check_owns_village(&db, &auth, body.from) {
+        return e;
+    }
+    match db.try_ship(&body.0) {
+        Ok(shipment) => {
+            let (event, time) = Event::deliver_shipment_at(&shipment);
+            addr.town_worker.do_send(TownWorkerEventMsg(event, time));
+            HttpResponse::Ok().into()
+        }
+        Err(ShippingError::Invalid(msg)) => HttpResponse::BadRequest().body(msg),
+        Err(ShippingError::DbError(e)) => HttpResponse::InternalServerError().body(e.to_string()),
+    }
+}
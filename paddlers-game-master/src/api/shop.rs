@@ -1,5 +1,9 @@
 mod buildings;
+#[cfg(test)]
+mod buildings_test;
 mod prophets;
+mod workers;
 
 pub use buildings::*;
 pub use prophets::*;
+pub use workers::*;
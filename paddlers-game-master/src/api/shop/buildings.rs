@@ -1,8 +1,24 @@
 use crate::buildings::BuildingFactory;
 use crate::db::DB;
+use crate::town_view::TownView;
 use crate::StringErr;
+use diesel::Connection;
+use paddlers_shared_lib::game_mechanics::town::{
+    validate_building_placement, validate_building_relocation, UnitPosition,
+};
 use paddlers_shared_lib::story::story_state::StoryState;
-use paddlers_shared_lib::{api::shop::*, game_mechanics::attributes::Attributes, prelude::*};
+use paddlers_shared_lib::{
+    api::shop::*,
+    game_mechanics::attributes::{Attributes, LeveledBuilding},
+    prelude::*,
+};
+
+/// Reasons for refusing to upgrade, move or delete a building
+#[derive(Debug)]
+pub enum BuildingChangeError {
+    Invalid(String),
+    DbError(diesel::result::Error),
+}
 
 impl DB {
     pub fn try_buy_building(
@@ -17,6 +33,44 @@ impl DB {
                 self.insert_building(&BuildingFactory::new(typ, pos, village));
             })
     }
+    /// Upgrades the building anchored at the given position by one level.
+    /// Defending buildings cannot be upgraded while visitors are in town, as the
+    /// outcome of a visit is computed with the buildings as they are stored now.
+    pub fn try_upgrade_building(
+        &self,
+        pos: (usize, usize),
+        village: VillageKey,
+    ) -> Result<(), BuildingChangeError> {
+        self.dbconn().transaction(|| {
+            // The lock prevents paying twice for the same level and visits from arriving in the meantime
+            self.lock_village(village)?;
+            let building = self
+                .find_building_by_coordinates(pos.0 as i32, pos.1 as i32, village)
+                .ok_or_else(|| {
+                    BuildingChangeError::Invalid(format!("No building at {}|{}", pos.0, pos.1))
+                })?;
+            let next = LeveledBuilding::new(building.building_type, building.level)
+                .next_level()
+                .ok_or_else(|| {
+                    BuildingChangeError::Invalid("Building is at maximum level".to_owned())
+                })?;
+            self.check_defence_can_change(&building)?;
+            self.try_spend(&next.price(), village)
+                .map_err(BuildingChangeError::Invalid)?;
+            self.update_building_level(&building, next)?;
+            Ok(())
+        })
+    }
+    /// Buildings that affect visitors must stay as they are until all visitors in town have left
+    fn check_defence_can_change(&self, building: &Building) -> Result<(), BuildingChangeError> {
+        let now = chrono::Utc::now().naive_utc();
+        if building.building_range.is_some() && self.visit_in_progress(building.village(), now)? {
+            return Err(BuildingChangeError::Invalid(
+                "Visitors are in town, try again after they have left".to_owned(),
+            ));
+        }
+        Ok(())
+    }
     /// Check for events to be executed upon inserting new buildings
     pub fn building_insertion_triggers(
         &self,
@@ -42,51 +96,124 @@ impl DB {
         pos: (usize, usize),
         village: VillageKey,
     ) -> StringErr {
-        // Check conflict with existing building
-        let (w, h) = typ.size();
-        debug_assert_eq!(w, 1, "Not implemented yet");
-        debug_assert_eq!(h, 1, "Not implemented yet");
-        let (x0, y0) = (pos.0 as usize, pos.1 as usize);
-        // let(x1,y1) = (x0+w, y0+h);
-        for other in self.buildings(village) {
-            let typ: BuildingType = other.building_type;
-            let (w, h) = typ.size();
-            debug_assert_eq!(w, 1, "Not implemented yet");
-            debug_assert_eq!(h, 1, "Not implemented yet");
-            let (x, y) = (other.x as usize, other.y as usize);
-            if x == x0 && y == y0 {
-                return Err("Space occupied".to_owned());
-            }
-        }
-
-        // Check conflict with map
-        // Note: Cleaner handling of map shape might be necessary in the future
-        if y0 == 6 {
-            return Err("Cannot build here".to_owned());
+        let town = TownView::load_village(self, village);
+        let units = self.placement_units(village);
+        validate_building_placement(&town.map, town.layout, &units, typ, pos)
+            .map_err(|e| e.to_string())
+    }
+    /// Moves a building to a new position, applying the same rules as for placing new buildings.
+    /// Hobos living in a nest refer to the building and therefore move along.
+    /// Defending buildings cannot be moved while visitors are in town.
+    pub fn try_relocate_building(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        village: VillageKey,
+    ) -> Result<(), BuildingChangeError> {
+        self.dbconn().transaction(|| {
+            // The lock keeps the town as validated until the building has been moved
+            self.lock_village(village)?;
+            let building = self
+                .find_building_by_coordinates(from.0 as i32, from.1 as i32, village)
+                .ok_or_else(|| {
+                    BuildingChangeError::Invalid(format!("No building at {}|{}", from.0, from.1))
+                })?;
+            self.check_defence_can_change(&building)?;
+            let town = TownView::load_village(self, village);
+            let units = self.placement_units(village);
+            validate_building_relocation(
+                &town.map,
+                &town.state,
+                town.layout,
+                &units,
+                building.building_type,
+                from,
+                to,
+            )
+            .map_err(|e| BuildingChangeError::Invalid(e.to_string()))?;
+            self.move_building(&building, to)?;
+            Ok(())
+        })
+    }
+    /// Deletes a building, unless its type prevents it.
+    /// Hobos living in a deleted nest move to other nests of the village, as far as there is space.
+    /// The remaining hobos are released and leave the town for good.
+    /// Nests cannot be deleted while any of their hobos is visiting another town.
+    pub fn try_delete_building(&self, building: &Building) -> Result<(), BuildingChangeError> {
+        if !building.building_type.can_be_deleted() {
+            return Err(BuildingChangeError::Invalid(
+                "This building cannot be deleted".to_owned(),
+            ));
         }
-
-        // Check conflict with stationary units
-        let workers = self.workers(village);
-        let (x0, y0) = (pos.0 as i32, pos.1 as i32);
-        for w in workers {
-            if w.x == x0 && w.y == y0 {
-                return Err("Unit blocks space".to_owned());
+        let village = building.village();
+        self.dbconn().transaction(|| {
+            // The lock prevents concurrent deletions from moving hobos into each other's nest
+            self.lock_village(village)?;
+            let hobos: Vec<HoboKey> = self
+                .hobos_in_nest(building.key())
+                .iter()
+                .map(|h| h.key())
+                .collect();
+            // The locks prevent the hobos from leaving for a visit in the meantime
+            self.lock_hobos(&hobos)?;
+            if hobos
+                .iter()
+                .any(|h| self.hobo_is_attacking(*h) || self.hobo_is_returning(*h))
+            {
+                return Err(BuildingChangeError::Invalid(
+                    "Hobos of this nest are visiting another town".to_owned(),
+                ));
             }
-        }
-        // Check conflict with walking units
-        let workers = self.workers_with_job(village, &[TaskType::Walk]);
-        for w in workers {
-            let mut worker_x = w.x;
-            let mut worker_y = w.y;
-            for task in self.worker_tasks(w.key()) {
-                if is_between(x0, worker_x, task.x) || is_between(y0, worker_y, task.y) {
-                    return Err("Walking unit blocks space".to_owned());
+            let mut free_spots = self.free_nest_spots(village, building.key());
+            for hobo in hobos {
+                match free_spots.iter_mut().find(|(_, free)| *free > 0) {
+                    Some((nest, free)) => {
+                        *free -= 1;
+                        self.set_hobo_nest(hobo, Some(*nest))?;
+                    }
+                    None => {
+                        self.delete_hobo(hobo)?;
+                    }
                 }
-                worker_x = task.x;
-                worker_y = task.y;
             }
+            self.delete_building(building)?;
+            Ok(())
+        })
+    }
+    /// Nests of the village, except the given one, with the number of hobos that still fit in
+    fn free_nest_spots(
+        &self,
+        village: VillageKey,
+        except: BuildingKey,
+    ) -> Vec<(BuildingKey, usize)> {
+        self.buildings(village)
+            .into_iter()
+            .filter(|b| b.key() != except && b.building_type.nest_capacity() > 0)
+            .map(|b| {
+                let hobos = self.hobos_in_nest(b.key()).len();
+                (
+                    b.key(),
+                    b.building_type.nest_capacity().saturating_sub(hobos),
+                )
+            })
+            .collect()
+    }
+    /// Positions of all workers in the village and the paths of those currently walking
+    pub(crate) fn placement_units(&self, village: VillageKey) -> Vec<UnitPosition> {
+        let mut units: Vec<UnitPosition> = self
+            .workers(village)
+            .into_iter()
+            .map(|w| UnitPosition::stationary((w.x as usize, w.y as usize)))
+            .collect();
+        for w in self.workers_with_job(village, &[TaskType::Walk]) {
+            let path = self
+                .worker_tasks(w.key())
+                .into_iter()
+                .map(|task| (task.x as usize, task.y as usize))
+                .collect();
+            units.push(UnitPosition::walking((w.x as usize, w.y as usize), path));
         }
-        Ok(())
+        units
     }
     pub fn player_allowed_to_build(
         &self,
@@ -98,6 +225,8 @@ impl DB {
     }
 }
 
-fn is_between(x: i32, a: i32, b: i32) -> bool {
-    (x - a) * (x - b) <= 0
+impl From<diesel::result::Error> for BuildingChangeError {
+    fn from(e: diesel::result::Error) -> Self {
+        BuildingChangeError::DbError(e)
+    }
 }
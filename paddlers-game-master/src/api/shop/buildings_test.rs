@@ -0,0 +1,271 @@
+//! These tests need a running database, configured the same way as for the game-master itself.
+//! They are ignored by default, run them with `cargo test -- --ignored`.
+
+use super::*;
+use crate::buildings::BuildingFactory;
+use crate::db::testing::*;
+use crate::db::*;
+use paddlers_shared_lib::api::shop::Cost;
+use paddlers_shared_lib::game_mechanics::attributes::LeveledBuilding;
+use paddlers_shared_lib::prelude::*;
+
+const FLOWERS_POS: (usize, usize) = (1, 1);
+
+#[test]
+#[ignore]
+fn upgrade_is_paid_once_per_level() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_town_with_flowers(&db);
+    // Enough for exactly one upgrade
+    give_resources(
+        &db,
+        &village,
+        LeveledBuilding::new(BuildingType::BlueFlowers, 2),
+    );
+
+    db.try_upgrade_building(FLOWERS_POS, village.key())
+        .expect("Upgrade failed");
+    match db.try_upgrade_building(FLOWERS_POS, village.key()) {
+        Err(BuildingChangeError::Invalid(_)) => {}
+        other => panic!("Second upgrade was not refused: {:?}", other),
+    }
+    assert_eq!(flowers_level(&db, &village), 2);
+    for (res, _) in BuildingType::BlueFlowers.cost() {
+        assert_eq!(db.resource(res, village.key()), 0);
+    }
+
+    delete_test_village(&db, &village);
+}
+
+#[test]
+#[ignore]
+fn concurrent_upgrades_are_paid_once() {
+    let pool = DB::new_pool();
+    let db: DB = (&pool).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_town_with_flowers(&db);
+    give_resources(
+        &db,
+        &village,
+        LeveledBuilding::new(BuildingType::BlueFlowers, 2),
+    );
+
+    let threads: Vec<_> = (0..4)
+        .map(|_| {
+            let pool = pool.clone();
+            let vid = village.key();
+            std::thread::spawn(move || {
+                let db: DB = (&pool).into();
+                db.try_upgrade_building(FLOWERS_POS, vid).is_ok()
+            })
+        })
+        .collect();
+    let upgrades = threads
+        .into_iter()
+        .filter(|t| t.join().expect("Thread panicked"))
+        .count();
+    assert_eq!(upgrades, 1);
+    assert_eq!(flowers_level(&db, &village), 2);
+
+    delete_test_village(&db, &village);
+}
+
+#[test]
+#[ignore]
+fn no_upgrade_of_defence_while_visitors_in_town() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_town_with_flowers(&db);
+    let price = LeveledBuilding::new(BuildingType::BlueFlowers, 2);
+    give_resources(&db, &village, price);
+    let visit = insert_arrived_visit(&db, &village);
+
+    match db.try_upgrade_building(FLOWERS_POS, village.key()) {
+        Err(BuildingChangeError::Invalid(_)) => {}
+        other => panic!("Upgrade was not refused: {:?}", other),
+    }
+    assert_eq!(flowers_level(&db, &village), 1);
+    for (res, n) in price.cost() {
+        assert_eq!(db.resource(res, village.key()), n, "{} was spent", res);
+    }
+
+    db.delete_attack_hobos(visit.key());
+    db.delete_attack(&visit).expect("Deleting attack failed");
+    delete_test_village(&db, &village);
+}
+
+#[test]
+#[ignore]
+fn no_relocation_of_defence_while_visitors_in_town() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_town_with_flowers(&db);
+    let station = (2, 1);
+    db.insert_building(&BuildingFactory::new(
+        BuildingType::BundlingStation,
+        station,
+        village.key(),
+    ));
+    let visit = insert_arrived_visit(&db, &village);
+
+    match db.try_relocate_building(FLOWERS_POS, (1, 5), village.key()) {
+        Err(BuildingChangeError::Invalid(_)) => {}
+        other => panic!("Relocation was not refused: {:?}", other),
+    }
+    assert_eq!(flowers_level(&db, &village), 1);
+    // Buildings that do not affect visitors can still be moved
+    db.try_relocate_building(station, (2, 5), village.key())
+        .expect("Relocation failed");
+    assert!(db
+        .find_building_by_coordinates(2, 5, village.key())
+        .is_some());
+
+    db.delete_attack_hobos(visit.key());
+    db.delete_attack(&visit).expect("Deleting attack failed");
+    db.try_relocate_building(FLOWERS_POS, (1, 5), village.key())
+        .expect("Relocation after the visit failed");
+    assert!(db
+        .find_building_by_coordinates(1, 5, village.key())
+        .is_some());
+
+    delete_test_village(&db, &village);
+}
+
+#[test]
+#[ignore]
+fn deleted_nest_fills_other_nests_and_releases_the_rest() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_test_village(&db);
+    let (deleted, _) = insert_nest(&db, &village, BuildingType::TripleNest, (1, 1), 3);
+    let (partly_full, _) = insert_nest(&db, &village, BuildingType::TripleNest, (2, 1), 2);
+    let (full, _) = insert_nest(&db, &village, BuildingType::SingleNest, (3, 1), 1);
+
+    db.try_delete_building(&deleted)
+        .expect("Deleting nest failed");
+
+    assert!(db
+        .find_building_by_coordinates(1, 1, village.key())
+        .is_none());
+    assert_eq!(db.hobos_in_nest(partly_full.key()).len(), 3);
+    assert_eq!(db.hobos_in_nest(full.key()).len(), 1);
+    // Two hobos have been released
+    assert_eq!(db.hobos(village.key()).len(), 4);
+
+    delete_test_village(&db, &village);
+}
+
+#[test]
+#[ignore]
+fn no_nest_deletion_while_hobos_visit_another_town() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_test_village(&db);
+    let (attacking, hobos) = insert_nest(&db, &village, BuildingType::SingleNest, (1, 1), 1);
+    let now = chrono::Utc::now().naive_utc();
+    let visit = NewAttack {
+        departure: now,
+        arrival: now + chrono::Duration::minutes(1),
+        origin_village_id: Some(village.id),
+        destination_village_id: village.id,
+    };
+    let visit = db
+        .insert_attack_with_hobos(&visit, &[(hobos[0].key(), 0)])
+        .expect("Inserting visit");
+    let (returning, hobos) = insert_nest(&db, &village, BuildingType::SingleNest, (2, 1), 1);
+    let trip = NewReturnTrip {
+        departure: now,
+        arrival: now + chrono::Duration::minutes(1),
+        home_village_id: village.id,
+        visited_village_id: None,
+    };
+    db.insert_return_trip_with_hobos(&trip, &[(hobos[0].key(), true)])
+        .expect("Inserting return trip");
+
+    for nest in &[attacking, returning] {
+        match db.try_delete_building(nest) {
+            Err(BuildingChangeError::Invalid(_)) => {}
+            other => panic!("Deletion was not refused: {:?}", other),
+        }
+        assert!(db
+            .find_building_by_coordinates(nest.x, nest.y, village.key())
+            .is_some());
+        // Busy hobos still live in the nest but cannot be invited anywhere
+        assert_eq!(db.hobos_in_nest(nest.key()).len(), 1);
+        assert!(db.idle_hobos_in_nest(nest.key()).is_empty());
+    }
+    assert_eq!(db.hobos(village.key()).len(), 2);
+
+    db.delete_attack_hobos(visit.key());
+    db.delete_attack(&visit).expect("Deleting attack failed");
+    delete_test_village(&db, &village);
+}
+
+fn insert_town_with_flowers(db: &DB) -> Village {
+    let village = insert_test_village(db);
+    db.init_resources(village.key());
+    db.insert_building(&BuildingFactory::new(
+        BuildingType::BlueFlowers,
+        FLOWERS_POS,
+        village.key(),
+    ));
+    village
+}
+
+fn give_resources(db: &DB, village: &Village, price: impl Cost) {
+    for (res, n) in price.cost() {
+        db.add_resource(res, village.key(), n)
+            .expect("Adding resources");
+    }
+}
+
+fn flowers_level(db: &DB, village: &Village) -> i32 {
+    db.find_building_by_coordinates(FLOWERS_POS.0 as i32, FLOWERS_POS.1 as i32, village.key())
+        .expect("Flowers are gone")
+        .level
+}
+
+/// A visitor that arrived a minute ago and is still in town
+fn insert_arrived_visit(db: &DB, village: &Village) -> Attack {
+    let now = chrono::Utc::now().naive_utc();
+    let hobo = db.insert_hobo(&NewHobo {
+        hp: 100,
+        home: village.id,
+        color: None,
+        speed: 0.1,
+        hurried: true,
+        nest: None,
+    });
+    let visit = NewAttack {
+        departure: now - chrono::Duration::minutes(2),
+        arrival: now - chrono::Duration::minutes(1),
+        origin_village_id: None,
+        destination_village_id: village.id,
+    };
+    db.insert_attack_with_hobos(&visit, &[(hobo.key(), 0)])
+        .expect("Inserting visit")
+}
+
+fn insert_nest(
+    db: &DB,
+    village: &Village,
+    typ: BuildingType,
+    pos: (usize, usize),
+    hobos: usize,
+) -> (Building, Vec<Hobo>) {
+    let nest = db.insert_building(&BuildingFactory::new(typ, pos, village.key()));
+    let hobos = (0..hobos)
+        .map(|_| {
+            db.insert_hobo(&NewHobo {
+                hp: 1,
+                home: village.id,
+                color: None,
+                speed: 0.1,
+                hurried: true,
+                nest: Some(nest.id),
+            })
+        })
+        .collect();
+    (nest, hobos)
+}
@@ -0,0 +1,98 @@
+use crate::db::DB;
+use crate::town_view::TownView;
+use diesel::{Connection, QueryResult};
+use paddlers_shared_lib::{api::shop::*, game_mechanics::town::*, prelude::*};
+
+/// Reasons for refusing to recruit a worker
+#[derive(Debug)]
+pub enum WorkerPurchaseError {
+    /// The village cannot house or afford another worker
+    Invalid(String),
+    DbError(diesel::result::Error),
+}
+
+impl DB {
+    pub fn try_buy_worker(&self, village: VillageKey) -> Result<(), WorkerPurchaseError> {
+        // The lock prevents concurrent purchases from exceeding the housing or spending resources twice
+        self.dbconn().transaction(|| {
+            self.lock_village(village)?;
+            let mut town = TownView::load_village(self, village);
+            town.state
+                .try_recruit_worker(TaskType::Idle)
+                .map_err(|e| WorkerPurchaseError::Invalid(e.to_string()))?;
+            let pos = self
+                .free_tile_next_to_shack(&town, village)
+                .ok_or_else(|| {
+                    WorkerPurchaseError::Invalid("No space in front of a shack".to_owned())
+                })?;
+            self.try_spend(&UnitType::Basic.price(), village)
+                .map_err(WorkerPurchaseError::Invalid)?;
+            self.insert_basic_worker(village, pos)?;
+            Ok(())
+        })
+    }
+
+    /// Finds an empty tile next to a shack where no worker is standing
+    fn free_tile_next_to_shack(&self, town: &TownView, village: VillageKey) -> Option<TileIndex> {
+        let occupied: Vec<TileIndex> = self
+            .workers(village)
+            .into_iter()
+            .map(|w| (w.x as usize, w.y as usize))
+            .collect();
+        self.buildings(village)
+            .into_iter()
+            .filter(|b| b.building_type == BuildingType::Shack)
+            .flat_map(|b| {
+                let (x, y) = (b.x as usize, b.y as usize);
+                vec![
+                    (x + 1, y),
+                    (x, y + 1),
+                    (x.wrapping_sub(1), y),
+                    (x, y.wrapping_sub(1)),
+                ]
+            })
+            .find(|idx| {
+                town.map.tile_type(*idx) == Some(&TownTileType::EMPTY) && !occupied.contains(idx)
+            })
+    }
+
+    fn insert_basic_worker(&self, vid: VillageKey, (x, y): TileIndex) -> QueryResult<Worker> {
+        let worker = NewWorker {
+            unit_type: UnitType::Basic,
+            x: x as i32,
+            y: y as i32,
+            color: Some(UnitColor::Yellow),
+            speed: 0.4,
+            home: vid.num(),
+            mana: None,
+            level: 1,
+            exp: 0,
+        };
+        let worker = self.insert_worker(&worker)?;
+        let task = NewTask {
+            worker_id: worker.id,
+            task_type: TaskType::Idle,
+            x: x as i32,
+            y: y as i32,
+            start_time: None,
+            target_hobo_id: None,
+        };
+        self.insert_task(&task)?;
+        self.insert_ability(&NewAbility {
+            worker_id: worker.id,
+            ability_type: AbilityType::Work,
+        })?;
+        self.insert_worker_flag(WorkerFlag {
+            worker_id: worker.id,
+            flag_type: WorkerFlagType::Work,
+            last_update: chrono::Utc::now().naive_utc(),
+        })?;
+        Ok(worker)
+    }
+}
+
+impl From<diesel::result::Error> for WorkerPurchaseError {
+    fn from(e: diesel::result::Error) -> Self {
+        WorkerPurchaseError::DbError(e)
+    }
+}
@@ -69,10 +69,11 @@ impl DB {
         println!("Player changes to {:?}", new_state);
         match new_state {
             StoryState::TempleBuilt => {
-                // Send first attacker
+                // Send first attacker to the home village
                 let village = self
                     .player_villages(player)
-                    .pop()
+                    .into_iter()
+                    .next()
                     .expect("player must have at least one village");
                 // TODO: This should be something like a prefab
                 let hobo = NewHobo {
@@ -96,6 +97,11 @@ impl DB {
                         };
                         addr.attack_funnel.send(pa)
                     })
+                    .map(|result| {
+                        if let Err(e) = result {
+                            eprintln!("Attack spawn failed: {}", e);
+                        }
+                    })
                     .map_err(|e| eprintln!("Attack spawn failed: {:?}", e));
                 Arbiter::spawn(future);
             }
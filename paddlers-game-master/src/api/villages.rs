@@ -0,0 +1,92 @@
+//! Game master API for players ruling over more than one village
+
+use crate::authentication::Authentication;
+use crate::db::DB;
+use actix_web::{web, HttpResponse};
+use diesel::Connection;
+use paddlers_shared_lib::api::villages::VillageFoundation;
+use paddlers_shared_lib::prelude::*;
+
+/// Reasons for refusing to send a prophet to a village
+#[derive(Debug)]
+enum VillageFoundationError {
+    /// The player has no control over the prophet or the prophet is busy
+    Forbidden(&'static str),
+    /// The position cannot be ruled by the prophet
+    InvalidPosition(&'static str),
+    DbError(diesel::result::Error),
+}
+
+/// Consumes an idle prophet of the player to found a new village or to convert an anarchist village.
+/// Prophets are unlocked with karma, hence the karma of a player limits the number of villages.
+pub(crate) fn found_village(
+    pool: web::Data<crate::db::Pool>,
+    body: web::Json<VillageFoundation>,
+    mut auth: Authentication,
+) -> HttpResponse {
+    let db: DB = pool.get_ref().into();
+    let player = match auth.player_object(&db) {
+        Some(p) => p.key(),
+        None => return HttpResponse::BadRequest().body("No such player"),
+    };
+    match db.try_found_village(player, body.prophet, body.position) {
+        Ok(_) => HttpResponse::Ok().into(),
+        Err(VillageFoundationError::Forbidden(msg)) => HttpResponse::Forbidden().body(msg),
+        Err(VillageFoundationError::InvalidPosition(msg)) => HttpResponse::Conflict().body(msg),
+        Err(VillageFoundationError::DbError(e)) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+impl DB {
+    fn try_found_village(
+        &self,
+        player: PlayerKey,
+        prophet: HoboKey,
+        (x, y): (i32, i32),
+    ) -> Result<Village, VillageFoundationError> {
+        // The lock on the prophet prevents it from founding two villages at once
+        self.dbconn().transaction(|| {
+            self.lock_hobos(&[prophet])?;
+            let hobo = self
+                .hobo(prophet)
+                .ok_or(VillageFoundationError::Forbidden("No such prophet"))?;
+            if hobo.color != Some(UnitColor::Prophet) {
+                return Err(VillageFoundationError::Forbidden("Hobo is not a prophet"));
+            }
+            let home = self.village(VillageKey(hobo.home)).map(|v| v.player_id);
+            if home != Some(Some(player.num())) {
+                return Err(VillageFoundationError::Forbidden(
+                    "Prophet not owned by player",
+                ));
+            }
+            if self.hobo_is_attacking(prophet) || self.hobo_is_returning(prophet) {
+                return Err(VillageFoundationError::Forbidden("Prophet is busy"));
+            }
+            let village = match self.village_at(x as f32, y as f32) {
+                Some(v) if v.player_id.is_none() => {
+                    self.claim_anarchist_village(player, v.key())
+                        .map_err(VillageFoundationError::InvalidPosition)?
+                }
+                Some(_) => {
+                    return Err(VillageFoundationError::InvalidPosition(
+                        "Village is already ruled by a player",
+                    ))
+                }
+                None => self
+                    .found_village(player, x, y)
+                    .map_err(VillageFoundationError::InvalidPosition)?,
+            };
+            // The prophet stays in the new village as its ruler
+            self.delete_hobo(prophet)?;
+            Ok(village)
+        })
+    }
+}
+
+impl From<diesel::result::Error> for VillageFoundationError {
+    fn from(e: diesel::result::Error) -> Self {
+        VillageFoundationError::DbError(e)
+    }
+}
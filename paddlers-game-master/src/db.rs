@@ -4,6 +4,10 @@ use paddlers_shared_lib::prelude::*;
 pub mod diesel_queries;
 pub use diesel_queries::*;
 mod db_actor;
+#[cfg(test)]
+mod diesel_queries_test;
+#[cfg(test)]
+pub(crate) mod testing;
 pub use db_actor::*;
 type Manager = ConnectionManager<PgConnection>;
 pub type Pool = r2d2::Pool<Manager>;
@@ -19,18 +19,6 @@ impl Handler<DeferredDbStatement> for DbActor {
             DeferredDbStatement::NewProphet(village) => {
                 self.db().add_prophet(village);
             }
-            DeferredDbStatement::NewAttack(planned_atk) => {
-                let attack = self.db().insert_attack(&planned_atk.attack);
-                for hobo in planned_atk.hobos.iter() {
-                    let atu = AttackToHobo {
-                        attack_id: attack.id,
-                        hobo_id: hobo.num(),
-                        satisfied: None,
-                        released: None,
-                    };
-                    self.db().insert_attack_to_hobo(&atu);
-                }
-            }
         }
     }
 }
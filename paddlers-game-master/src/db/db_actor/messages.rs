@@ -8,16 +8,10 @@ use paddlers_shared_lib::prelude::*;
 /// price should already be payed before-hand.
 pub enum DeferredDbStatement {
     NewProphet(VillageKey),
-    NewAttack(ScheduledAttack),
 }
 impl Message for DeferredDbStatement {
     type Result = ();
 }
-#[derive(Debug)]
-pub struct ScheduledAttack {
-    pub attack: NewAttack,
-    pub hobos: Vec<HoboKey>,
-}
 
 pub struct NewHoboMessage(pub NewHobo);
 pub struct NewHoboResponse(pub Hobo);
@@ -1,5 +1,6 @@
 use super::*;
 use diesel::prelude::*;
+use paddlers_shared_lib::game_mechanics::attributes::{Attributes, LeveledBuilding};
 use paddlers_shared_lib::models::dsl;
 use paddlers_shared_lib::schema::*;
 use paddlers_shared_lib::story::story_state::StoryState;
@@ -27,11 +28,27 @@ impl DB {
         }
     }
 
-    pub fn delete_attack(&self, atk: &Attack) {
-        let result = diesel::delete(atk).execute(self.dbconn());
-        if result.is_err() {
-            println!("Couldn't delete attack {:?}", atk);
-        }
+    /// Returns whether the attack existed before
+    pub fn delete_attack(&self, atk: &Attack) -> QueryResult<bool> {
+        let n = diesel::delete(atk).execute(self.dbconn())?;
+        Ok(n > 0)
+    }
+
+    /// Deletes an attack together with its links to hobos, unless it has already arrived.
+    /// Returns whether the attack has been deleted.
+    pub fn delete_attack_before_arrival(&self, atk: AttackKey) -> QueryResult<bool> {
+        let now = chrono::Utc::now().naive_utc();
+        let target = attacks::table
+            .find(atk.num())
+            .filter(attacks::arrival.gt(now));
+        let n = diesel::delete(target).execute(self.dbconn())?;
+        Ok(n > 0)
+    }
+
+    /// Returns whether the return trip existed before
+    pub fn delete_return_trip(&self, trip: &ReturnTrip) -> QueryResult<bool> {
+        let n = diesel::delete(trip).execute(self.dbconn())?;
+        Ok(n > 0)
     }
 
     pub fn insert_hobo(&self, u: &NewHobo) -> Hobo {
@@ -40,11 +57,10 @@ impl DB {
             .get_result(self.dbconn())
             .expect("Inserting hobo")
     }
-    pub fn insert_worker(&self, u: &NewWorker) -> Worker {
+    pub fn insert_worker(&self, u: &NewWorker) -> QueryResult<Worker> {
         diesel::insert_into(workers::dsl::workers)
             .values(u)
             .get_result(self.dbconn())
-            .expect("Inserting worker")
     }
     pub fn update_worker(&self, u: &Worker) {
         diesel::update(u)
@@ -52,24 +68,123 @@ impl DB {
             .execute(self.dbconn())
             .expect("Updating worker");
     }
-    pub fn add_worker_mana(&self, w: WorkerKey, plus: i32, max: i32) {
-        let worker = self.worker_priv(w).expect("Invalid worker key");
-        diesel::update(&worker)
-            .set(workers::mana.eq(Some(max.min(worker.mana.unwrap_or(0) + plus))))
-            .execute(self.dbconn())
-            .expect("Updating worker for mana");
-    }
-    pub fn insert_attack(&self, new_attack: &NewAttack) -> Attack {
-        diesel::insert_into(attacks::dsl::attacks)
+    /// Inserts a visit with its visitors, each listed with the spot where it will rest
+    pub fn insert_attack_with_hobos(
+        &self,
+        new_attack: &NewAttack,
+        hobos: &[(HoboKey, usize)],
+    ) -> QueryResult<Attack> {
+        let attack: Attack = diesel::insert_into(attacks::dsl::attacks)
             .values(new_attack)
+            .get_result(self.dbconn())?;
+        let atus: Vec<AttackToHobo> = hobos
+            .iter()
+            .map(|(hobo, spot)| AttackToHobo {
+                attack_id: attack.id,
+                hobo_id: hobo.num(),
+                satisfied: None,
+                released: None,
+                resting_spot: *spot as i32,
+            })
+            .collect();
+        diesel::insert_into(attacks_to_hobos::dsl::attacks_to_hobos)
+            .values(&atus)
+            .execute(self.dbconn())?;
+        Ok(attack)
+    }
+    /// Stores the hobos of a finished visit on their way home, together with whether they have been satisfied
+    pub fn insert_return_trip_with_hobos(
+        &self,
+        new_trip: &NewReturnTrip,
+        hobos: &[(HoboKey, bool)],
+    ) -> QueryResult<ReturnTrip> {
+        let trip: ReturnTrip = diesel::insert_into(return_trips::dsl::return_trips)
+            .values(new_trip)
+            .get_result(self.dbconn())?;
+        let rows: Vec<ReturnTripToHobo> = hobos
+            .iter()
+            .map(|(hobo, satisfied)| ReturnTripToHobo {
+                return_trip_id: trip.id,
+                hobo_id: hobo.num(),
+                satisfied: *satisfied,
+            })
+            .collect();
+        diesel::insert_into(return_trips_to_hobos::dsl::return_trips_to_hobos)
+            .values(&rows)
+            .execute(self.dbconn())?;
+        Ok(trip)
+    }
+    pub fn insert_shipment(&self, shipment: &NewShipment) -> QueryResult<Shipment> {
+        diesel::insert_into(shipments::dsl::shipments)
+            .values(shipment)
             .get_result(self.dbconn())
-            .expect("Inserting attack")
     }
-    pub fn insert_attack_to_hobo(&self, atu: &AttackToHobo) {
-        diesel::insert_into(attacks_to_hobos::dsl::attacks_to_hobos)
-            .values(atu)
-            .execute(self.dbconn())
-            .expect("Inserting attack to hobo");
+    /// Returns whether the shipment existed before
+    pub fn delete_shipment(&self, shipment: ShipmentKey) -> QueryResult<bool> {
+        let n = diesel::delete(shipments::table.find(shipment.num())).execute(self.dbconn())?;
+        Ok(n > 0)
+    }
+    pub fn insert_trade_offer(&self, offer: &NewTradeOffer) -> QueryResult<TradeOffer> {
+        diesel::insert_into(trade_offers::dsl::trade_offers)
+            .values(offer)
+            .get_result(self.dbconn())
+    }
+    /// Removes the offer from the marketplace and returns it, if it was still open.
+    /// Only one of several concurrent calls can receive the offer.
+    pub fn delete_trade_offer(&self, offer: TradeOfferKey) -> QueryResult<Option<TradeOffer>> {
+        diesel::delete(trade_offers::table.find(offer.num()))
+            .get_result(self.dbconn())
+            .optional()
+    }
+    /// Locks the row of the village until the end of the current transaction.
+    /// Used to serialize all changes that depend on the complete state of a village.
+    pub fn lock_village(&self, vid: VillageKey) -> QueryResult<()> {
+        villages::table
+            .find(vid.num())
+            .select(villages::id)
+            .for_update()
+            .get_result::<i64>(self.dbconn())
+            .map(|_| ())
+    }
+    /// Locks the rows of the hobos until the end of the current transaction.
+    pub fn lock_hobos(&self, hobos: &[HoboKey]) -> QueryResult<()> {
+        let ids: Vec<i64> = hobos.iter().map(HoboKey::num).collect();
+        hobos::table
+            .filter(hobos::id.eq_any(ids))
+            .order_by(hobos::id)
+            .select(hobos::id)
+            .for_update()
+            .load::<i64>(self.dbconn())
+            .map(|_| ())
+    }
+    /// Whether visitors have arrived in the village whose visit has not been evaluated, yet.
+    /// The outcome of such a visit depends on the defending buildings as they were when the visitors arrived.
+    pub fn visit_in_progress(
+        &self,
+        vid: VillageKey,
+        now: chrono::NaiveDateTime,
+    ) -> QueryResult<bool> {
+        diesel::select(diesel::dsl::exists(
+            attacks::table
+                .filter(attacks::destination_village_id.eq(vid.num()))
+                .filter(attacks::arrival.le(now)),
+        ))
+        .get_result(self.dbconn())
+    }
+    /// Resting spots held by unhurried visitors of the village which have not been released, yet.
+    /// Includes visitors still on their way, ordered by their arrival.
+    pub fn taken_resting_spots(&self, vid: VillageKey) -> QueryResult<Vec<usize>> {
+        attacks_to_hobos::table
+            .inner_join(attacks::table)
+            .inner_join(hobos::table)
+            .filter(attacks::destination_village_id.eq(vid.num()))
+            .filter(hobos::hurried.eq(false))
+            .filter(attacks_to_hobos::satisfied.is_null())
+            .filter(attacks_to_hobos::released.is_null())
+            .order_by((attacks::arrival.asc(), attacks::id.asc()))
+            .select(attacks_to_hobos::resting_spot)
+            .load::<i32>(self.dbconn())
+            .map(|spots| spots.into_iter().map(|s| s as usize).collect())
     }
     pub fn insert_resource(&self, res: &Resource) -> QueryResult<usize> {
         diesel::insert_into(dsl::resources)
@@ -99,16 +214,35 @@ impl DB {
             .get_result(self.dbconn())
             .expect("Inserting building")
     }
-    pub fn delete_building(&self, building: &Building) {
+    /// Sets the level of a building and the attributes that come with it
+    pub fn update_building_level(
+        &self,
+        building: &Building,
+        level: LeveledBuilding,
+    ) -> QueryResult<Building> {
+        diesel::update(buildings::table.find(building.id))
+            .set((
+                buildings::level.eq(level.level),
+                buildings::building_range.eq(level.range()),
+                buildings::attack_power.eq(level.attack_power().map(|i| i as i32)),
+                buildings::attacks_per_cycle.eq(level.attacks_per_cycle().map(|i| i as i32)),
+            ))
+            .get_result(self.dbconn())
+    }
+    /// Moves a building without changing anything else about it, including the creation time
+    pub fn move_building(&self, building: &Building, to: (usize, usize)) -> QueryResult<Building> {
+        diesel::update(buildings::table.find(building.id))
+            .set((buildings::x.eq(to.0 as i32), buildings::y.eq(to.1 as i32)))
+            .get_result(self.dbconn())
+    }
+    pub fn delete_building(&self, building: &Building) -> QueryResult<usize> {
         diesel::delete(buildings::table.filter(buildings::id.eq(building.id)))
             .execute(self.dbconn())
-            .expect("Deleting building");
     }
-    pub fn insert_task(&self, task: &NewTask) -> Task {
+    pub fn insert_task(&self, task: &NewTask) -> QueryResult<Task> {
         diesel::insert_into(tasks::dsl::tasks)
             .values(task)
             .get_result(self.dbconn())
-            .expect("Inserting task")
     }
 
     pub fn insert_tasks(&self, tasks: &[NewTask]) -> Vec<Task> {
@@ -134,6 +268,19 @@ impl DB {
             .execute(self.dbconn())
             .expect("Deleting task");
     }
+    pub fn insert_repeating_tasks(&self, tasks: &[NewRepeatingTask]) {
+        diesel::insert_into(repeating_tasks::dsl::repeating_tasks)
+            .values(tasks)
+            .execute(self.dbconn())
+            .expect("Inserting repeating tasks");
+    }
+    pub fn delete_repeating_tasks(&self, worker_id: WorkerKey) {
+        diesel::delete(
+            repeating_tasks::table.filter(repeating_tasks::worker_id.eq(worker_id.num())),
+        )
+        .execute(self.dbconn())
+        .expect("Deleting repeating tasks");
+    }
     pub fn insert_streams(&self, streams: &[NewStream]) -> Vec<Stream> {
         diesel::insert_into(streams::dsl::streams)
             .values(streams)
@@ -146,11 +293,61 @@ impl DB {
             .get_results(self.dbconn())
             .expect("Inserting villages")
     }
-    pub fn insert_ability(&self, a: &NewAbility) -> Ability {
+    /// Inserts a village unless there already is a village at the same position.
+    /// Returns None if the position is taken, which is safe to check also inside a transaction.
+    pub fn insert_village_if_position_free(
+        &self,
+        village: &NewVillage,
+    ) -> QueryResult<Option<Village>> {
+        diesel::insert_into(villages::dsl::villages)
+            .values(village)
+            .on_conflict_do_nothing()
+            .get_result(self.dbconn())
+            .optional()
+    }
+    /// Assigns a village without owner to a player.
+    /// Returns None if the village does not exist or already has an owner.
+    pub fn set_owner_of_anarchist_village(
+        &self,
+        vid: VillageKey,
+        pid: PlayerKey,
+    ) -> QueryResult<Option<Village>> {
+        let target = villages::table
+            .find(vid.num())
+            .filter(villages::player_id.is_null());
+        diesel::update(target)
+            .set(villages::player_id.eq(Some(pid.num())))
+            .get_result(self.dbconn())
+            .optional()
+    }
+    pub fn delete_hobo(&self, hobo: HoboKey) -> QueryResult<usize> {
+        diesel::delete(hobos::table.find(hobo.num())).execute(self.dbconn())
+    }
+    /// Moves a hobo into another nest, or out of any nest
+    pub fn set_hobo_nest(&self, hobo: HoboKey, nest: Option<BuildingKey>) -> QueryResult<usize> {
+        diesel::update(hobos::table.find(hobo.num()))
+            .set(hobos::nest.eq(nest.map(|n| n.num())))
+            .execute(self.dbconn())
+    }
+    pub fn insert_ability(&self, a: &NewAbility) -> QueryResult<Ability> {
         diesel::insert_into(abilities::dsl::abilities)
             .values(a)
             .get_result(self.dbconn())
-            .expect("Inserting ability")
+    }
+    /// Adds an ability unless the worker already has it
+    pub fn insert_ability_if_missing(&self, a: &NewAbility) -> QueryResult<usize> {
+        diesel::insert_into(abilities::dsl::abilities)
+            .values(a)
+            .on_conflict_do_nothing()
+            .execute(self.dbconn())
+    }
+    /// Heroes above level 1, which may have learned abilities on the way
+    pub fn leveled_heroes(&self) -> Vec<Worker> {
+        workers::table
+            .filter(workers::unit_type.eq(UnitType::Hero))
+            .filter(workers::level.gt(1))
+            .get_results(self.dbconn())
+            .expect("Loading heroes")
     }
     pub fn insert_effect(&self, e: &NewEffect) -> Effect {
         diesel::insert_into(effects::dsl::effects)
@@ -158,6 +355,12 @@ impl DB {
             .get_result(self.dbconn())
             .expect("Inserting effect")
     }
+    pub fn insert_bloom(&self, b: &NewBloom) -> Bloom {
+        diesel::insert_into(blooms::table)
+            .values(b)
+            .get_result(self.dbconn())
+            .expect("Inserting bloom")
+    }
     pub fn update_ability_used_timestamp(&self, worker: WorkerKey, at: AbilityType) {
         let target = abilities::table.find((at, worker.num()));
         diesel::update(target)
@@ -165,11 +368,10 @@ impl DB {
             .execute(self.dbconn())
             .expect("Updating ability timestamp");
     }
-    pub fn insert_worker_flag(&self, wf: WorkerFlag) {
+    pub fn insert_worker_flag(&self, wf: WorkerFlag) -> QueryResult<usize> {
         diesel::insert_into(worker_flags::dsl::worker_flags)
             .values(wf)
             .execute(self.dbconn())
-            .expect("Inserting flag");
     }
     pub fn update_worker_flag_timestamp_now(&self, w: WorkerKey, f: WorkerFlagType) {
         let target = worker_flags::table.find((w.num(), f));
@@ -178,23 +380,38 @@ impl DB {
             .execute(self.dbconn())
             .expect("Updating flag timestamp to now");
     }
-    pub fn update_worker_flag_timestamp(
+    /// Regenerates mana of all workers in player villages, based on the time passed since the last update.
+    /// Only full mana points are added, the flag timestamp is moved forward by exactly that many intervals.
+    /// Mana is capped at `max_mana_by_level[level - 1]`, the last entry applies to all higher levels.
+    pub fn regenerate_workers_mana(
         &self,
-        w: WorkerKey,
-        f: WorkerFlagType,
-        ts: chrono::NaiveDateTime,
-    ) {
-        let target = worker_flags::table.find((w.num(), f));
-        diesel::update(target)
-            .set(worker_flags::last_update.eq(ts))
+        now: chrono::NaiveDateTime,
+        interval_ms: i64,
+        max_mana_by_level: &[i32],
+    ) -> QueryResult<usize> {
+        diesel::sql_query(REGENERATE_MANA_SQL)
+            .bind::<diesel::sql_types::Timestamp, _>(now)
+            .bind::<diesel::sql_types::BigInt, _>(interval_ms)
+            .bind::<diesel::sql_types::Array<diesel::sql_types::Integer>, _>(max_mana_by_level)
             .execute(self.dbconn())
-            .expect("Updating flag timestamp");
     }
-    pub fn worker_flags(&self, worker: WorkerKey) -> Vec<WorkerFlag> {
-        worker_flags::table
-            .filter(worker_flags::worker_id.eq(worker.num()))
-            .get_results(self.dbconn())
-            .expect("Error loading data")
+    /// Adds the resources collected by all workers of the given unit type in player villages currently doing the given job.
+    /// Only full resource units are added, the flag timestamp is moved forward by exactly that many intervals.
+    pub fn collect_workers_resources(
+        &self,
+        now: chrono::NaiveDateTime,
+        unit_type: UnitType,
+        job: TaskType,
+        resource: ResourceType,
+        interval_ms: i64,
+    ) -> QueryResult<usize> {
+        diesel::sql_query(COLLECT_RESOURCES_SQL)
+            .bind::<diesel::sql_types::Timestamp, _>(now)
+            .bind::<diesel::sql_types::BigInt, _>(interval_ms)
+            .bind::<Task_type, _>(job)
+            .bind::<Resource_type, _>(resource)
+            .bind::<Unit_type, _>(unit_type)
+            .execute(self.dbconn())
     }
     pub fn insert_visit_report(&self, vr: NewVisitReport) -> VisitReport {
         diesel::insert_into(visit_reports::dsl::visit_reports)
@@ -229,3 +446,61 @@ impl DB {
             .expect("setting released");
     }
 }
+
+// Both queries compute the number of full intervals ($2 in ms) passed since the last update of a worker flag, up to $1.
+// The result is the same as `(now - last_update).num_milliseconds() / interval_ms` in Rust:
+// EXTRACT returns the seconds as a floating point number, which is exact to the microsecond for any realistic
+// duration. The cast to BIGINT rounds it back to whole microseconds, then both integer divisions truncate towards
+// zero, just like chrono::Duration::num_milliseconds() and the division in Rust do.
+// See diesel_queries_test.rs for a comparison on fixed timestamps.
+
+const REGENERATE_MANA_SQL: &str = "
+    WITH due AS (
+        SELECT f.worker_id,
+            ((EXTRACT(EPOCH FROM ($1 - f.last_update)) * 1000000)::BIGINT / 1000) / $2 AS n
+        FROM worker_flags f
+        JOIN workers w ON w.id = f.worker_id
+        JOIN villages v ON v.id = w.home
+        WHERE f.flag_type = 'mana_regeneration' AND v.player_id IS NOT NULL
+    ), flags AS (
+        UPDATE worker_flags f
+        SET last_update = f.last_update + (due.n * $2) * INTERVAL '1 millisecond'
+        FROM due
+        WHERE f.worker_id = due.worker_id AND f.flag_type = 'mana_regeneration' AND due.n > 0
+        RETURNING due.worker_id, due.n
+    )
+    UPDATE workers w
+    SET mana = LEAST(
+        COALESCE(w.mana, 0) + flags.n,
+        $3[GREATEST(1, LEAST(w.level, CARDINALITY($3)))]
+    )::INTEGER
+    FROM flags
+    WHERE w.id = flags.worker_id";
+
+const COLLECT_RESOURCES_SQL: &str = "
+    WITH due AS (
+        SELECT f.worker_id, w.home AS village_id,
+            ((EXTRACT(EPOCH FROM ($1 - f.last_update)) * 1000000)::BIGINT / 1000) / $2 AS n
+        FROM worker_flags f
+        JOIN workers w ON w.id = f.worker_id
+        JOIN villages v ON v.id = w.home
+        WHERE f.flag_type = 'work' AND v.player_id IS NOT NULL AND w.unit_type = $5
+        AND (
+            SELECT t.task_type FROM tasks t
+            WHERE t.worker_id = f.worker_id AND t.start_time <= $1
+            ORDER BY t.start_time ASC
+            LIMIT 1
+        ) = $3
+    ), flags AS (
+        UPDATE worker_flags f
+        SET last_update = f.last_update + (due.n * $2) * INTERVAL '1 millisecond'
+        FROM due
+        WHERE f.worker_id = due.worker_id AND f.flag_type = 'work' AND due.n > 0
+        RETURNING due.village_id, due.n
+    ), collected AS (
+        SELECT village_id, SUM(n)::BIGINT AS n FROM flags GROUP BY village_id
+    )
+    UPDATE resources r
+    SET amount = r.amount + collected.n
+    FROM collected
+    WHERE r.village_id = collected.village_id AND r.resource_type = $4";
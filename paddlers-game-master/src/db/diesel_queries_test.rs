@@ -0,0 +1,172 @@
+//! These tests need a running database, configured the same way as for the game-master itself.
+//! They are ignored by default, run them with `cargo test -- --ignored`.
+
+use super::testing::*;
+use super::*;
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use diesel::prelude::*;
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::schema::{resources, villages, worker_flags, workers};
+
+const MANA_INTERVAL_MS: i64 = 360_000;
+const COLLECTION_INTERVAL_MS: i64 = 60_000;
+
+#[test]
+#[ignore]
+fn mana_regeneration_counts_full_milliseconds_like_chrono() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let (player, village) = insert_player_village(&db);
+    let worker = insert_hero(&db, &village, WorkerFlagType::ManaRegeneration);
+
+    for elapsed in elapsed_cases(MANA_INTERVAL_MS) {
+        reset_flag(&db, &worker, WorkerFlagType::ManaRegeneration);
+        diesel::update(&worker)
+            .set(workers::mana.eq(Some(0)))
+            .execute(db.dbconn())
+            .expect("Resetting mana");
+
+        db.regenerate_workers_mana(start() + elapsed, MANA_INTERVAL_MS, &[1_000_000])
+            .expect("Regenerating mana");
+
+        let n = elapsed.num_milliseconds() / MANA_INTERVAL_MS;
+        let mana = db.worker_priv(worker.key()).expect("Worker is gone").mana;
+        assert_eq!(mana, Some(n as i32), "after {}", elapsed);
+        assert_eq!(
+            flag_timestamp(&db, &worker, WorkerFlagType::ManaRegeneration),
+            start() + Duration::milliseconds(MANA_INTERVAL_MS * n),
+            "after {}",
+            elapsed
+        );
+    }
+
+    delete_player_village(&db, &player, &village);
+}
+
+#[test]
+#[ignore]
+fn resource_collection_counts_full_milliseconds_like_chrono() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let (player, village) = insert_player_village(&db);
+    db.init_resources(village.key());
+    let worker = insert_hero(&db, &village, WorkerFlagType::Work);
+    db.insert_task(&NewTask {
+        worker_id: worker.id,
+        task_type: TaskType::ChopTree,
+        x: 0,
+        y: 0,
+        start_time: Some(start() - Duration::days(1)),
+        target_hobo_id: None,
+    })
+    .expect("Inserting task");
+
+    for elapsed in elapsed_cases(COLLECTION_INTERVAL_MS) {
+        reset_flag(&db, &worker, WorkerFlagType::Work);
+        diesel::update(resources::table.find((ResourceType::Logs, village.id)))
+            .set(resources::amount.eq(0))
+            .execute(db.dbconn())
+            .expect("Resetting resources");
+
+        db.collect_workers_resources(
+            start() + elapsed,
+            UnitType::Hero,
+            TaskType::ChopTree,
+            ResourceType::Logs,
+            COLLECTION_INTERVAL_MS,
+        )
+        .expect("Collecting resources");
+
+        let n = elapsed.num_milliseconds() / COLLECTION_INTERVAL_MS;
+        assert_eq!(
+            db.resource(ResourceType::Logs, village.key()),
+            n,
+            "after {}",
+            elapsed
+        );
+        assert_eq!(
+            flag_timestamp(&db, &worker, WorkerFlagType::Work),
+            start() + Duration::milliseconds(COLLECTION_INTERVAL_MS * n),
+            "after {}",
+            elapsed
+        );
+    }
+
+    delete_player_village(&db, &player, &village);
+}
+
+/// Last update of the flags in the tests.
+/// Long before any real flag, so that the statements under test leave real workers alone.
+fn start() -> NaiveDateTime {
+    NaiveDate::from_ymd(2000, 1, 1).and_hms_micro(12, 0, 0, 123_456)
+}
+
+/// Time passed since the last update, with values close to the interval boundaries
+fn elapsed_cases(interval_ms: i64) -> Vec<Duration> {
+    let interval = Duration::milliseconds(interval_ms);
+    let us = Duration::microseconds(1);
+    vec![
+        Duration::zero(),
+        us,
+        interval - us,
+        interval,
+        interval + us,
+        interval * 7 - Duration::microseconds(999),
+        interval * 7 + Duration::microseconds(999),
+        Duration::days(3) + Duration::microseconds(987_654),
+        Duration::days(400) - us,
+    ]
+}
+
+fn insert_player_village(db: &DB) -> (Player, Village) {
+    let player = insert_test_player(db);
+    let village = insert_test_village(db);
+    let village = diesel::update(villages::table.find(village.id))
+        .set(villages::player_id.eq(player.id))
+        .get_result(db.dbconn())
+        .expect("Assigning village");
+    (player, village)
+}
+
+fn delete_player_village(db: &DB, player: &Player, village: &Village) {
+    delete_test_village(db, village);
+    delete_test_player(db, player);
+}
+
+fn insert_hero(db: &DB, village: &Village, flag_type: WorkerFlagType) -> Worker {
+    let worker = db
+        .insert_worker(&NewWorker {
+            home: village.id,
+            x: 0,
+            y: 0,
+            unit_type: UnitType::Hero,
+            color: None,
+            speed: 0.1,
+            mana: Some(0),
+            level: 1,
+            exp: 0,
+        })
+        .expect("Inserting worker");
+    db.insert_worker_flag(WorkerFlag {
+        worker_id: worker.id,
+        flag_type,
+        last_update: start(),
+    })
+    .expect("Inserting worker flag");
+    worker
+}
+
+fn reset_flag(db: &DB, worker: &Worker, flag_type: WorkerFlagType) {
+    diesel::update(worker_flags::table.find((worker.id, flag_type)))
+        .set(worker_flags::last_update.eq(start()))
+        .execute(db.dbconn())
+        .expect("Resetting worker flag");
+}
+
+fn flag_timestamp(db: &DB, worker: &Worker, flag_type: WorkerFlagType) -> NaiveDateTime {
+    worker_flags::table
+        .find((worker.id, flag_type))
+        .select(worker_flags::last_update)
+        .first(db.dbconn())
+        .expect("Loading worker flag")
+}
@@ -0,0 +1,55 @@
+//! Helpers for tests that need a running database
+
+use super::DB;
+use diesel::prelude::*;
+use paddlers_shared_lib::game_mechanics::map::MAP_MAX_X;
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::schema::{players, villages};
+
+/// Inserts a village without owner and without resources
+pub(crate) fn insert_test_village(db: &DB) -> Village {
+    let stream = db
+        .streams(0.0, MAP_MAX_X as f32)
+        .pop()
+        .expect("Test requires a generated map");
+    // Outside of the map, to not interfere with real villages.
+    // Each village needs its own position, tests running in parallel take the next free one.
+    (100..)
+        .find_map(|x| {
+            let village = NewVillage {
+                x: -x as f32,
+                y: -100.0,
+                stream_id: stream.id,
+                player_id: None,
+                faith: None,
+                town_layout: TownLayout::Basic,
+            };
+            db.insert_village_if_position_free(&village)
+                .expect("Inserting test village")
+        })
+        .expect("No free position")
+}
+
+/// Deletes a village inserted by a test, together with everything that depends on it.
+/// Visits to the village have to be deleted by the test beforehand.
+pub(crate) fn delete_test_village(db: &DB, village: &Village) {
+    diesel::delete(villages::table.find(village.id))
+        .execute(db.dbconn())
+        .expect("Deleting test village");
+}
+
+pub(crate) fn insert_test_player(db: &DB) -> Player {
+    db.insert_player(&NewPlayer {
+        uuid: uuid::Uuid::new_v4(),
+        karma: 0,
+        display_name: "Test Player".to_owned(),
+    })
+    .expect("Inserting test player")
+}
+
+/// Deletes a player inserted by a test, after all villages of the player have been deleted
+pub(crate) fn delete_test_player(db: &DB, player: &Player) {
+    diesel::delete(players::table.find(player.id))
+        .execute(db.dbconn())
+        .expect("Deleting test player");
+}
@@ -6,14 +6,16 @@
 //!     - No two attacks reach a town at the same time
 //!     - No hobo is involved in more than one attack at the time
 //!     - The maximum number of resting hobos is not surpassed
+//!     - Each resting hobo has its own resting spot
 
 use crate::db::*;
 use crate::game_master::event::Event;
-use crate::game_master::town_defence::AttackingHobo;
 use crate::game_master::town_worker::{TownWorker, TownWorkerEventMsg};
 use actix::prelude::*;
-use chrono::{offset::TimeZone, NaiveDateTime, Utc};
-use paddlers_shared_lib::game_mechanics::{map::map_distance, town::defence::IAttackingHobo};
+use chrono::NaiveDateTime;
+use diesel::{Connection, QueryResult};
+use paddlers_shared_lib::game_mechanics::map::map_distance;
+use paddlers_shared_lib::game_mechanics::town::{next_resting_spot, visitor_queue_capacity};
 use paddlers_shared_lib::prelude::*;
 use std::ops::Add;
 
@@ -21,7 +23,6 @@ const MIN_DELAY_BETWEEN_ATTACKS: i64 = 20;
 
 pub struct AttackFunnel {
     dbpool: Pool,
-    db_actor: Addr<DbActor>,
     town_worker: Addr<TownWorker>,
 }
 
@@ -44,92 +45,125 @@ pub struct PlannedAttack {
     pub no_delay: bool,
 }
 impl Message for PlannedAttack {
-    type Result = ();
+    type Result = Result<AttackKey, AttackFunnelError>;
+}
+
+/// Reasons for the funnel to refuse a planned attack
+#[derive(Debug)]
+pub enum AttackFunnelError {
+    /// The planned attack has no hobos
+    NoHoboAvailable,
+    /// These hobos of the planned attack are already involved in another attack or are still on their way home
+    HobosBusy(Vec<HoboKey>),
+    DbError(diesel::result::Error),
 }
 
 impl Handler<PlannedAttack> for AttackFunnel {
-    type Result = ();
+    type Result = Result<AttackKey, AttackFunnelError>;
 
     fn handle(&mut self, msg: PlannedAttack, _ctx: &mut Context<Self>) -> Self::Result {
         let db = self.db();
         let vid = msg.destination_village.key();
 
-        // Checks and attack creation must be sequential
-        // TODO (Correctness): Somehow efficiently check that hobos are not attacking already
-        let unit_count = msg.hobos.len();
-        let unhurried = msg
-            .hobos
-            .iter()
-            .cloned()
-            .filter(|h| !h.hurried)
-            .collect::<Vec<_>>();
-        let hobos = msg.hobos.into_iter().map(|h| h.key()).collect();
-
-        let travel_time;
-        if msg.no_delay {
-            travel_time = 0;
-        } else if let Some(v0) = msg.origin_village {
-            let v1 = msg.destination_village;
-            let distance = map_distance((v0.x, v0.y), (v1.x, v1.y));
-            let seconds = 20.0 * distance;
-            travel_time = MIN_DELAY_BETWEEN_ATTACKS.max(seconds as i64);
+        let travel_time = if msg.no_delay {
+            chrono::Duration::zero()
+        } else if let Some(v0) = &msg.origin_village {
+            travel_time(v0, &msg.destination_village)
         } else {
-            travel_time = MIN_DELAY_BETWEEN_ATTACKS;
-        }
-        let now = chrono::Utc::now().naive_utc();
-        let earliest_arrival = now.add(chrono::Duration::seconds(travel_time));
-        let arrival = Self::next_timeslot(&db, vid, unit_count, earliest_arrival);
-        let attack = NewAttack {
-            departure: now,
-            arrival: arrival,
-            origin_village_id: msg.origin_village.map(|k| k.id),
-            destination_village_id: msg.destination_village.id,
+            chrono::Duration::seconds(MIN_DELAY_BETWEEN_ATTACKS)
         };
+        let now = chrono::Utc::now().naive_utc();
+        let earliest_arrival = now.add(travel_time);
+        let origin_village_id = msg.origin_village.map(|k| k.id);
+        let planned_hobos = msg.hobos;
+        let layout = msg.destination_village.town_layout;
 
-        // Put new attack in DB
-        let pa = ScheduledAttack { attack, hobos };
-        self.db_actor.do_send(DeferredDbStatement::NewAttack(pa));
-        // Everything until here must be sequential
-        // FIXME: the deferred DB statement is not sequential and it would be possible that two attacks receive the same time slot!
-
-        // Validate the resting queue the attack arrives, unless there is no unhurried hobo
-        if unhurried.len() > 0 {
-            let delayed_event = Event::CheckRestingVisitors {
-                village_id: msg.destination_village.key(),
-            };
-            let time = arrival;
-            self.town_worker.do_send(TownWorkerEventMsg(
-                delayed_event,
-                Utc.from_utc_datetime(&time),
-            ));
-        }
-        // For all unhurried hobos, the hp should be checked when they reach the resting place
-        for hobo in unhurried {
-            let delayed_event = Event::CheckVisitorHp {
-                hobo_id: hobo.key(),
+        // Checks and attack creation must be atomic.
+        // The lock on the destination village serializes all attacks on it, even across DB connections.
+        // The locks on the hobos prevent them from joining two attacks on different villages.
+        let (attack, visitors) = db.dbconn().transaction(|| {
+            db.lock_village(vid)?;
+            let hobo_keys: Vec<HoboKey> = planned_hobos.iter().map(|h| h.key()).collect();
+            db.lock_hobos(&hobo_keys)?;
+            if planned_hobos.is_empty() {
+                return Err(AttackFunnelError::NoHoboAvailable);
+            }
+            // The whole attack is refused if any hobo is already attacking or still on its way home
+            let busy: Vec<HoboKey> = hobo_keys
+                .into_iter()
+                .filter(|h| db.hobo_is_attacking(*h) || db.hobo_is_returning(*h))
+                .collect();
+            if !busy.is_empty() {
+                return Err(AttackFunnelError::HobosBusy(busy));
+            }
+            let visitors = Self::assign_resting_spots(&db, vid, layout, planned_hobos)?;
+            let arrival = Self::next_timeslot(&db, vid, visitors.len(), earliest_arrival);
+            let attack = NewAttack {
+                departure: now,
+                arrival: arrival,
+                origin_village_id,
+                destination_village_id: vid.num(),
             };
-            let swim_time: chrono::Duration =
-                AttackingHobo::s_time_until_resting(hobo.speed).into();
-            let event_time = arrival + swim_time;
-            self.town_worker.do_send(TownWorkerEventMsg(
-                delayed_event,
-                Utc.from_utc_datetime(&event_time),
-            ));
+            let keys: Vec<(HoboKey, usize)> =
+                visitors.iter().map(|(h, spot)| (h.key(), *spot)).collect();
+            let attack = db.insert_attack_with_hobos(&attack, &keys)?;
+            Ok((attack, visitors))
+        })?;
+
+        let unhurried = visitors
+            .into_iter()
+            .filter(|(h, _)| !h.hurried)
+            .collect::<Vec<_>>();
+        for (delayed_event, time) in Event::attack_events(&attack, layout, &unhurried) {
+            self.town_worker
+                .do_send(TownWorkerEventMsg(delayed_event, time));
         }
+        Ok(attack.key())
     }
 }
 
+/// Time it takes for visitors to swim from one village to another
+pub(crate) fn travel_time(from: &Village, to: &Village) -> chrono::Duration {
+    let distance = map_distance((from.x, from.y), (to.x, to.y));
+    let seconds = 20.0 * distance;
+    chrono::Duration::seconds(MIN_DELAY_BETWEEN_ATTACKS.max(seconds as i64))
+}
+
 impl AttackFunnel {
-    pub fn new(dbpool: Pool, db_actor: Addr<DbActor>, town_worker: Addr<TownWorker>) -> Self {
+    pub fn new(dbpool: Pool, town_worker: Addr<TownWorker>) -> Self {
         AttackFunnel {
             dbpool,
-            db_actor,
             town_worker,
         }
     }
     fn db(&self) -> DB {
         (&self.dbpool).into()
     }
+    /// Pairs each hobo with the spot where it will rest.
+    /// Hurried hobos never rest, they are all put on the first spot.
+    fn assign_resting_spots(
+        db: &DB,
+        vid: VillageKey,
+        layout: TownLayout,
+        hobos: Vec<Hobo>,
+    ) -> QueryResult<Vec<(Hobo, usize)>> {
+        let buildings = db.buildings(vid);
+        let capacity = visitor_queue_capacity(layout, buildings.iter().map(|b| &b.building_type));
+        let mut taken = db.taken_resting_spots(vid)?;
+        Ok(hobos
+            .into_iter()
+            .map(|hobo| {
+                if hobo.hurried {
+                    return (hobo, 0);
+                }
+                let spot = next_resting_spot(&taken, capacity);
+                // A reused spot is freed by the visitor that has to leave
+                taken.retain(|s| *s != spot);
+                taken.push(spot);
+                (hobo, spot)
+            })
+            .collect())
+    }
     fn next_timeslot(
         db: &DB,
         vid: VillageKey,
@@ -170,3 +204,22 @@ impl AttackFunnel {
         chrono::Duration::seconds(seconds as i64)
     }
 }
+
+impl From<diesel::result::Error> for AttackFunnelError {
+    fn from(e: diesel::result::Error) -> Self {
+        AttackFunnelError::DbError(e)
+    }
+}
+
+impl std::fmt::Display for AttackFunnelError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttackFunnelError::NoHoboAvailable => write!(f, "No hobo available for the attack"),
+            AttackFunnelError::HobosBusy(hobos) => {
+                let ids: Vec<String> = hobos.iter().map(|h| h.num().to_string()).collect();
+                write!(f, "Hobos already busy: {}", ids.join(", "))
+            }
+            AttackFunnelError::DbError(e) => write!(f, "DB error: {}", e),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! These tests need a running database, configured the same way as for the game-master itself.
+//! They are ignored by default, run them with `cargo test -- --ignored`.
+
+use super::attack_funnel::*;
+use super::town_worker::TownWorker;
+use crate::buildings::BuildingFactory;
+use crate::db::testing::*;
+use crate::db::*;
+use actix::prelude::*;
+use futures::future::join_all;
+use paddlers_shared_lib::prelude::*;
+
+const FUNNELS: usize = 4;
+const ATTACKS: usize = 40;
+
+#[test]
+#[ignore]
+fn concurrent_attacks_get_unique_time_slots() {
+    let dbpool = DB::new_pool();
+    let db: DB = (&dbpool).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_test_village(&db);
+    let hobos: Vec<Hobo> = (0..ATTACKS)
+        .map(|_| {
+            db.insert_hobo(&NewHobo {
+                hp: 1,
+                home: village.id,
+                color: None,
+                speed: 0.1,
+                hurried: true,
+                nest: None,
+            })
+        })
+        .collect();
+
+    let mut sys = System::new("Attack Funnel Test");
+    let town_worker = TownWorker::new(dbpool.clone()).start();
+    // Several funnels, each in its own thread, to have requests racing against each other
+    let funnels: Vec<Addr<AttackFunnel>> = (0..FUNNELS)
+        .map(|_| {
+            let pool = dbpool.clone();
+            let tw = town_worker.clone();
+            AttackFunnel::start_in_arbiter(&Arbiter::new(), move |_| AttackFunnel::new(pool, tw))
+        })
+        .collect();
+    let requests = hobos.into_iter().enumerate().map(|(i, hobo)| {
+        funnels[i % FUNNELS].send(PlannedAttack {
+            origin_village: None,
+            destination_village: village,
+            hobos: vec![hobo],
+            no_delay: true,
+        })
+    });
+    let results = sys
+        .block_on(join_all(requests))
+        .expect("Sending attacks failed");
+    for result in results {
+        result.expect("Attack rejected by funnel");
+    }
+
+    let attacks = db.attacks(village.key(), None);
+    assert_eq!(attacks.len(), ATTACKS);
+    for pair in attacks.windows(2) {
+        assert!(
+            pair[0].arrival < pair[1].arrival,
+            "Two attacks arrive at {}",
+            pair[0].arrival
+        );
+    }
+
+    for atk in &attacks {
+        db.delete_attack_hobos(atk.key());
+        db.delete_attack(atk).expect("Deleting attack failed");
+    }
+    delete_test_village(&db, &village);
+}
+
+#[test]
+#[ignore]
+fn resting_visitors_get_their_own_spot() {
+    let dbpool = DB::new_pool();
+    let db: DB = (&dbpool).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_test_village(&db);
+    // Together with the base queue, a harbour makes room for all spots on the lane
+    db.insert_building(&BuildingFactory::new(
+        BuildingType::Harbour,
+        (0, 0),
+        village.key(),
+    ));
+    let capacity = village.town_layout.resting_spots();
+
+    let mut sys = System::new("Resting Spot Test");
+    let town_worker = TownWorker::new(dbpool.clone()).start();
+    let funnel = AttackFunnel::new(dbpool.clone(), town_worker).start();
+    // One more visitor than there are spots, sent one after the other
+    for _ in 0..=capacity {
+        let hobo = db.insert_hobo(&NewHobo {
+            hp: 1,
+            home: village.id,
+            color: None,
+            speed: 0.1,
+            hurried: false,
+            nest: None,
+        });
+        sys.block_on(funnel.send(PlannedAttack {
+            origin_village: None,
+            destination_village: village,
+            hobos: vec![hobo],
+            no_delay: true,
+        }))
+        .expect("Sending attack failed")
+        .expect("Attack rejected by funnel");
+    }
+
+    let attacks = db.attacks(village.key(), None);
+    let spots: Vec<i32> = attacks
+        .iter()
+        .flat_map(|atk| db.attack_hobos_with_attack_info(atk))
+        .map(|(_, info)| info.resting_spot)
+        .collect();
+    let mut first_spots = spots[0..capacity].to_vec();
+    first_spots.sort();
+    assert_eq!(first_spots, (0..capacity as i32).collect::<Vec<_>>());
+    // The last visitor takes the spot of the first, which has to leave when the last arrives
+    assert_eq!(spots[capacity], spots[0]);
+
+    for atk in &attacks {
+        db.delete_attack_hobos(atk.key());
+        db.delete_attack(atk).expect("Deleting attack failed");
+    }
+    delete_test_village(&db, &village);
+}
+
+#[test]
+#[ignore]
+fn attack_with_busy_hobo_is_refused() {
+    let dbpool = DB::new_pool();
+    let db: DB = (&dbpool).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_test_village(&db);
+    let hobos: Vec<Hobo> = (0..2)
+        .map(|_| {
+            db.insert_hobo(&NewHobo {
+                hp: 1,
+                home: village.id,
+                color: None,
+                speed: 0.1,
+                hurried: true,
+                nest: None,
+            })
+        })
+        .collect();
+
+    let mut sys = System::new("Busy Hobo Test");
+    let town_worker = TownWorker::new(dbpool.clone()).start();
+    let funnel = AttackFunnel::new(dbpool.clone(), town_worker).start();
+    sys.block_on(funnel.send(PlannedAttack {
+        origin_village: None,
+        destination_village: village,
+        hobos: vec![hobos[0].clone()],
+        no_delay: true,
+    }))
+    .expect("Sending attack failed")
+    .expect("Attack rejected by funnel");
+    // The first hobo is still visiting, only the second one is free
+    let result = sys
+        .block_on(funnel.send(PlannedAttack {
+            origin_village: None,
+            destination_village: village,
+            hobos: hobos.clone(),
+            no_delay: true,
+        }))
+        .expect("Sending attack failed");
+    match result {
+        Err(AttackFunnelError::HobosBusy(busy)) => assert_eq!(busy, vec![hobos[0].key()]),
+        other => panic!("Attack was not refused: {:?}", other),
+    }
+
+    let attacks = db.attacks(village.key(), None);
+    assert_eq!(attacks.len(), 1);
+    assert!(!db.hobo_is_attacking(hobos[1].key()));
+
+    for atk in &attacks {
+        db.delete_attack_hobos(atk.key());
+        db.delete_attack(atk).expect("Deleting attack failed");
+    }
+    delete_test_village(&db, &village);
+}
+
+#[test]
+#[ignore]
+fn recalled_hobos_are_free_for_another_visit() {
+    let dbpool = DB::new_pool();
+    let db: DB = (&dbpool).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_test_village(&db);
+    let hobo = db.insert_hobo(&NewHobo {
+        hp: 1,
+        home: village.id,
+        color: None,
+        speed: 0.1,
+        hurried: false,
+        nest: None,
+    });
+
+    let mut sys = System::new("Recall Test");
+    let town_worker = TownWorker::new(dbpool.clone()).start();
+    let funnel = AttackFunnel::new(dbpool.clone(), town_worker).start();
+    let plan = || PlannedAttack {
+        origin_village: None,
+        destination_village: village,
+        hobos: vec![hobo.clone()],
+        no_delay: false,
+    };
+    // Without delay, the visit would arrive immediately and could not be recalled anymore
+    let first = sys
+        .block_on(funnel.send(plan()))
+        .expect("Sending attack failed")
+        .expect("Attack rejected by funnel");
+    assert!(db
+        .delete_attack_before_arrival(first)
+        .expect("Recalling failed"));
+    assert!(db.attack(first).is_none());
+    assert!(!db.hobo_is_attacking(hobo.key()));
+
+    let second = sys
+        .block_on(funnel.send(plan()))
+        .expect("Sending attack failed")
+        .expect("Recalled hobo rejected by funnel");
+    assert!(db.hobo_is_attacking(hobo.key()));
+
+    let atk = db.attack(second).expect("Attack is gone");
+    db.delete_attack_hobos(atk.key());
+    db.delete_attack(&atk).expect("Deleting attack failed");
+    delete_test_village(&db, &village);
+}
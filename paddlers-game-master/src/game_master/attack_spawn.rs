@@ -106,6 +106,11 @@ impl AttackSpawner {
                 };
                 attack_funnel.send(pa)
             })
+            .map(|result| {
+                if let Err(e) = result {
+                    eprintln!("Attack spawn failed: {}", e);
+                }
+            })
             .map_err(|e| eprintln!("Attack spawn failed: {:?}", e));
         Arbiter::spawn(planned_attack);
     }
@@ -1,8 +1,12 @@
 use crate::db::*;
-use crate::worker_actions::finish_task;
+use crate::worker_actions::{expand_repeating_tasks, finish_task};
 use chrono::prelude::*;
-use paddlers_shared_lib::game_mechanics::town::MAX_VISITOR_QUEUE;
+use paddlers_shared_lib::game_mechanics::town::{visitor_queue_capacity, ITownLayout, TOWN_X};
 use paddlers_shared_lib::prelude::*;
+use std::collections::HashMap;
+
+/// Seconds after the arrival of a visit until it is checked for the first time
+const FIRST_ATTACK_CHECK_DELAY: i64 = 2 * TOWN_X as i64;
 
 #[derive(Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Debug)]
 /// For actions that must be performed at a later point in time.
@@ -11,37 +15,74 @@ pub enum Event {
     WorkerTask { task_id: TaskKey },
     CheckRestingVisitors { village_id: VillageKey },
     CheckVisitorHp { hobo_id: HoboKey },
+    CheckAttack { attack_id: AttackKey },
+    FinishReturnTrip { trip_id: ReturnTripKey },
+    DeliverShipment { shipment_id: ShipmentKey },
 }
 
 impl Event {
-    pub(super) fn run(&self, db: &DB) -> Option<(Event, DateTime<Utc>)> {
+    pub(super) fn run(&self, db: &DB) -> Vec<(Event, DateTime<Utc>)> {
         match self {
             Self::WorkerTask { task_id } => {
                 let res = finish_task(db, *task_id, None, None);
                 if let Err(e) = res {
                     println!("Task execution failed: {}", e);
-                    None
+                    vec![]
                 } else {
-                    res.unwrap()
+                    res.unwrap().into_iter().collect()
                 }
             }
             Self::CheckRestingVisitors { village_id } => {
                 // Release all visitors that are queued beyond the limit
+                let mut events = vec![];
                 let visitors = db.resting_visitors(*village_id);
-                if visitors.len() > MAX_VISITOR_QUEUE {
-                    for (hobo, attack_id) in &visitors[0..visitors.len() - MAX_VISITOR_QUEUE] {
-                        db.release_resting_visitor(hobo.key(), *attack_id)
+                let buildings = db.buildings(*village_id);
+                let layout = db
+                    .village(*village_id)
+                    .map(|v| v.town_layout)
+                    .unwrap_or_default();
+                let capacity =
+                    visitor_queue_capacity(layout, buildings.iter().map(|b| &b.building_type));
+                if visitors.len() > capacity {
+                    for (hobo, attack_id) in &visitors[0..visitors.len() - capacity] {
+                        db.release_resting_visitor(hobo.key(), *attack_id);
+                        // Releasing a visitor determines when the visit can be finished
+                        let event = Event::CheckAttack {
+                            attack_id: *attack_id,
+                        };
+                        events.push((event, Utc::now()));
                     }
                 }
-                None
+                events.sort();
+                events.dedup();
+                events
             }
             Self::CheckVisitorHp { hobo_id } => {
                 let now = chrono::Utc::now().naive_utc();
+                let mut events = vec![];
                 for (atk, _info) in db.hobo_attack_info(*hobo_id) {
                     // Performance: Checking the entire attack is a bit of an overkill (was the easiest to implement without code duplication)
-                    db.maybe_evaluate_attack(&atk, now);
+                    events.append(&mut db.maybe_evaluate_attack(&atk, now));
+                }
+                events
+            }
+            Self::CheckAttack { attack_id } => {
+                let now = chrono::Utc::now().naive_utc();
+                db.attack(*attack_id)
+                    .map(|atk| db.maybe_evaluate_attack(&atk, now))
+                    .unwrap_or_default()
+            }
+            Self::FinishReturnTrip { trip_id } => {
+                if let Some(trip) = db.return_trip(*trip_id) {
+                    db.finish_return_trip(&trip);
                 }
-                None
+                vec![]
+            }
+            Self::DeliverShipment { shipment_id } => {
+                if let Some(shipment) = db.shipment(*shipment_id) {
+                    db.deliver_shipment(&shipment);
+                }
+                vec![]
             }
         }
     }
@@ -49,8 +90,15 @@ impl Event {
         db: &DB,
         worker_id: WorkerKey,
     ) -> Option<(Self, DateTime<Utc>)> {
-        let (current, next) = db.current_and_next_task(worker_id);
+        let (current, mut next) = db.current_and_next_task(worker_id);
         let current_task = current.expect("Units must always have a task");
+        if next.is_none() {
+            // Repeating tasks are added to the queue lazily, one round at the time
+            if let Err(e) = expand_repeating_tasks(db, &current_task) {
+                println!("{}", e);
+            }
+            next = db.current_and_next_task(worker_id).1;
+        }
         next.map(|next_task| {
             (
                 Event::WorkerTask {
@@ -60,4 +108,99 @@ impl Event {
             )
         })
     }
+    /// Events to be scheduled for a new visit.
+    /// Only unhurried hobos need events additional to the check of the entire visit.
+    /// They are listed together with their resting spot.
+    pub(crate) fn attack_events(
+        attack: &Attack,
+        layout: TownLayout,
+        unhurried: &[(Hobo, usize)],
+    ) -> Vec<(Self, DateTime<Utc>)> {
+        let arrival = attack.arrival;
+        let mut events = vec![];
+        // First check of the visit, which will then reschedule itself until the visit is finished
+        let first_check = arrival + chrono::Duration::seconds(FIRST_ATTACK_CHECK_DELAY);
+        events.push(Self::check_attack_at(attack.key(), first_check));
+        // Validate the resting queue the attack arrives, unless there is no unhurried hobo
+        if unhurried.len() > 0 {
+            events.push((
+                Event::CheckRestingVisitors {
+                    village_id: attack.destination(),
+                },
+                Utc.from_utc_datetime(&arrival),
+            ));
+        }
+        // For all unhurried hobos, the hp should be checked when they reach the resting place
+        for (hobo, spot) in unhurried {
+            let swim_time: chrono::Duration = layout.time_until_resting(hobo.speed, *spot).into();
+            events.push((
+                Event::CheckVisitorHp {
+                    hobo_id: hobo.key(),
+                },
+                Utc.from_utc_datetime(&(arrival + swim_time)),
+            ));
+        }
+        events
+    }
+    /// Reconstructs the events of all visits which are still in progress.
+    /// Events that have been due in the past are included, they will be executed in order on the next poll.
+    pub(crate) fn load_attack_events(db: &DB) -> Vec<(Self, DateTime<Utc>)> {
+        let mut unhurried_visitors: HashMap<i64, Vec<(Hobo, usize)>> = HashMap::new();
+        for (atk, hobo, spot) in db.unhurried_visitors_pending() {
+            unhurried_visitors
+                .entry(atk.id)
+                .or_insert_with(Vec::new)
+                .push((hobo, spot));
+        }
+        let mut events = vec![];
+        for atk in db.all_attacks() {
+            let unhurried = unhurried_visitors.remove(&atk.id).unwrap_or_default();
+            let layout = db
+                .village(atk.destination())
+                .map(|v| v.town_layout)
+                .unwrap_or_default();
+            events.append(&mut Self::attack_events(&atk, layout, &unhurried));
+        }
+        events
+    }
+    /// Reconstructs the events of all visitors on their way home
+    pub(crate) fn load_return_trip_events(db: &DB) -> Vec<(Self, DateTime<Utc>)> {
+        db.all_return_trips()
+            .iter()
+            .map(Self::finish_return_trip_at)
+            .collect()
+    }
+    pub(super) fn finish_return_trip_at(trip: &ReturnTrip) -> (Self, DateTime<Utc>) {
+        (
+            Event::FinishReturnTrip {
+                trip_id: trip.key(),
+            },
+            Utc.from_utc_datetime(&trip.arrival),
+        )
+    }
+    /// Reconstructs the events of all shipments still on their way
+    pub(crate) fn load_shipment_events(db: &DB) -> Vec<(Self, DateTime<Utc>)> {
+        db.all_shipments()
+            .iter()
+            .map(Self::deliver_shipment_at)
+            .collect()
+    }
+    pub(crate) fn deliver_shipment_at(shipment: &Shipment) -> (Self, DateTime<Utc>) {
+        (
+            Event::DeliverShipment {
+                shipment_id: shipment.key(),
+            },
+            Utc.from_utc_datetime(&shipment.arrival),
+        )
+    }
+    /// Schedules a check of the visit, not earlier than a second from now.
+    /// (Prevents busy loops due to rounding errors when computing the finish time.)
+    pub(super) fn check_attack_at(
+        attack_id: AttackKey,
+        time: NaiveDateTime,
+    ) -> (Self, DateTime<Utc>) {
+        let earliest = Utc::now() + chrono::Duration::seconds(1);
+        let time = Utc.from_utc_datetime(&time).max(earliest);
+        (Event::CheckAttack { attack_id }, time)
+    }
 }
@@ -1,5 +1,6 @@
 use super::event::Event;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeZone, Utc};
+use paddlers_shared_lib::prelude::*;
 use std::cmp::Ordering;
 use std::collections::BinaryHeap;
 
@@ -37,6 +38,29 @@ impl EventQueue {
         }
         None
     }
+    /// Drops all events for which the predicate returns true
+    pub fn remove_events(&mut self, mut predicate: impl FnMut(&Event, &DateTime<Utc>) -> bool) {
+        let queue = std::mem::replace(&mut self.queue, BinaryHeap::new());
+        self.queue = queue
+            .into_iter()
+            .filter(|te| !predicate(&te.event, &te.time))
+            .collect();
+    }
+    /// Drops all events of a visit which has been cancelled before its arrival
+    pub fn remove_visit_events(&mut self, attack: &Attack, hobos: &[HoboKey]) {
+        let attack_id = attack.key();
+        let destination = attack.destination();
+        let arrival = Utc.from_utc_datetime(&attack.arrival);
+        self.remove_events(|event, time| match event {
+            Event::CheckAttack { attack_id: id } => *id == attack_id,
+            Event::CheckVisitorHp { hobo_id } => hobos.contains(hobo_id),
+            // The attack funnel gives each visit of a village its own arrival time
+            Event::CheckRestingVisitors { village_id } => {
+                *village_id == destination && *time == arrival
+            }
+            _ => false,
+        });
+    }
     #[allow(dead_code)]
     pub fn time_of_next_event(&self) -> Option<&DateTime<Utc>> {
         self.queue.peek().map(|te| &te.time)
@@ -0,0 +1,64 @@
+use super::event::Event;
+use super::event_queue::EventQueue;
+use chrono::{NaiveDate, NaiveDateTime};
+use paddlers_shared_lib::prelude::*;
+
+const VILLAGE: i64 = 1;
+
+#[test]
+fn recalled_visit_leaves_no_events() {
+    let mut queue = EventQueue::new();
+    let recalled = attack(1, NaiveDate::from_ymd(2000, 1, 1).and_hms(12, 0, 0));
+    let other = attack(2, NaiveDate::from_ymd(2000, 1, 1).and_hms(12, 10, 0));
+    for (atk, hobo_id) in &[(&recalled, 1), (&other, 2)] {
+        for (event, time) in Event::attack_events(atk, TownLayout::Basic, &[(hobo(*hobo_id), 0)]) {
+            queue.add_event(event, time);
+        }
+    }
+
+    queue.remove_visit_events(&recalled, &[HoboKey(1)]);
+
+    // Checks of visits are never scheduled in the past, hence some events are not due yet.
+    // Draining the queue with a predicate that matches everything returns them regardless of time.
+    let mut remaining = vec![];
+    queue.remove_events(|event, _time| {
+        remaining.push(*event);
+        true
+    });
+    remaining.sort();
+    let mut expected = vec![
+        Event::CheckRestingVisitors {
+            village_id: VillageKey(VILLAGE),
+        },
+        Event::CheckAttack {
+            attack_id: other.key(),
+        },
+        Event::CheckVisitorHp {
+            hobo_id: HoboKey(2),
+        },
+    ];
+    expected.sort();
+    assert_eq!(remaining, expected);
+}
+
+fn attack(id: i64, arrival: NaiveDateTime) -> Attack {
+    Attack {
+        id,
+        departure: arrival - chrono::Duration::minutes(1),
+        arrival,
+        origin_village_id: None,
+        destination_village_id: VILLAGE,
+    }
+}
+
+fn hobo(id: i64) -> Hobo {
+    Hobo {
+        id,
+        home: VILLAGE,
+        color: None,
+        speed: 0.1,
+        hp: 1,
+        hurried: false,
+        nest: None,
+    }
+}
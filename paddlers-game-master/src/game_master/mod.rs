@@ -1,9 +1,15 @@
 pub(super) mod attack_funnel;
+#[cfg(test)]
+mod attack_funnel_test;
 pub(super) mod attack_spawn;
 pub(super) mod economy_worker;
 pub(super) mod event;
 mod event_queue;
+#[cfg(test)]
+mod event_queue_test;
 mod town_defence;
+#[cfg(test)]
+mod town_defence_test;
 pub(super) mod town_worker;
 
 use crate::db::*;
@@ -19,8 +25,14 @@ use paddlers_shared_lib::story::story_state::StoryState;
 use rand::RngCore;
 use std::time::Duration;
 
+/// Seconds between two runs of the safety net for visits
+const SAFETY_NET_INTERVAL: i64 = 60;
+/// Seconds after arrival, until a visit is considered by the safety net
+const SAFETY_NET_ATTACK_DELAY: i64 = 2 * TOWN_X as i64 + SAFETY_NET_INTERVAL;
+
 pub struct GameMaster {
     last_attack: NaiveDateTime,
+    last_attack_check: NaiveDateTime,
     dbpool: Pool,
     attacker_addr: Addr<AttackSpawner>,
     current_batch: Option<VillageBatch>,
@@ -34,6 +46,7 @@ impl GameMaster {
     pub fn new(dbpool: Pool, attacker_addr: &Addr<AttackSpawner>) -> Self {
         GameMaster {
             last_attack: NaiveDateTime::from_timestamp(0, 0),
+            last_attack_check: NaiveDateTime::from_timestamp(0, 0),
             dbpool: dbpool,
             attacker_addr: attacker_addr.clone(),
             current_batch: None,
@@ -59,10 +72,17 @@ impl GameMaster {
         // println!("Cycle");
 
         let db: DB = (&self.dbpool).into();
-        check_attacks(&db);
+        let now = chrono::Utc::now().naive_utc();
+
+        // Visits are checked by scheduled events, this is only required in case some event has been lost
+        if now - self.last_attack_check >= chrono::Duration::seconds(SAFETY_NET_INTERVAL) {
+            self.last_attack_check = now;
+            check_attacks(&db, now);
+            check_return_trips(&db, now);
+            check_shipments(&db, now);
+        }
 
         if self.current_batch.is_none() {
-            let now = chrono::Utc::now().naive_utc();
             if now - self.last_attack >= chrono::Duration::seconds(40) {
                 self.last_attack = now;
                 self.load_new_batch(&db);
@@ -131,15 +151,35 @@ fn repetitive_attack_strength(player: &Player) -> Option<HoboLevel> {
     }
 }
 
-// TODO: Efficiently check only required attacks
-fn check_attacks(db: &DB) {
-    for village in db.all_player_villages() {
-        let attacks = db.attacks(village.key(), None);
-        let now = chrono::Utc::now().naive_utc();
-        for atk in attacks.iter() {
-            if atk.arrival + chrono::Duration::seconds(2 * TOWN_X as i64) < now {
-                db.maybe_evaluate_attack(atk, now);
-            }
+/// Safety net for visits that have not been finished by the scheduled checks in the `TownWorker`.
+fn check_attacks(db: &DB, now: NaiveDateTime) {
+    let overdue = now - chrono::Duration::seconds(SAFETY_NET_ATTACK_DELAY);
+    // Visits waiting for resting visitors cannot be finished yet, page through them
+    let mut after = None;
+    loop {
+        let attacks = db.attacks_arrived_before(overdue, after);
+        after = match attacks.last() {
+            Some(atk) => Some(atk.key()),
+            None => break,
+        };
+        for atk in &attacks {
+            db.maybe_evaluate_attack(atk, now);
         }
     }
 }
+
+/// Safety net for visitors returning home, in case the scheduled event has been lost
+fn check_return_trips(db: &DB, now: NaiveDateTime) {
+    let overdue = now - chrono::Duration::seconds(SAFETY_NET_INTERVAL);
+    for trip in db.return_trips_arrived_before(overdue) {
+        db.finish_return_trip(&trip);
+    }
+}
+
+/// Safety net for shipments, in case the scheduled event has been lost
+fn check_shipments(db: &DB, now: NaiveDateTime) {
+    let overdue = now - chrono::Duration::seconds(SAFETY_NET_INTERVAL);
+    for shipment in db.shipments_arrived_before(overdue) {
+        db.deliver_shipment(&shipment);
+    }
+}
@@ -14,27 +14,36 @@
 //! This can be marked in the db using the status on each HoboToAttack.
 
 use crate::db::DB;
+use crate::game_master::attack_funnel::travel_time;
+use crate::game_master::event::Event;
 use crate::town_view::TownView;
-use chrono::NaiveDateTime;
+use chrono::prelude::*;
+use diesel::{Connection, QueryResult};
+use paddlers_shared_lib::game_mechanics::rewards::{visit_rewards, SatisfiedVisitor};
 use paddlers_shared_lib::game_mechanics::town::*;
 use paddlers_shared_lib::prelude::*;
 
-pub(crate) struct AttackingHobo<'a> {
-    hobo: &'a Hobo,
-    attack_to_hobo: &'a AttackToHobo,
-    effects: &'a [Effect],
-    attack: &'a Attack,
-}
-
 impl DB {
     /// Checks if all visitors have already left (or been satisfied).
     /// If so, the visit is evaluated and a report with rewards is generated.
-    pub fn maybe_evaluate_attack(&self, atk: &Attack, now: NaiveDateTime) {
+    /// Visitors sent by another village then start their trip back home.
+    ///
+    /// Returns the events to schedule next.
+    /// That is a check at the point in time when the visit can be finished, unless it depends on
+    /// a resting visitor that has not been released, yet.
+    /// Or, if the visit is done, the arrival of visitors returning home.
+    pub fn maybe_evaluate_attack(
+        &self,
+        atk: &Attack,
+        now: NaiveDateTime,
+    ) -> Vec<(Event, DateTime<Utc>)> {
+        let evaluation_time = now;
         let now: Timestamp = now.into();
         let village = atk.destination();
         let active_units = self.attack_hobos_active_with_attack_info(atk);
         let town = TownView::load_village(&self, village);
 
+        let mut finish_time = Some(now);
         for (hobo, info) in &active_units {
             let effects = self.effects_on_hobo(hobo.key());
             let unit = AttackingHobo {
@@ -50,16 +59,104 @@ impl DB {
                 }
             } else if town.hobo_left_town(&unit, now) {
                 self.set_satisfied(hobo.key(), atk.key(), false);
+            } else {
+                finish_time = match (finish_time, town.time_of_leaving(&unit)) {
+                    (Some(t0), Some(t1)) if t1 > t0 => Some(t1),
+                    (t, Some(_)) => t,
+                    (_, None) => None,
+                };
             }
         }
 
-        // Check if all are satisfied or have left otherwise, then finish visit
-        if self.attack_done(atk) {
-            self.generate_report(atk);
-            if atk.origin_village_id.is_none() {
-                self.delete_attack_hobos(atk.key());
+        // Check if all are satisfied or have left otherwise, then finish visit.
+        // The village is locked and deleting the attack decides which of several concurrent
+        // evaluations finishes the visit, all others are rolled back.
+        let result = self
+            .dbconn()
+            .transaction::<_, diesel::result::Error, _>(|| {
+                self.lock_village(village)?;
+                if !self.attack_done(atk) {
+                    return Ok(None);
+                }
+                self.generate_report(atk);
+                let mut events = vec![];
+                if let Some(home) = atk.origin() {
+                    if let Some(trip) = self.start_return_trip(atk, home, evaluation_time)? {
+                        events.push(Event::finish_return_trip_at(&trip));
+                    }
+                } else {
+                    self.delete_attack_hobos(atk.key());
+                }
+                if !self.delete_attack(atk)? {
+                    return Err(diesel::result::Error::RollbackTransaction);
+                }
+                Ok(Some(events))
+            });
+        match result {
+            Ok(Some(events)) => events,
+            Ok(None) => finish_time
+                .map(|t| Event::check_attack_at(atk.key(), t.into()))
+                .into_iter()
+                .collect(),
+            // Finished by another evaluation
+            Err(diesel::result::Error::RollbackTransaction) => vec![],
+            Err(e) => {
+                println!("Couldn't finish visit {:?}: {}", atk, e);
+                vec![]
             }
-            self.delete_attack(atk);
+        }
+    }
+
+    /// Sends the visitors of a finished visit back to their home village
+    fn start_return_trip(
+        &self,
+        atk: &Attack,
+        home: VillageKey,
+        now: NaiveDateTime,
+    ) -> QueryResult<Option<ReturnTrip>> {
+        let (home_village, visited_village) =
+            match (self.village(home), self.village(atk.destination())) {
+                (Some(home), Some(visited)) => (home, visited),
+                _ => return Ok(None),
+            };
+        let hobos: Vec<(HoboKey, bool)> = self
+            .attack_hobos_with_attack_info(atk)
+            .into_iter()
+            .map(|(hobo, info)| (hobo.key(), info.satisfied.unwrap_or(false)))
+            .collect();
+        let trip = NewReturnTrip {
+            departure: now,
+            arrival: now + travel_time(&visited_village, &home_village),
+            home_village_id: home.num(),
+            visited_village_id: Some(visited_village.id),
+        };
+        self.insert_return_trip_with_hobos(&trip, &hobos).map(Some)
+    }
+
+    /// Visitors have arrived back home, which is reported to the home village.
+    /// Afterwards, the visitors are available in their nest again.
+    /// Trips that have already been finished are ignored.
+    pub fn finish_return_trip(&self, trip: &ReturnTrip) {
+        // Read before deleting, the links to the hobos are removed together with the trip
+        let hobos = self.return_trip_hobos(trip.key());
+        let satisfied = hobos.iter().filter(|(_, info)| info.satisfied).count() as i64;
+        let report = NewVisitReport {
+            village_id: trip.home_village_id,
+            karma: satisfied,
+            report_type: VisitReportType::Outbound,
+            other_village_id: trip.visited_village_id,
+            visitors: hobos.len() as i64,
+        };
+        let result = self
+            .dbconn()
+            .transaction::<_, diesel::result::Error, _>(|| {
+                if self.delete_return_trip(trip)? {
+                    self.insert_visit_report(report);
+                }
+                Ok(())
+            });
+        if let Err(e) = result {
+            println!("Couldn't finish return trip {:?}: {}", trip, e);
         }
     }
 
@@ -67,124 +164,58 @@ impl DB {
         let mut report = NewVisitReport {
             village_id: atk.destination_village_id,
             karma: 0,
+            report_type: VisitReportType::Inbound,
+            other_village_id: atk.origin_village_id,
+            visitors: self.attack_hobos(atk.key()).len() as i64,
         };
 
         let happy_hobos = self.attack_hobos_satisfied(atk);
         report.karma = happy_hobos.len() as i64;
 
-        use std::ops::Add;
-        let feathers = happy_hobos.iter().map(reward_feathers).fold(0, i64::add);
-        let sticks = happy_hobos.iter().map(reward_sticks).fold(0, i64::add);
-        let logs = happy_hobos.iter().map(reward_logs).fold(0, i64::add);
-
-        if report.karma + feathers + sticks + logs == 0 {
+        let visitors: Vec<SatisfiedVisitor> = happy_hobos
+            .iter()
+            .map(|hobo| SatisfiedVisitor {
+                hp: hobo.hp,
+                speed: hobo.speed,
+                hurried: hobo.hurried,
+                color: hobo.color,
+                effects: self.effects_on_hobo(hobo.key()).len(),
+            })
+            .collect();
+        let buildings = self.buildings(atk.destination());
+        let items = visit_rewards(&visitors, buildings.iter().map(|b| &b.building_type));
+
+        if report.karma == 0 && items.is_empty() {
             return;
         }
 
         let vr = self.insert_visit_report(report);
 
-        let mut rewards = vec![];
-        if feathers > 0 {
-            rewards.push(NewReward {
+        let rewards = items
+            .into_iter()
+            .map(|item| NewReward {
                 visit_report_id: vr.id,
-                resource_type: ResourceType::Feathers,
-                amount: feathers,
-            });
-        }
-        if sticks > 0 {
-            rewards.push(NewReward {
-                visit_report_id: vr.id,
-                resource_type: ResourceType::Sticks,
-                amount: sticks,
-            });
-        }
-        if logs > 0 {
-            rewards.push(NewReward {
-                visit_report_id: vr.id,
-                resource_type: ResourceType::Logs,
-                amount: logs,
-            });
-        }
+                resource_type: item.resource,
+                amount: item.amount,
+                reason: item.reason,
+                building_type: item.building_type,
+            })
+            .collect();
         self.insert_visit_report_rewards(rewards);
     }
 }
 
-/// TODO [0.1.5]
-fn reward_feathers(unit: &Hobo) -> i64 {
-    let f = if unit.hurried {
-        (1.0 + unit.hp as f32 * unit.speed / 4.0).log2().floor()
-    } else {
-        (1.0 + unit.hp as f32 / 16.0).log2().ceil()
-    };
-    // println!("Unit {:?} gives {} feathers", unit, f);
-    f as i64
-}
-
-/// TODO [0.1.5]
-fn reward_sticks(unit: &Hobo) -> i64 {
-    if unit.id % 20 == 0 {
-        5
-    } else {
-        0
-    }
-}
-
-/// TODO [0.1.5]
-fn reward_logs(unit: &Hobo) -> i64 {
-    if unit.id % 60 == 11 {
-        5
-    } else {
-        0
-    }
-}
-
-impl<'a> IAttackingHobo for AttackingHobo<'a> {
-    fn max_hp(&self) -> u32 {
-        self.hobo.hp as u32
-    }
-    fn speed(&self) -> f32 {
-        self.hobo.speed
-    }
-    fn hurried(&self) -> bool {
-        self.hobo.hurried
-    }
-    fn arrival(&self) -> Timestamp {
-        self.attack.arrival.into()
-    }
-    fn released(&self) -> Option<Timestamp> {
-        self.attack_to_hobo.released.map(|t| t.into())
-    }
-    fn effects_strength(&self) -> i32 {
-        self.effects
-            .iter()
-            .filter(|e| e.attribute == HoboAttributeType::Health)
-            .filter(|e| e.strength.is_some())
-            .fold(0, |acc, e| acc + e.strength.unwrap() as i64) as i32
-    }
-}
-
 impl ITownLayoutMarker for TownView {
-    const LAYOUT: TownLayout = TownLayout::Basic;
+    fn town_layout(&self) -> TownLayout {
+        self.layout
+    }
 }
 impl IDefendingTown for TownView {
     type AuraId = i64;
     fn auras_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, i32)> {
-        let mut auras = vec![];
-        for b in &self.buildings_with_aura {
-            if time < b.creation.into() {
-                continue;
-            }
-            if b.attacks_per_cycle.is_none() {
-                if let (Some(range), Some(ap)) = (b.building_range, b.attack_power) {
-                    let dx = (b.x - index.0 as i32).abs();
-                    let dy = (b.y - index.1 as i32).abs();
-                    let in_range = (dx * dx + dy * dy) as f32 <= range * range;
-                    if in_range {
-                        auras.push((b.id, ap));
-                    }
-                }
-            }
-        }
-        auras
+        self.defence.auras_in_range(index, time)
+    }
+    fn towers_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, Tower)> {
+        self.defence.towers_in_range(index, time)
     }
 }
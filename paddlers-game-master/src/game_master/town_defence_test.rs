@@ -0,0 +1,186 @@
+//! These tests need a running database, configured the same way as for the game-master itself.
+//! They are ignored by default, run them with `cargo test -- --ignored`.
+
+use super::event::Event;
+use super::{check_return_trips, SAFETY_NET_INTERVAL};
+use crate::db::testing::*;
+use crate::db::*;
+use chrono::{Duration, NaiveDateTime, Utc};
+use paddlers_shared_lib::prelude::*;
+
+#[test]
+#[ignore]
+fn finished_return_trip_reports_home_and_frees_hobos() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let home = insert_test_village(&db);
+    let now = Utc::now().naive_utc();
+    let (trip, hobos) = insert_return_trip(&db, &home, now - Duration::seconds(1));
+    assert!(db.hobo_is_returning(hobos[0].key()));
+
+    Event::FinishReturnTrip {
+        trip_id: trip.key(),
+    }
+    .run(&db);
+
+    assert!(db.return_trip(trip.key()).is_none());
+    for hobo in &hobos {
+        assert!(!db.hobo_is_returning(hobo.key()));
+    }
+    let reports = db.reports(home.key(), None);
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].report_type, VisitReportType::Outbound);
+    assert_eq!(reports[0].visitors, 2);
+    // Only one of the two visitors was satisfied
+    assert_eq!(reports[0].karma, 1);
+
+    delete_test_village(&db, &home);
+}
+
+#[test]
+#[ignore]
+fn return_trip_is_finished_only_once() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let home = insert_test_village(&db);
+    let now = Utc::now().naive_utc();
+    let arrival = now - Duration::seconds(2 * SAFETY_NET_INTERVAL);
+    let (trip, _hobos) = insert_return_trip(&db, &home, arrival);
+
+    // The scheduled event and the safety net may both try to finish the same trip
+    let event = Event::FinishReturnTrip {
+        trip_id: trip.key(),
+    };
+    event.run(&db);
+    event.run(&db);
+    check_return_trips(&db, now);
+    db.finish_return_trip(&trip);
+
+    assert_eq!(db.reports(home.key(), None).len(), 1);
+
+    delete_test_village(&db, &home);
+}
+
+#[test]
+#[ignore]
+fn visit_is_finished_only_once() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let home = insert_test_village(&db);
+    let visited = insert_test_village(&db);
+    let now = Utc::now().naive_utc();
+    let hobos: Vec<HoboKey> = (0..2)
+        .map(|_| {
+            db.insert_hobo(&NewHobo {
+                hp: 1,
+                home: home.id,
+                color: None,
+                speed: 0.1,
+                hurried: true,
+                nest: None,
+            })
+            .key()
+        })
+        .collect();
+    let visit = NewAttack {
+        departure: now - Duration::minutes(2),
+        arrival: now - Duration::minutes(1),
+        origin_village_id: Some(home.id),
+        destination_village_id: visited.id,
+    };
+    let spots: Vec<(HoboKey, usize)> = hobos.iter().map(|h| (*h, 0)).collect();
+    let atk = db
+        .insert_attack_with_hobos(&visit, &spots)
+        .expect("Inserting visit");
+    for hobo in &hobos {
+        db.set_satisfied(*hobo, atk.key(), true);
+    }
+
+    // The scheduled check and the safety net may both evaluate the same visit
+    assert_eq!(db.maybe_evaluate_attack(&atk, now).len(), 1);
+    assert!(db.maybe_evaluate_attack(&atk, now).is_empty());
+
+    assert!(db.attack(atk.key()).is_none());
+    assert_eq!(db.reports(visited.key(), None).len(), 1);
+    let trips = db
+        .all_return_trips()
+        .into_iter()
+        .filter(|trip| trip.home_village_id == home.id)
+        .count();
+    assert_eq!(trips, 1);
+
+    delete_test_village(&db, &visited);
+    delete_test_village(&db, &home);
+}
+
+#[test]
+#[ignore]
+fn hobo_is_on_one_return_trip_at_most() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let home = insert_test_village(&db);
+    let now = Utc::now().naive_utc();
+    let (trip, hobos) = insert_return_trip(&db, &home, now + Duration::minutes(1));
+
+    let second_trip = NewReturnTrip {
+        departure: now,
+        arrival: now + Duration::minutes(2),
+        home_village_id: home.id,
+        visited_village_id: None,
+    };
+    assert!(db
+        .insert_return_trip_with_hobos(&second_trip, &[(hobos[0].key(), true)])
+        .is_err());
+    assert_eq!(db.return_trip_hobos(trip.key()).len(), 2);
+
+    delete_test_village(&db, &home);
+}
+
+#[test]
+#[ignore]
+fn safety_net_finishes_only_overdue_trips() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let home = insert_test_village(&db);
+    let now = Utc::now().naive_utc();
+    let (overdue, _) =
+        insert_return_trip(&db, &home, now - Duration::seconds(2 * SAFETY_NET_INTERVAL));
+    // Arrived, but the scheduled event still has time to finish it
+    let (recent, _) = insert_return_trip(&db, &home, now - Duration::seconds(1));
+    let (travelling, _) = insert_return_trip(&db, &home, now + Duration::minutes(1));
+
+    check_return_trips(&db, now);
+
+    assert!(db.return_trip(overdue.key()).is_none());
+    assert!(db.return_trip(recent.key()).is_some());
+    assert!(db.return_trip(travelling.key()).is_some());
+    assert_eq!(db.reports(home.key(), None).len(), 1);
+
+    delete_test_village(&db, &home);
+}
+
+/// A trip of two visitors, of which the first one has been satisfied
+fn insert_return_trip(db: &DB, home: &Village, arrival: NaiveDateTime) -> (ReturnTrip, Vec<Hobo>) {
+    let hobos: Vec<Hobo> = (0..2)
+        .map(|_| {
+            db.insert_hobo(&NewHobo {
+                hp: 1,
+                home: home.id,
+                color: None,
+                speed: 0.1,
+                hurried: true,
+                nest: None,
+            })
+        })
+        .collect();
+    let trip = NewReturnTrip {
+        departure: arrival - Duration::minutes(1),
+        arrival,
+        home_village_id: home.id,
+        visited_village_id: None,
+    };
+    let trip = db
+        .insert_return_trip_with_hobos(&trip, &[(hobos[0].key(), true), (hobos[1].key(), false)])
+        .expect("Inserting return trip");
+    (trip, hobos)
+}
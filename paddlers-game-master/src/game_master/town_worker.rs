@@ -24,8 +24,7 @@ impl TownWorker {
     }
     fn work(&mut self, ctx: &mut Context<Self>) {
         while let Some(event) = self.event_queue.poll_event() {
-            let res = event.run(&self.db());
-            if let Some((next_event, time)) = res {
+            for (next_event, time) in event.run(&self.db()) {
                 self.event_queue.add_event(next_event, time);
             }
         }
@@ -40,6 +39,15 @@ impl TownWorker {
                 }
             }
         }
+        for (event, time) in Event::load_attack_events(&db) {
+            self.event_queue.add_event(event, time);
+        }
+        for (event, time) in Event::load_return_trip_events(&db) {
+            self.event_queue.add_event(event, time);
+        }
+        for (event, time) in Event::load_shipment_events(&db) {
+            self.event_queue.add_event(event, time);
+        }
         self
     }
 }
@@ -69,3 +77,21 @@ impl Handler<TownWorkerEventMsg> for TownWorker {
         self.event_queue.add_event(msg.0, msg.1);
     }
 }
+
+/// Removes all pending events of a visit which has been cancelled before its arrival
+#[derive(Debug)]
+pub struct TownWorkerCancelVisitMsg {
+    pub attack: Attack,
+    pub hobos: Vec<HoboKey>,
+}
+
+impl Message for TownWorkerCancelVisitMsg {
+    type Result = ();
+}
+impl Handler<TownWorkerCancelVisitMsg> for TownWorker {
+    type Result = ();
+    fn handle(&mut self, msg: TownWorkerCancelVisitMsg, _ctx: &mut Context<Self>) {
+        self.event_queue
+            .remove_visit_events(&msg.attack, &msg.hobos);
+    }
+}
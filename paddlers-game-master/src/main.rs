@@ -22,13 +22,21 @@ use game_master::{
     town_worker::TownWorker, GameMaster,
 };
 use paddlers_shared_lib::api::{
-    attacks::InvitationDescriptor, reports::ReportCollect, story::StoryStateTransition,
+    attacks::{AttackRecall, InvitationDescriptor},
+    market::{TradeOfferAcceptance, TradeOfferCancellation, TradeOfferCreation},
+    reports::ReportCollect,
+    shipments::ShipmentOrder,
+    story::StoryStateTransition,
+    villages::VillageFoundation,
 };
 use paddlers_shared_lib::prelude::HoboKey;
 use paddlers_shared_lib::{
     api::{
         attacks::AttackDescriptor,
-        shop::{BuildingDeletion, BuildingPurchase, ProphetPurchase},
+        shop::{
+            BuildingDeletion, BuildingPurchase, BuildingRelocation, BuildingUpgrade,
+            ProphetPurchase, WorkerPurchase,
+        },
         statistics::FrontendRuntimeStatistics,
         tasks::TaskList,
     },
@@ -68,8 +76,7 @@ fn main() {
 
     // Spawn all "normal" actors onto the actix system
     let town_worker_actor = TownWorker::new(dbpool.clone()).start();
-    let attack_funnel =
-        AttackFunnel::new(dbpool.clone(), db_actor.clone(), town_worker_actor.clone()).start();
+    let attack_funnel = AttackFunnel::new(dbpool.clone(), town_worker_actor.clone()).start();
     let attack_worker =
         AttackSpawner::new(dbpool.clone(), db_actor.clone(), attack_funnel.clone()).start();
     let gm_actor = GameMaster::new(dbpool.clone(), &attack_worker).start();
@@ -109,11 +116,26 @@ fn main() {
                     .data(web::Json::<BuildingDeletion>)
                     .route(web::post().to(api::delete_building)),
             )
+            .service(
+                web::resource("/shop/building/relocate")
+                    .data(web::Json::<BuildingRelocation>)
+                    .route(web::post().to(api::relocate_building)),
+            )
+            .service(
+                web::resource("/shop/building/upgrade")
+                    .data(web::Json::<BuildingUpgrade>)
+                    .route(web::post().to(api::upgrade_building)),
+            )
             .service(
                 web::resource("/shop/unit/prophet")
                     .data(web::Json::<ProphetPurchase>)
                     .route(web::post().to_async(api::purchase_prophet)),
             )
+            .service(
+                web::resource("/shop/unit/worker")
+                    .data(web::Json::<WorkerPurchase>)
+                    .route(web::post().to_async(api::purchase_worker)),
+            )
             .service(
                 web::resource("/worker/overwriteTasks")
                     .data(web::Json::<TaskList>)
@@ -129,11 +151,36 @@ fn main() {
                     .data(web::Json::<InvitationDescriptor>)
                     .route(web::post().to_async(api::new_invitation)),
             )
+            .service(
+                web::resource("/attacks/recall")
+                    .data(web::Json::<AttackRecall>)
+                    .route(web::post().to(api::recall_attack)),
+            )
             .service(
                 web::resource("/attacks/notifications/visitor_satisfied")
                     .data(web::Json::<HoboKey>)
                     .route(web::post().to(api::visitor_satisfied_notification)),
             )
+            .service(
+                web::resource("/shipments/create")
+                    .data(web::Json::<ShipmentOrder>)
+                    .route(web::post().to(api::ship_resources)),
+            )
+            .service(
+                web::resource("/market/offer/create")
+                    .data(web::Json::<TradeOfferCreation>)
+                    .route(web::post().to(api::create_trade_offer)),
+            )
+            .service(
+                web::resource("/market/offer/accept")
+                    .data(web::Json::<TradeOfferAcceptance>)
+                    .route(web::post().to(api::accept_trade_offer)),
+            )
+            .service(
+                web::resource("/market/offer/cancel")
+                    .data(web::Json::<TradeOfferCancellation>)
+                    .route(web::post().to(api::cancel_trade_offer)),
+            )
             .service(
                 web::resource("/report/collect")
                     .data(web::Json::<ReportCollect>)
@@ -144,6 +191,11 @@ fn main() {
                     .data(web::Json::<StoryStateTransition>)
                     .route(web::post().to(api::story_transition)),
             )
+            .service(
+                web::resource("/village/found")
+                    .data(web::Json::<VillageFoundation>)
+                    .route(web::post().to(api::found_village)),
+            )
             .service(
                 web::resource("/stats")
                     .data(web::Json::<FrontendRuntimeStatistics>)
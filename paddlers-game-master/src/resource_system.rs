@@ -1,3 +1,13 @@
+mod market;
+#[cfg(test)]
+mod market_test;
+mod shipping;
+#[cfg(test)]
+mod shipping_test;
+
+pub use market::MarketError;
+pub use shipping::ShippingError;
+
 use crate::{db::DB, StringErr};
 use paddlers_shared_lib::{api::shop::*, prelude::*};
 
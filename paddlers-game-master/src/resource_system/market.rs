@@ -0,0 +1,125 @@
+//! Marketplace on which players trade resources with each other.
+//!
+//! Offered resources are taken from the village when the offer is posted and held back until
+//! the offer is either accepted or cancelled. Accepted trades are delivered by boat.
+
+use super::shipping::shipping_arrival;
+use crate::db::DB;
+use diesel::Connection;
+use paddlers_shared_lib::api::market::TradeOfferCreation;
+use paddlers_shared_lib::api::shop::Price;
+use paddlers_shared_lib::prelude::*;
+
+/// Reasons for refusing a market operation
+#[derive(Debug)]
+pub enum MarketError {
+    /// The request is not possible in the current state of the market or the villages
+    Invalid(String),
+    DbError(diesel::result::Error),
+}
+
+impl DB {
+    /// Puts an offer on the market and holds back the offered resources.
+    /// The caller is responsible for checking that the player owns the village.
+    pub fn try_post_trade_offer(
+        &self,
+        offer: &TradeOfferCreation,
+    ) -> Result<TradeOffer, MarketError> {
+        if offer.offered_amount <= 0 || offer.requested_amount <= 0 {
+            return Err(MarketError::Invalid("Amounts must be positive".to_owned()));
+        }
+        if offer.offered_resource == offer.requested_resource {
+            return Err(MarketError::Invalid(
+                "Cannot trade a resource for itself".to_owned(),
+            ));
+        }
+        let new_offer = NewTradeOffer {
+            village_id: offer.village.num(),
+            offered_resource: offer.offered_resource,
+            offered_amount: offer.offered_amount,
+            requested_resource: offer.requested_resource,
+            requested_amount: offer.requested_amount,
+        };
+        let escrow = Price(vec![(offer.offered_resource, offer.offered_amount)]);
+        self.dbconn().transaction(|| {
+            self.lock_village(offer.village)?;
+            self.try_spend(&escrow, offer.village)
+                .map_err(MarketError::Invalid)?;
+            Ok(self.insert_trade_offer(&new_offer)?)
+        })
+    }
+
+    /// Pays the requested resources from the village and closes the offer.
+    /// Returns the two shipments that deliver the traded resources to both villages.
+    /// The caller is responsible for checking that the player owns the village.
+    pub fn try_accept_trade_offer(
+        &self,
+        offer: TradeOfferKey,
+        village: VillageKey,
+    ) -> Result<Vec<Shipment>, MarketError> {
+        let buyer = self
+            .village(village)
+            .ok_or_else(|| MarketError::Invalid("No such village".to_owned()))?;
+        self.dbconn().transaction(|| {
+            let offer = self
+                .delete_trade_offer(offer)?
+                .ok_or_else(|| MarketError::Invalid("Offer is no longer open".to_owned()))?;
+            if offer.village() == village {
+                return Err(MarketError::Invalid(
+                    "Cannot accept an own offer".to_owned(),
+                ));
+            }
+            let seller = self
+                .village(offer.village())
+                .ok_or_else(|| MarketError::Invalid("No such village".to_owned()))?;
+            let price = Price(vec![(offer.requested_resource, offer.requested_amount)]);
+            self.lock_village(village)?;
+            self.try_spend(&price, village)
+                .map_err(MarketError::Invalid)?;
+            let now = chrono::Utc::now().naive_utc();
+            let arrival = shipping_arrival(&seller, &buyer, now);
+            let goods = NewShipment {
+                departure: now,
+                arrival,
+                origin_village_id: Some(seller.id),
+                destination_village_id: buyer.id,
+                resource_type: offer.offered_resource,
+                amount: offer.offered_amount,
+            };
+            let payment = NewShipment {
+                departure: now,
+                arrival,
+                origin_village_id: Some(buyer.id),
+                destination_village_id: seller.id,
+                resource_type: offer.requested_resource,
+                amount: offer.requested_amount,
+            };
+            Ok(vec![
+                self.insert_shipment(&goods)?,
+                self.insert_shipment(&payment)?,
+            ])
+        })
+    }
+
+    /// Removes an offer from the market and returns the held back resources to its village.
+    /// The caller is responsible for checking that the player owns the village of the offer.
+    pub fn try_cancel_trade_offer(&self, offer: TradeOfferKey) -> Result<(), MarketError> {
+        self.dbconn().transaction(|| {
+            let offer = self
+                .delete_trade_offer(offer)?
+                .ok_or_else(|| MarketError::Invalid("Offer is no longer open".to_owned()))?;
+            self.add_resource(
+                offer.offered_resource,
+                offer.village(),
+                offer.offered_amount,
+            )?;
+            Ok(())
+        })
+    }
+}
+
+impl From<diesel::result::Error> for MarketError {
+    fn from(e: diesel::result::Error) -> Self {
+        MarketError::DbError(e)
+    }
+}
@@ -0,0 +1,110 @@
+//! These tests need a running database, configured the same way as for the game-master itself.
+//! They are ignored by default, run them with `cargo test -- --ignored`.
+
+use super::MarketError;
+use crate::db::testing::*;
+use crate::db::*;
+use paddlers_shared_lib::api::market::TradeOfferCreation;
+use paddlers_shared_lib::api::shop::Price;
+use paddlers_shared_lib::prelude::*;
+
+const OFFERED: i64 = 10;
+const REQUESTED: i64 = 5;
+
+#[test]
+#[ignore]
+fn accepted_offer_is_delivered_to_both_villages() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let (seller, buyer) = insert_trading_villages(&db);
+    let offer = post_offer(&db, &seller);
+    assert_eq!(db.resource(ResourceType::Sticks, seller.key()), 0);
+
+    let shipments = db
+        .try_accept_trade_offer(TradeOfferKey(offer.id), buyer.key())
+        .expect("Accepting failed");
+    assert!(db.trade_offer(TradeOfferKey(offer.id)).is_none());
+    assert_eq!(db.resource(ResourceType::Logs, buyer.key()), 0);
+    assert_eq!(shipments.len(), 2);
+
+    for shipment in &shipments {
+        db.deliver_shipment(shipment);
+    }
+    assert_eq!(db.resource(ResourceType::Sticks, buyer.key()), OFFERED);
+    assert_eq!(db.resource(ResourceType::Logs, seller.key()), REQUESTED);
+
+    delete_test_village(&db, &seller);
+    delete_test_village(&db, &buyer);
+}
+
+#[test]
+#[ignore]
+fn offer_is_accepted_only_once() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let (seller, buyer) = insert_trading_villages(&db);
+    // Enough to pay twice
+    db.add_resource(ResourceType::Logs, buyer.key(), REQUESTED)
+        .expect("Adding resources");
+    let offer = post_offer(&db, &seller);
+
+    db.try_accept_trade_offer(TradeOfferKey(offer.id), buyer.key())
+        .expect("Accepting failed");
+    match db.try_accept_trade_offer(TradeOfferKey(offer.id), buyer.key()) {
+        Err(MarketError::Invalid(_)) => {}
+        other => panic!("Second acceptance was not refused: {:?}", other),
+    }
+    assert_eq!(db.resource(ResourceType::Logs, buyer.key()), REQUESTED);
+    assert_eq!(db.incoming_shipments(buyer.key()).len(), 1);
+
+    delete_test_village(&db, &seller);
+    delete_test_village(&db, &buyer);
+}
+
+#[test]
+#[ignore]
+fn refused_acceptance_keeps_offer_open() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let (seller, buyer) = insert_trading_villages(&db);
+    // One log short of the price
+    db.spend(&Price(vec![(ResourceType::Logs, 1)]), buyer.key());
+    let offer = post_offer(&db, &seller);
+
+    for village in &[buyer.key(), seller.key()] {
+        match db.try_accept_trade_offer(TradeOfferKey(offer.id), *village) {
+            Err(MarketError::Invalid(_)) => {}
+            other => panic!("Acceptance was not refused: {:?}", other),
+        }
+        assert!(db.trade_offer(TradeOfferKey(offer.id)).is_some());
+    }
+    assert_eq!(db.resource(ResourceType::Logs, buyer.key()), REQUESTED - 1);
+    assert!(db.incoming_shipments(buyer.key()).is_empty());
+
+    delete_test_village(&db, &seller);
+    delete_test_village(&db, &buyer);
+}
+
+/// The seller has the offered sticks, the buyer has the requested logs
+fn insert_trading_villages(db: &DB) -> (Village, Village) {
+    let seller = insert_test_village(db);
+    db.init_resources(seller.key());
+    db.add_resource(ResourceType::Sticks, seller.key(), OFFERED)
+        .expect("Adding resources");
+    let buyer = insert_test_village(db);
+    db.init_resources(buyer.key());
+    db.add_resource(ResourceType::Logs, buyer.key(), REQUESTED)
+        .expect("Adding resources");
+    (seller, buyer)
+}
+
+fn post_offer(db: &DB, seller: &Village) -> TradeOffer {
+    db.try_post_trade_offer(&TradeOfferCreation {
+        village: seller.key(),
+        offered_resource: ResourceType::Sticks,
+        offered_amount: OFFERED,
+        requested_resource: ResourceType::Logs,
+        requested_amount: REQUESTED,
+    })
+    .expect("Posting offer failed")
+}
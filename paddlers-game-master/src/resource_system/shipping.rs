@@ -0,0 +1,100 @@
+//! Resources transported by boat from one village to another
+
+use crate::db::DB;
+use chrono::NaiveDateTime;
+use diesel::Connection;
+use paddlers_shared_lib::api::shipments::ShipmentOrder;
+use paddlers_shared_lib::api::shop::Price;
+use paddlers_shared_lib::game_mechanics::map::map_distance;
+use paddlers_shared_lib::prelude::*;
+
+/// Seconds a boat needs per unit of distance on the map
+const SHIPPING_SECONDS_PER_DISTANCE: f32 = 10.0;
+const MIN_SHIPPING_SECONDS: i64 = 10;
+
+/// Reasons for refusing a shipment
+#[derive(Debug)]
+pub enum ShippingError {
+    /// The order is not possible in the current state of the villages
+    Invalid(String),
+    DbError(diesel::result::Error),
+}
+
+impl DB {
+    /// Takes the resources from the origin village and puts them on a boat to the destination.
+    /// The caller is responsible for checking that the player owns the origin village.
+    pub fn try_ship(&self, order: &ShipmentOrder) -> Result<Shipment, ShippingError> {
+        if order.amount <= 0 {
+            return Err(ShippingError::Invalid("Nothing to ship".to_owned()));
+        }
+        if order.from == order.to {
+            return Err(ShippingError::Invalid(
+                "Cannot ship to the same village".to_owned(),
+            ));
+        }
+        let origin = self
+            .village(order.from)
+            .ok_or_else(|| ShippingError::Invalid("No such origin village".to_owned()))?;
+        let destination = self
+            .village(order.to)
+            .ok_or_else(|| ShippingError::Invalid("No such destination village".to_owned()))?;
+        if destination.player_id.is_none() {
+            return Err(ShippingError::Invalid(
+                "Anarchists do not accept shipments".to_owned(),
+            ));
+        }
+        let now = chrono::Utc::now().naive_utc();
+        let shipment = NewShipment {
+            departure: now,
+            arrival: shipping_arrival(&origin, &destination, now),
+            origin_village_id: Some(origin.id),
+            destination_village_id: destination.id,
+            resource_type: order.resource,
+            amount: order.amount,
+        };
+        let price = Price(vec![(order.resource, order.amount)]);
+        // The lock prevents spending the same resources twice in concurrent requests
+        self.dbconn().transaction(|| {
+            self.lock_village(order.from)?;
+            self.try_spend(&price, order.from)
+                .map_err(ShippingError::Invalid)?;
+            Ok(self.insert_shipment(&shipment)?)
+        })
+    }
+
+    /// Unloads the boat in the destination village.
+    /// Shipments that have already been delivered are ignored.
+    pub fn deliver_shipment(&self, shipment: &Shipment) {
+        let result = self
+            .dbconn()
+            .transaction::<_, diesel::result::Error, _>(|| {
+                if self.delete_shipment(shipment.key())? {
+                    self.add_resource(
+                        shipment.resource_type,
+                        shipment.destination(),
+                        shipment.amount,
+                    )?;
+                }
+                Ok(())
+            });
+        if let Err(e) = result {
+            println!("Couldn't deliver shipment {:?}: {}", shipment, e);
+        }
+    }
+}
+
+pub(super) fn shipping_arrival(
+    from: &Village,
+    to: &Village,
+    departure: NaiveDateTime,
+) -> NaiveDateTime {
+    let distance = map_distance((from.x, from.y), (to.x, to.y));
+    let seconds = (SHIPPING_SECONDS_PER_DISTANCE * distance) as i64;
+    departure + chrono::Duration::seconds(MIN_SHIPPING_SECONDS.max(seconds))
+}
+
+impl From<diesel::result::Error> for ShippingError {
+    fn from(e: diesel::result::Error) -> Self {
+        ShippingError::DbError(e)
+    }
+}
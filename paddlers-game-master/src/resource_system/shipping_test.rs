@@ -0,0 +1,105 @@
+//! These tests need a running database, configured the same way as for the game-master itself.
+//! They are ignored by default, run them with `cargo test -- --ignored`.
+
+use super::ShippingError;
+use crate::db::testing::*;
+use crate::db::*;
+use diesel::prelude::*;
+use paddlers_shared_lib::api::shipments::ShipmentOrder;
+use paddlers_shared_lib::prelude::*;
+use paddlers_shared_lib::schema::villages;
+
+const AMOUNT: i64 = 10;
+
+#[test]
+#[ignore]
+fn shipment_moves_resources_on_delivery() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let player = insert_test_player(&db);
+    let origin = insert_owned_village(&db, &player);
+    let destination = insert_owned_village(&db, &player);
+    db.add_resource(ResourceType::Sticks, origin.key(), AMOUNT)
+        .expect("Adding resources");
+
+    // No harbour needed in the origin village
+    let shipment = db
+        .try_ship(&order(&origin, &destination))
+        .expect("Shipping failed");
+    assert_eq!(db.resource(ResourceType::Sticks, origin.key()), 0);
+    assert_eq!(db.resource(ResourceType::Sticks, destination.key()), 0);
+    assert!(shipment.arrival > shipment.departure);
+
+    db.deliver_shipment(&shipment);
+    assert_eq!(db.resource(ResourceType::Sticks, destination.key()), AMOUNT);
+    assert!(db.shipment(shipment.key()).is_none());
+
+    delete_test_village(&db, &origin);
+    delete_test_village(&db, &destination);
+    delete_test_player(&db, &player);
+}
+
+#[test]
+#[ignore]
+fn shipment_is_delivered_only_once() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let player = insert_test_player(&db);
+    let origin = insert_owned_village(&db, &player);
+    let destination = insert_owned_village(&db, &player);
+    db.add_resource(ResourceType::Logs, origin.key(), AMOUNT)
+        .expect("Adding resources");
+    let mut order = order(&origin, &destination);
+    order.resource = ResourceType::Logs;
+    let shipment = db.try_ship(&order).expect("Shipping failed");
+
+    // The event queue and the safety net may both try to deliver the same shipment
+    db.deliver_shipment(&shipment);
+    db.deliver_shipment(&shipment);
+    assert_eq!(db.resource(ResourceType::Logs, destination.key()), AMOUNT);
+
+    delete_test_village(&db, &origin);
+    delete_test_village(&db, &destination);
+    delete_test_player(&db, &player);
+}
+
+#[test]
+#[ignore]
+fn shipment_without_resources_is_refused() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let player = insert_test_player(&db);
+    let origin = insert_owned_village(&db, &player);
+    let destination = insert_owned_village(&db, &player);
+    db.add_resource(ResourceType::Sticks, origin.key(), AMOUNT - 1)
+        .expect("Adding resources");
+
+    match db.try_ship(&order(&origin, &destination)) {
+        Err(ShippingError::Invalid(_)) => {}
+        other => panic!("Shipment was not refused: {:?}", other),
+    }
+    assert_eq!(db.resource(ResourceType::Sticks, origin.key()), AMOUNT - 1);
+    assert!(db.incoming_shipments(destination.key()).is_empty());
+
+    delete_test_village(&db, &origin);
+    delete_test_village(&db, &destination);
+    delete_test_player(&db, &player);
+}
+
+fn insert_owned_village(db: &DB, player: &Player) -> Village {
+    let village = insert_test_village(db);
+    db.init_resources(village.key());
+    diesel::update(villages::table.find(village.id))
+        .set(villages::player_id.eq(player.id))
+        .get_result(db.dbconn())
+        .expect("Assigning village")
+}
+
+fn order(from: &Village, to: &Village) -> ShipmentOrder {
+    ShipmentOrder {
+        from: from.key(),
+        to: to.key(),
+        resource: ResourceType::Sticks,
+        amount: AMOUNT,
+    }
+}
@@ -5,11 +5,16 @@
 
 mod map_generation;
 mod new_player;
+#[cfg(test)]
+mod setup_test;
 
 use crate::buildings::BuildingFactory;
 use crate::db::DB;
+use crate::town_view::TownView;
 use diesel::result::{DatabaseErrorKind, Error};
 use dotenv::dotenv;
+use paddlers_shared_lib::game_mechanics::town::*;
+use paddlers_shared_lib::game_mechanics::worker::hero_level_ups;
 use paddlers_shared_lib::test_data::*;
 use paddlers_shared_lib::{
     api::PlayerInitData, prelude::*, sql_db::run_db_migrations, story::story_state::StoryState,
@@ -38,6 +43,8 @@ impl DB {
         if env::var("DATABASE_INIT").is_ok() {
             let server = 1;
             run_db_migrations(self.dbconn())?;
+            self.relocate_misplaced_buildings();
+            self.grant_missing_hero_abilities();
             self.init_map(server);
         }
         if env::var("INSERT_TEST_DATA").is_ok() {
@@ -60,7 +67,74 @@ impl DB {
         Ok(())
     }
     fn insert_temple(&self, village: VillageKey) {
-        let building = BuildingFactory::new(BuildingType::Temple, (4, 2), village);
+        let town = TownView::load_village(self, village);
+        let units = self.placement_units(village);
+        let anchor = temple_anchor(&town.map, town.layout, &units).expect("No space for temple");
+        let building = BuildingFactory::new(BuildingType::Temple, anchor, village);
         self.insert_building(&building);
     }
+    /// Buildings stored before they covered several tiles may now overlap the lane or other buildings.
+    /// Those are moved to the closest free space, smaller buildings keep their place.
+    fn relocate_misplaced_buildings(&self) {
+        for village in self.all_villages() {
+            let mut map = TownMap::new(village.town_layout);
+            let units = self.placement_units(village.key());
+            let mut buildings = self.buildings(village.key());
+            buildings.sort_by_key(|b| {
+                let (w, h) = b.building_type.size();
+                (w * h, b.id)
+            });
+            for b in buildings {
+                let anchor = (b.x as usize, b.y as usize);
+                if map.insert_building(b.building_type, anchor).is_ok() {
+                    continue;
+                }
+                let in_use = b
+                    .building_type
+                    .footprint(anchor)
+                    .iter()
+                    .any(|tile| units.iter().any(|u| u.tile == *tile));
+                let target = if in_use {
+                    None
+                } else {
+                    find_building_space(&map, village.town_layout, &units, b.building_type, anchor)
+                };
+                match target {
+                    Some(to) => match self.move_building(&b, to) {
+                        Ok(_) => map
+                            .insert_building(b.building_type, to)
+                            .expect("Space has been checked"),
+                        Err(e) => println!("Moving building {} failed: {}", b.id, e),
+                    },
+                    None => println!(
+                        "Warning: building {} at {:?} does not fit and cannot be moved",
+                        b.id, anchor
+                    ),
+                }
+            }
+        }
+    }
+    /// Heroes that were leveled up before levels came with abilities have not learned them.
+    /// They get all abilities of their level now, abilities a hero already has are left untouched.
+    fn grant_missing_hero_abilities(&self) {
+        for hero in self.leveled_heroes() {
+            for ability_type in hero_level_ups(1, hero.level).filter_map(|l| l.ability) {
+                let ability = NewAbility {
+                    worker_id: hero.id,
+                    ability_type,
+                };
+                if let Err(e) = self.insert_ability_if_missing(&ability) {
+                    println!(
+                        "Granting {:?} to hero {} failed: {}",
+                        ability_type, hero.id, e
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Where the temple of a new village goes, as close to the center as the lane and the hero allow
+fn temple_anchor(map: &TownMap, layout: TownLayout, units: &[UnitPosition]) -> Option<TileIndex> {
+    find_building_space(map, layout, units, BuildingType::Temple, (4, 1))
 }
@@ -2,6 +2,8 @@
 
 mod anarchists;
 mod village_creation;
+#[cfg(test)]
+mod village_creation_test;
 
 use crate::db::DB;
 use paddlers_shared_lib::game_mechanics::map::*;
@@ -11,14 +11,15 @@ const HOBOS_PER_TOWN: usize = 3;
 impl DB {
     pub fn generate_anarchist_town_content(
         &self,
-        village: VillageKey,
+        village: &Village,
         lcg: &mut Lcg,
     ) -> Result<(), &'static str> {
-        self.add_random_forest_to_village(village, lcg);
-        self.generate_anarchist_hobos(HOBOS_PER_TOWN, village, lcg)?;
+        let map = TownMap::new(village.town_layout);
+        self.add_random_forest_to_village(village.key(), &map, lcg);
+        self.generate_anarchist_hobos(HOBOS_PER_TOWN, village.key(), &map, lcg)?;
         Ok(())
     }
-    fn add_random_forest_to_village(&self, village: VillageKey, lcg: &mut Lcg) {
+    fn add_random_forest_to_village(&self, village: VillageKey, map: &TownMap, lcg: &mut Lcg) {
         // Two contiguous forests in the top corners
         let mut left = lcg.next_in_range(0, 2 * TOWN_X as u64 / 3);
         let mut right = lcg.next_in_range(TOWN_X as u64 / 3, TOWN_X as u64);
@@ -30,18 +31,17 @@ impl DB {
             right = right.min(TOWN_X as u64);
             right = right.max(left + 1);
 
-            for x in 0..left as usize {
-                self.insert_tree(village, x, y);
-            }
-            for x in right as usize..TOWN_X {
-                self.insert_tree(village, x, y);
+            for x in (0..left as usize).chain(right as usize..TOWN_X) {
+                if !is_lane(map, x as i32, y as i32) {
+                    self.insert_tree(village, x, y);
+                }
             }
         }
         // A few single trees
         let n = lcg.next_in_range(0, 8);
         for _ in 0..n {
             let (x, y) = random_town_coordinate(lcg);
-            if y as usize == TOWN_LANE_Y {
+            if is_lane(map, x, y) {
                 continue;
             }
             if self.find_building_by_coordinates(x, y, village).is_none() {
@@ -58,11 +58,12 @@ impl DB {
     fn random_empty_town_coordinate(
         &self,
         village: VillageKey,
+        map: &TownMap,
         lcg: &mut Lcg,
     ) -> Result<(i32, i32), &'static str> {
         for _ in 0..100 {
             let (x, y) = random_town_coordinate(lcg);
-            if y as usize == TOWN_LANE_Y {
+            if is_lane(map, x, y) {
                 continue;
             }
             if self.find_building_by_coordinates(x, y, village).is_none() {
@@ -76,12 +77,13 @@ impl DB {
         &self,
         n: usize,
         village: VillageKey,
+        map: &TownMap,
         lcg: &mut Lcg,
     ) -> Result<(), &'static str> {
         let hurried = false;
         let speed = 0.1;
         for _ in 0..n {
-            let (x, y) = self.random_empty_town_coordinate(village, lcg)?;
+            let (x, y) = self.random_empty_town_coordinate(village, map, lcg)?;
             let hp = lcg.next_in_range(4, 6) as i64;
             self.insert_anarchist_hobo_with_nest(village, x, y, hp, speed, hurried);
         }
@@ -111,6 +113,10 @@ impl DB {
     }
 }
 
+fn is_lane(map: &TownMap, x: i32, y: i32) -> bool {
+    map.tile_type((x as usize, y as usize)) == Some(&TownTileType::LANE)
+}
+
 fn random_town_coordinate(lcg: &mut Lcg) -> (i32, i32) {
     let x = lcg.next_in_range(0, TOWN_X as u64);
     let y = lcg.next_in_range(0, TOWN_Y as u64);
@@ -18,6 +18,45 @@ impl DB {
         }
         Err("World full: No space for another village")
     }
+    /// Creates a new village for the player at a specific position.
+    /// The position must be a free village position on one of the streams.
+    pub fn found_village(&self, pid: PlayerKey, x: i32, y: i32) -> Result<Village, &'static str> {
+        let (x, y) = (x as f32, y as f32);
+        let stream = self
+            .streams_to_add_village()
+            .into_iter()
+            .find(|s| village_positions(&s.control_points).contains(&(x, y)))
+            .ok_or("No village can be placed at this position")?;
+        let v = NewVillage {
+            stream_id: stream.id,
+            x,
+            y,
+            player_id: Some(pid.num()),
+            faith: None, // Start with default value
+            town_layout: stream_town_layout(&stream),
+        };
+        // The unique position index decides which of two players founding a village at the same time wins
+        let village = self
+            .insert_village_if_position_free(&v)
+            .map_err(|_| "Village could not be founded")?
+            .ok_or("Position is occupied by another village")?;
+        self.init_player_village(village.key());
+        Ok(village)
+    }
+    /// Puts an anarchist village under the rule of a player.
+    /// Buildings and hobos of the anarchists stay where they are.
+    pub fn claim_anarchist_village(
+        &self,
+        pid: PlayerKey,
+        vid: VillageKey,
+    ) -> Result<Village, &'static str> {
+        let village = self
+            .set_owner_of_anarchist_village(vid, pid)
+            .map_err(|_| "Village could not be claimed")?
+            .ok_or("Village is not ruled by anarchists")?;
+        self.init_player_village(village.key());
+        Ok(village)
+    }
     pub fn generate_anarchists(&self, n: usize, seed: u64) -> Result<(), &'static str> {
         let mut lcg = Lcg::new(seed);
         for i in 1..n + 1 {
@@ -32,7 +71,7 @@ impl DB {
     ) -> Result<Village, &'static str> {
         let s = self.stream(stream_id);
         let village = self.insert_village_on_stream(&s, None)?;
-        self.generate_anarchist_town_content(village.key(), lcg)?;
+        self.generate_anarchist_town_content(&village, lcg)?;
         Ok(village)
     }
     fn insert_village_on_stream(
@@ -49,8 +88,15 @@ impl DB {
                     y,
                     player_id: player.as_ref().map(PlayerKey::num),
                     faith: None, // Start with default value
+                    town_layout: stream_town_layout(s),
                 };
-                return Ok(self.insert_villages(&[v])[0]);
+                // Another village may have been placed here in the meantime, then try the next position
+                let inserted = self
+                    .insert_village_if_position_free(&v)
+                    .map_err(|_| "Inserting village failed")?;
+                if let Some(village) = inserted {
+                    return Ok(village);
+                }
             }
         }
         Err("Stream full: No space for another village")
@@ -78,6 +124,7 @@ impl DB {
                     y,
                     player_id: None,
                     faith: None, // Start with default value
+                    town_layout: stream_town_layout(&s),
                 };
                 self.insert_villages(&[v]);
             }
@@ -85,7 +132,17 @@ impl DB {
     }
 }
 
-fn village_positions(stream_points: &[f32]) -> Vec<(f32, f32)> {
+/// All villages on the same stream share the layout of the river flowing through them
+fn stream_town_layout(s: &Stream) -> TownLayout {
+    match s.id % 4 {
+        0 => TownLayout::Basic,
+        1 => TownLayout::Bend,
+        2 => TownLayout::Meander,
+        _ => TownLayout::Fork,
+    }
+}
+
+pub(super) fn village_positions(stream_points: &[f32]) -> Vec<(f32, f32)> {
     let mut v: std::collections::HashSet<(i32, i32)> = std::collections::HashSet::new();
     let points: Vec<(f32, f32)> = stream_points
         .chunks_exact(2)
@@ -0,0 +1,59 @@
+//! These tests need a running database, configured the same way as for the game-master itself.
+//! They are ignored by default, run them with `cargo test -- --ignored`.
+
+use super::village_creation::*;
+use crate::db::testing::*;
+use crate::db::*;
+use paddlers_shared_lib::game_mechanics::map::MAP_MAX_X;
+use paddlers_shared_lib::prelude::*;
+
+const THREADS: usize = 4;
+
+#[test]
+#[ignore]
+fn concurrent_foundations_create_one_village() {
+    let pool = DB::new_pool();
+    let db: DB = (&pool).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let players: Vec<Player> = (0..THREADS).map(|_| insert_test_player(&db)).collect();
+    let (x, y) = free_village_position(&db);
+
+    let threads: Vec<_> = players
+        .iter()
+        .map(|player| {
+            let pool = pool.clone();
+            let pid = player.key();
+            std::thread::spawn(move || {
+                let db: DB = (&pool).into();
+                db.found_village(pid, x, y).ok()
+            })
+        })
+        .collect();
+    let villages: Vec<Village> = threads
+        .into_iter()
+        .filter_map(|t| t.join().expect("Thread panicked"))
+        .collect();
+    assert_eq!(villages.len(), 1);
+    let village = db
+        .village_at(x as f32, y as f32)
+        .expect("No village founded");
+    assert_eq!(village.id, villages[0].id);
+
+    // Founding on an occupied position is refused right away
+    assert!(db.found_village(players[0].key(), x, y).is_err());
+
+    delete_test_village(&db, &village);
+    for player in &players {
+        delete_test_player(&db, player);
+    }
+}
+
+/// A position on the map where a village can be founded
+fn free_village_position(db: &DB) -> (i32, i32) {
+    db.streams(0.0, MAP_MAX_X as f32)
+        .iter()
+        .flat_map(|s| village_positions(&s.control_points))
+        .find(|&(x, y)| db.village_at(x, y).is_none())
+        .map(|(x, y)| (x as i32, y as i32))
+        .expect("Test requires a free position on the map")
+}
@@ -2,6 +2,9 @@ use crate::db::DB;
 use diesel::QueryResult;
 use paddlers_shared_lib::prelude::*;
 
+/// Where the hero of a new village starts
+pub(super) const HERO_START: (i32, i32) = (5, 2);
+
 impl DB {
     pub(super) fn new_player(&self, display_name: String, uuid: uuid::Uuid) -> QueryResult<Player> {
         let player = NewPlayer {
@@ -10,14 +13,18 @@ impl DB {
             uuid,
         };
         let player = self.insert_player(&player)?;
-        let village = self.new_village(player.key());
-
-        self.insert_hero(village.key());
+        self.new_village(player.key());
         Ok(player)
     }
 
+    /// Gives a village that has just come under the rule of a player everything needed to start playing
+    pub(super) fn init_player_village(&self, vid: VillageKey) {
+        self.insert_initial_resources(vid);
+        self.insert_hero(vid);
+    }
+
     fn insert_hero(&self, vid: VillageKey) -> Worker {
-        let (x, y) = (5, 2);
+        let (x, y) = HERO_START;
         let worker = NewWorker {
             unit_type: UnitType::Hero,
             x: x,
@@ -29,7 +36,7 @@ impl DB {
             level: 1,
             exp: 0,
         };
-        let worker = self.insert_worker(&worker);
+        let worker = self.insert_worker(&worker).expect("Inserting worker");
         let task = NewTask {
             worker_id: worker.id,
             task_type: TaskType::Idle,
@@ -38,33 +45,37 @@ impl DB {
             start_time: None,
             target_hobo_id: None,
         };
-        self.insert_task(&task);
+        self.insert_task(&task).expect("Inserting task");
         let work_ability = NewAbility {
             worker_id: worker.id,
             ability_type: AbilityType::Work,
         };
-        self.insert_ability(&work_ability);
+        self.insert_ability(&work_ability)
+            .expect("Inserting ability");
         let welcome_ability = NewAbility {
             worker_id: worker.id,
             ability_type: AbilityType::Welcome,
         };
-        self.insert_ability(&welcome_ability);
+        self.insert_ability(&welcome_ability)
+            .expect("Inserting ability");
         self.insert_worker_flag(WorkerFlag {
             worker_id: worker.id,
             flag_type: WorkerFlagType::ManaRegeneration,
             last_update: chrono::Utc::now().naive_utc(),
-        });
+        })
+        .expect("Inserting flag");
         self.insert_worker_flag(WorkerFlag {
             worker_id: worker.id,
             flag_type: WorkerFlagType::Work,
             last_update: chrono::Utc::now().naive_utc(),
-        });
+        })
+        .expect("Inserting flag");
         worker
     }
 
     fn new_village(&self, pid: PlayerKey) -> Village {
         let village = self.add_village(pid).expect("Village insertion failed");
-        self.insert_initial_resources(village.key());
+        self.init_player_village(village.key());
         village
     }
 
@@ -0,0 +1,75 @@
+use super::new_player::HERO_START;
+use super::*;
+use crate::db::testing::*;
+
+#[test]
+fn fresh_village_keeps_lane_walkable() {
+    let hero = [UnitPosition::stationary((
+        HERO_START.0 as usize,
+        HERO_START.1 as usize,
+    ))];
+    for layout in &[
+        TownLayout::Basic,
+        TownLayout::Bend,
+        TownLayout::Meander,
+        TownLayout::Fork,
+    ] {
+        let mut map = TownMap::new(*layout);
+        let anchor = temple_anchor(&map, *layout, &hero).expect("No space for temple");
+        map.insert_building(BuildingType::Temple, anchor)
+            .expect("Temple does not fit");
+        for tile in layout.lane() {
+            assert!(
+                map[tile].is_walkable(),
+                "{:?} blocked in {:?}",
+                tile,
+                layout
+            );
+            assert_eq!(map[tile], TownTileType::LANE);
+        }
+    }
+}
+
+/// Needs a running database, run it with `cargo test -- --ignored`
+#[test]
+#[ignore]
+fn leveled_heroes_get_missing_abilities() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_test_village(&db);
+    let level = 5;
+    let hero = db
+        .insert_worker(&NewWorker {
+            home: village.id,
+            x: 0,
+            y: 0,
+            unit_type: UnitType::Hero,
+            color: None,
+            speed: 0.5,
+            mana: Some(0),
+            level,
+            exp: 0,
+        })
+        .expect("Inserting hero");
+    let expected: Vec<AbilityType> = hero_level_ups(1, level).filter_map(|l| l.ability).collect();
+    assert!(!expected.is_empty());
+    // One of the abilities has already been learned
+    db.insert_ability(&NewAbility {
+        worker_id: hero.id,
+        ability_type: expected[0],
+    })
+    .expect("Inserting ability");
+
+    db.grant_missing_hero_abilities();
+    db.grant_missing_hero_abilities();
+
+    let abilities: Vec<AbilityType> = db
+        .worker_abilities(hero.key())
+        .iter()
+        .map(|a| a.ability_type)
+        .collect();
+    assert_eq!(abilities.len(), expected.len());
+    assert!(expected.iter().all(|a| abilities.contains(a)));
+
+    delete_test_village(&db, &village);
+}
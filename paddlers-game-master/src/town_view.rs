@@ -11,43 +11,62 @@ use paddlers_shared_lib::prelude::*;
 pub struct TownView {
     pub map: TownMap,
     pub state: TownState<i64>,
-    pub buildings_with_aura: Vec<Building>,
+    pub defence: DefendingTown,
+    pub layout: TownLayout,
 }
 
 impl TownView {
     pub(crate) fn load_village(db: &DB, village: VillageKey) -> Self {
-        let mut map = TownMap::new(TownLayout::Basic);
+        let layout = db
+            .village(village)
+            .map(|v| v.town_layout)
+            .unwrap_or_default();
+        let mut map = TownMap::new(layout);
         let mut state = TownState::new();
         let now = chrono::Utc::now().naive_utc();
 
         let buildings = db.buildings(village);
-        let mut buildings_with_aura = vec![];
+        let mut defending_buildings = vec![];
         for b in buildings {
             let idx = (b.x as usize, b.y as usize);
-            map[idx] = TownTileType::BUILDING(b.building_type);
+            if let Err(e) = map.insert_building(b.building_type, idx) {
+                println!("Warning: ignoring building {} at {:?}: {}", b.id, idx, e);
+                continue;
+            }
+            let footprint = b.building_type.footprint(idx);
             let capacity = b.building_type.capacity();
             let task_type = match b.building_type {
                 BuildingType::BundlingStation => TaskType::GatherSticks,
                 BuildingType::SawMill => TaskType::ChopTree,
                 _ => TaskType::Idle,
             };
-            let entity_count = db.count_workers_at_pos_doing_job(village, b.x, b.y, task_type);
-            state.insert(idx, TileState::new_building(b.id, capacity, entity_count));
+            let entity_count = footprint
+                .iter()
+                .map(|(x, y)| {
+                    db.count_workers_at_pos_doing_job(village, *x as i32, *y as i32, task_type)
+                })
+                .sum();
+            state.insert(
+                &footprint,
+                TileState::new_building(b.id, capacity, entity_count),
+            );
             let forest_supply = match b.building_type {
                 BuildingType::Tree => tree_size(now - b.creation),
                 _ => 0,
             };
             state.forest_size += forest_supply;
-            if b.attacks_per_cycle.is_none()
-                && b.building_range.is_some()
-                && b.attack_power.is_some()
-            {
-                buildings_with_aura.push(b);
+            state.worker_housing += b.building_type.worker_housing();
+            // Both, aura buildings and towers (with attacks_per_cycle) are needed for defence computations
+            if let Some(defending) = DefendingBuilding::from_db(&b) {
+                defending_buildings.push(defending);
             }
         }
 
         let workers = db.workers(village);
         for worker in workers {
+            if worker.unit_type == UnitType::Basic {
+                state.register_basic_worker();
+            }
             if let Some(task) = db.current_task(worker.key()) {
                 state
                     .register_task_begin(task.task_type)
@@ -60,7 +79,16 @@ impl TownView {
         TownView {
             map,
             state,
-            buildings_with_aura,
+            defence: DefendingTown {
+                layout,
+                buildings: defending_buildings,
+                blooms: db
+                    .blooms(village)
+                    .iter()
+                    .map(|b| (b.building_id, AuraBloom::from_db(b)))
+                    .collect(),
+            },
+            layout,
         }
     }
 
@@ -4,6 +4,8 @@
 //! For now, I am still don't really know how I want it to look like.
 
 mod worker_abilities;
+#[cfg(test)]
+mod worker_actions_test;
 mod worker_updates;
 
 use crate::db::DB;
@@ -28,6 +30,7 @@ trait WorkerAction {
 pub struct ValidatedTaskList {
     pub new_tasks: Vec<NewTask>,
     pub update_tasks: Vec<Task>,
+    pub repeating_tasks: Vec<NewRepeatingTask>,
     pub village_id: VillageKey,
 }
 pub(crate) fn validate_task_list(
@@ -45,21 +48,65 @@ pub(crate) fn validate_task_list(
     let mut current_task = db
         .current_task(worker.key())
         .expect("Must have a current task");
-    let mut timestamp =
+    let timestamp =
         interrupt_task(&mut current_task, &worker).ok_or("Cannot interrupt current task.")?;
     worker.x = current_task.x;
     worker.y = current_task.y;
 
-    // iterate tasks and match for task types
-    let mut tasks = vec![];
+    let (tasks, end) = simulate_tasks(db, &tl.tasks, &mut town, &mut worker, timestamp)?;
 
-    for task in tl.tasks.iter() {
+    let mut repeating_tasks = vec![];
+    if tl.repeat {
+        if tl
+            .tasks
+            .iter()
+            .any(|t| !t.task_type.repeatable() || t.target.is_some())
+        {
+            return Err("Task cannot be repeated".into());
+        }
+        if end <= timestamp {
+            return Err("Repeating tasks must take some time".into());
+        }
+        // The second round has to start where the first round ended
+        simulate_tasks(db, &tl.tasks, &mut town, &mut worker, end)?;
+        repeating_tasks = tl
+            .tasks
+            .iter()
+            .map(|t| NewRepeatingTask {
+                worker_id: worker_id.num(),
+                task_type: t.task_type,
+                x: t.x as i32,
+                y: t.y as i32,
+                duration_ms: t.duration.unwrap_or(0) as i64 * 1000,
+            })
+            .collect();
+    }
+    Ok(ValidatedTaskList {
+        new_tasks: tasks,
+        update_tasks: vec![current_task],
+        repeating_tasks,
+        village_id,
+    })
+}
+
+/// Checks that the tasks can be performed one after another, starting at the given time.
+/// Returns the tasks ready to be inserted and the time when the last task is finished.
+fn simulate_tasks(
+    db: &DB,
+    raw_tasks: &[RawTask],
+    town: &mut TownView,
+    worker: &mut Worker,
+    mut timestamp: NaiveDateTime,
+) -> Result<(Vec<NewTask>, NaiveDateTime), Box<dyn std::error::Error>> {
+    let worker_id = worker.key();
+    let mut tasks = vec![];
+    for task in raw_tasks.iter() {
         // Validate target hobo exists if there is one
         if let Some(target_id) = task.target {
             db.hobo(HoboKey(target_id)).ok_or("No such hobo id")?;
         }
 
-        validate_ability(db, task.task_type, worker_id, timestamp)?;
+        validate_ability(db, task.task_type, worker_id, task.target, timestamp)?;
 
         let new_task = NewTask {
             worker_id: worker_id.num(),
@@ -69,26 +116,61 @@ pub(crate) fn validate_task_list(
             start_time: Some(timestamp),
             target_hobo_id: task.target,
         };
-        simulate_begin_task(&new_task, &mut town, &mut worker)?;
-        let duration = simulate_finish_task(&new_task, &mut town, &mut worker)?;
+        simulate_begin_task(&new_task, town, worker)?;
+        let duration = simulate_finish_task(&new_task, town, worker)?;
         tasks.push(new_task);
-        timestamp += duration;
+        timestamp += duration + Duration::seconds(task.duration.unwrap_or(0) as i64);
     }
-    Ok(ValidatedTaskList {
-        new_tasks: tasks,
-        update_tasks: vec![current_task],
-        village_id,
-    })
+    Ok((tasks, timestamp))
+}
+
+/// Appends the next round of a worker's repeating tasks to its task queue.
+/// Must be called when the last task in the queue has started.
+/// If the next round cannot be performed, the repetition ends.
+pub(crate) fn expand_repeating_tasks(db: &DB, current_task: &Task) -> Result<(), String> {
+    let worker_id = current_task.worker();
+    let repeating = db.repeating_tasks(worker_id);
+    if let Some(last) = repeating.last() {
+        let raw_tasks: Vec<RawTask> = repeating
+            .iter()
+            .map(|t| {
+                RawTask::new(t.task_type, (t.x as usize, t.y as usize))
+                    .with_duration((t.duration_ms / 1000) as u32)
+            })
+            .collect();
+        let mut worker = db
+            .worker_priv(worker_id)
+            .ok_or("Task references non-existing worker")?;
+        let mut town = TownView::load_village(db, worker.home());
+        // The current task is the last of the previous round
+        let start = current_task.start_time
+            + simulate_finish_task(current_task, &mut town, &mut worker)?
+            + Duration::milliseconds(last.duration_ms);
+        match simulate_tasks(db, &raw_tasks, &mut town, &mut worker, start) {
+            Ok((tasks, _end)) => {
+                db.insert_tasks(&tasks);
+            }
+            Err(e) => {
+                db.delete_repeating_tasks(worker_id);
+                return Err(format!("Repetition stopped: {}", e));
+            }
+        }
+    }
+    Ok(())
 }
+
 pub(crate) fn replace_worker_tasks(
     db: &DB,
     worker: &Addr<TownWorker>,
     worker_id: WorkerKey,
     tasks: &[NewTask],
+    repeating_tasks: &[NewRepeatingTask],
     village_id: VillageKey,
 ) {
     db.flush_task_queue(worker_id);
+    db.delete_repeating_tasks(worker_id);
     let _inserted = db.insert_tasks(tasks);
+    db.insert_repeating_tasks(repeating_tasks);
     let current_task =
         execute_worker_tasks(db, worker_id, village_id).expect("Worker has no current task");
     if let Some(next_task) = db.earliest_future_task(worker_id) {
@@ -144,8 +226,12 @@ fn interrupt_task(current_task: &mut Task, worker: &Worker) -> Option<NaiveDateT
             current_task.y = y;
             Some(moment)
         }
-        TaskType::WelcomeAbility => {
-            let cast_time = current_task.start_time + AbilityType::Welcome.busy_duration();
+        TaskType::WelcomeAbility
+        | TaskType::CheerAbility
+        | TaskType::SlowAbility
+        | TaskType::BloomAbility => {
+            let a = AbilityType::from_task(&current_task.task_type)?;
+            let cast_time = current_task.start_time + a.busy_duration();
             Some(cast_time)
         }
     }
@@ -197,25 +283,57 @@ pub(crate) fn finish_task(
 
 fn apply_task_to_db(db: &DB, task: &Task, worker: &mut Worker) -> Result<(), String> {
     match task.task_type {
-        TaskType::WelcomeAbility => {
-            let a = AbilityType::Welcome;
-            let (attribute, strength) = a.apply();
-            let ne = NewEffect {
-                hobo_id: task.target().ok_or("Ability must have a target")?.num(),
-                attribute,
-                strength: Some(strength),
-                start_time: None, // default = now
-            };
-            db.insert_effect(&ne);
+        TaskType::WelcomeAbility
+        | TaskType::CheerAbility
+        | TaskType::SlowAbility
+        | TaskType::BloomAbility => {
+            let a = AbilityType::from_task(&task.task_type).expect("Ability task");
+            let center = (task.x as usize, task.y as usize);
+            if let Some(strength) = a.aura_buff(worker.level) {
+                for b in db.buildings(worker.home()) {
+                    if a.affects_building(b.building_type, (b.x as usize, b.y as usize), center) {
+                        db.insert_bloom(&NewBloom {
+                            building_id: b.id,
+                            strength,
+                            start_time: task.start_time,
+                        });
+                    }
+                }
+            } else {
+                let (attribute, strength) = a.apply(worker.level);
+                let targets = if a.area_of_effect() {
+                    let now = chrono::Utc::now().naive_utc();
+                    visitors_in_range(db, worker.home(), center, a.range(), now)
+                } else {
+                    vec![task.target().ok_or("Ability must have a target")?]
+                };
+                for hobo in targets {
+                    let ne = NewEffect {
+                        hobo_id: hobo.num(),
+                        attribute,
+                        strength: Some(strength),
+                        start_time: None, // default = now
+                    };
+                    db.insert_effect(&ne);
+                }
+            }
             db.update_ability_used_timestamp(WorkerKey(worker.id), a);
-            *worker.mana.as_mut().unwrap() -= AbilityType::Welcome.mana_cost();
+            *worker.mana.as_mut().unwrap() -= a.mana_cost();
         }
         TaskType::CollectReward => {
             if let Some(building) = db.find_building_by_coordinates(task.x, task.y, worker.home()) {
                 match building.building_type.reward_exp() {
                     Some(exp) => {
-                        worker.add_exp(exp);
-                        db.delete_building(&building);
+                        for level_up in worker.add_exp(exp) {
+                            if let Some(ability_type) = level_up.ability {
+                                db.insert_ability(&NewAbility {
+                                    worker_id: worker.id,
+                                    ability_type,
+                                })
+                                .map_err(|e| e.to_string())?;
+                            }
+                        }
+                        db.delete_building(&building).map_err(|e| e.to_string())?;
                     }
                     None => {
                         return Err(format!(
@@ -254,8 +372,11 @@ fn simulate_finish_task<T: WorkerAction>(
                 .map_err(|e| e.to_string())?;
             worker_out_of_building(town, worker, (task.x() as usize, task.y() as usize))
         }
-        TaskType::WelcomeAbility => {
-            let a = AbilityType::Welcome;
+        TaskType::WelcomeAbility
+        | TaskType::CheerAbility
+        | TaskType::SlowAbility
+        | TaskType::BloomAbility => {
+            let a = AbilityType::from_task(task.task_type()).expect("Ability task");
             let duration = a.busy_duration();
             Ok(duration)
         }
@@ -284,9 +405,13 @@ fn simulate_begin_task<T: WorkerAction>(
                 .map_err(|e| e.to_string())?;
             worker_into_building(town, worker, (task.x() as usize, task.y() as usize))
         }
-        TaskType::WelcomeAbility => {
+        TaskType::WelcomeAbility
+        | TaskType::CheerAbility
+        | TaskType::SlowAbility
+        | TaskType::BloomAbility => {
             if let Some(mana) = &mut worker.mana {
-                let cost = AbilityType::Welcome.mana_cost();
+                let a = AbilityType::from_task(task.task_type()).expect("Ability task");
+                let cost = a.mana_cost();
                 if *mana >= cost {
                     *mana = *mana - cost;
                     Ok(())
@@ -294,7 +419,7 @@ fn simulate_begin_task<T: WorkerAction>(
                     Err("Not enough mana".to_owned())
                 }
             } else {
-                Err("Worker has no mana but tries to use welcome ability".to_owned())
+                Err("Worker has no mana but tries to use an ability".to_owned())
             }
         }
         TaskType::Defend => Err("Task not implemented".to_owned()),
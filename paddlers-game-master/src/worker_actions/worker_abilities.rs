@@ -43,6 +43,7 @@ pub(super) fn validate_ability(
     db: &DB,
     task_type: TaskType,
     worker_id: WorkerKey,
+    target: Option<i64>,
     now: chrono::NaiveDateTime,
 ) -> Result<(), String> {
     if let Some(ability_type) = AbilityType::from_task(&task_type) {
@@ -50,6 +51,15 @@ pub(super) fn validate_ability(
         // let range = ability_type.range();
         // TODO: Take movement of visitor into account
 
+        if ability_type != AbilityType::Work && !ability_type.area_of_effect() {
+            let hobo = target
+                .and_then(|id| db.hobo(HoboKey(id)))
+                .ok_or("Ability must have a target")?;
+            if !ability_type.can_target(hobo.hurried) {
+                return Err(format!("Cannot use {} on this visitor", ability_type));
+            }
+        }
+
         if let Some(a) = db.worker_ability(worker_id, ability_type) {
             if let Some(last_used) = a.last_used {
                 let free_to_use = last_used + ability_type.cooldown();
@@ -63,3 +73,33 @@ pub(super) fn validate_ability(
     }
     Ok(())
 }
+
+/// All visitors currently in the village that are within the given range of a tile
+pub(super) fn visitors_in_range(
+    db: &DB,
+    village: VillageKey,
+    center: TileIndex,
+    range: f32,
+    now: chrono::NaiveDateTime,
+) -> Vec<HoboKey> {
+    let town = TownView::load_village(db, village);
+    let now: Timestamp = now.into();
+    let mut out = vec![];
+    for atk in db.attacks(village, None) {
+        for (hobo, info) in db.attack_hobos_active_with_attack_info(&atk) {
+            let effects = db.effects_on_hobo(hobo.key());
+            let unit = AttackingHobo {
+                hobo: &hobo,
+                attack_to_hobo: &info,
+                effects: &effects,
+                attack: &atk,
+            };
+            if let Some(pos) = town.visitor_position(&unit, now) {
+                if distance2(center, *pos) <= range * range {
+                    out.push(hobo.key());
+                }
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,158 @@
+//! These tests need a running database, configured the same way as for the game-master itself.
+//! They are ignored by default, run them with `cargo test -- --ignored`.
+
+use super::*;
+use crate::db::testing::*;
+use chrono::NaiveDate;
+
+/// Where the worker rests in the test rounds, both on grass and away from the lane
+const HOME: (usize, usize) = (0, 0);
+const FIELD: (usize, usize) = (2, 0);
+const FIELD_SECONDS: u32 = 60;
+const HOME_SECONDS: u32 = 30;
+
+#[test]
+#[ignore]
+fn simulated_tasks_wait_for_walking_and_durations() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_test_village(&db);
+    let mut worker = insert_worker(&db, &village);
+    let mut town = TownView::load_village(&db, village.key());
+
+    let (tasks, end) =
+        simulate_tasks(&db, &round(), &mut town, &mut worker, start()).expect("Simulation failed");
+
+    let starts: Vec<NaiveDateTime> = tasks.iter().map(|t| t.start_time.unwrap()).collect();
+    let walk = starts[1] - starts[0];
+    assert!(walk > Duration::zero());
+    assert_eq!(starts[0], start());
+    assert_eq!(
+        starts[2],
+        starts[1] + Duration::seconds(FIELD_SECONDS as i64)
+    );
+    assert_eq!(starts[3], starts[2] + walk);
+    assert_eq!(end, starts[3] + Duration::seconds(HOME_SECONDS as i64));
+    assert_eq!((worker.x, worker.y), (HOME.0 as i32, HOME.1 as i32));
+
+    delete_test_village(&db, &village);
+}
+
+#[test]
+#[ignore]
+fn repeating_tasks_expand_round_after_round() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_test_village(&db);
+    let worker = insert_worker(&db, &village);
+    db.insert_repeating_tasks(&repeating_round(&worker));
+    let first = db.insert_tasks(&[NewTask {
+        worker_id: worker.id,
+        task_type: TaskType::Idle,
+        x: HOME.0 as i32,
+        y: HOME.1 as i32,
+        start_time: Some(start()),
+        target_hobo_id: None,
+    }]);
+
+    // Each round is appended after the last task of the previous one, which may be far in the past
+    let mut current = first[0].clone();
+    let mut round_start = start() + Duration::seconds(HOME_SECONDS as i64);
+    for n in 1..=3 {
+        expand_repeating_tasks(&db, &current).expect("Expansion failed");
+        let tasks = sorted_tasks(&db, &worker);
+        assert_eq!(tasks.len(), 1 + 4 * n, "after round {}", n);
+        let round = &tasks[tasks.len() - 4..];
+        assert_eq!(round[0].start_time, round_start, "round {}", n);
+        let walk = round[1].start_time - round[0].start_time;
+        assert_eq!(
+            round[2].start_time,
+            round[1].start_time + Duration::seconds(FIELD_SECONDS as i64)
+        );
+        assert_eq!(round[3].start_time, round[2].start_time + walk);
+        current = round[3].clone();
+        round_start = current.start_time + Duration::seconds(HOME_SECONDS as i64);
+    }
+
+    delete_test_village(&db, &village);
+}
+
+#[test]
+#[ignore]
+fn impossible_round_stops_repetition() {
+    let db: DB = (&DB::new_pool()).into();
+    db.db_scripts_by_env().expect("DB initialization failed.");
+    let village = insert_test_village(&db);
+    let worker = insert_worker(&db, &village);
+    // There is no tree to chop in the test village
+    db.insert_repeating_tasks(&[NewRepeatingTask {
+        worker_id: worker.id,
+        task_type: TaskType::ChopTree,
+        x: FIELD.0 as i32,
+        y: FIELD.1 as i32,
+        duration_ms: 1000,
+    }]);
+    let current = db.insert_tasks(&[NewTask {
+        worker_id: worker.id,
+        task_type: TaskType::Idle,
+        x: HOME.0 as i32,
+        y: HOME.1 as i32,
+        start_time: Some(start()),
+        target_hobo_id: None,
+    }]);
+
+    assert!(expand_repeating_tasks(&db, &current[0]).is_err());
+    assert!(db.repeating_tasks(worker.key()).is_empty());
+    assert_eq!(db.worker_tasks(worker.key()).len(), 1);
+
+    delete_test_village(&db, &village);
+}
+
+/// Long before any real task
+fn start() -> NaiveDateTime {
+    NaiveDate::from_ymd(2000, 1, 1).and_hms(12, 0, 0)
+}
+
+/// Walk to the field, stay there, walk back home and stay there
+fn round() -> Vec<RawTask> {
+    vec![
+        RawTask::new(TaskType::Walk, FIELD),
+        RawTask::new(TaskType::Idle, FIELD).with_duration(FIELD_SECONDS),
+        RawTask::new(TaskType::Walk, HOME),
+        RawTask::new(TaskType::Idle, HOME).with_duration(HOME_SECONDS),
+    ]
+}
+
+fn repeating_round(worker: &Worker) -> Vec<NewRepeatingTask> {
+    round()
+        .into_iter()
+        .map(|t| NewRepeatingTask {
+            worker_id: worker.id,
+            task_type: t.task_type,
+            x: t.x as i32,
+            y: t.y as i32,
+            duration_ms: t.duration.unwrap_or(0) as i64 * 1000,
+        })
+        .collect()
+}
+
+fn insert_worker(db: &DB, village: &Village) -> Worker {
+    db.insert_worker(&NewWorker {
+        home: village.id,
+        x: HOME.0 as i32,
+        y: HOME.1 as i32,
+        unit_type: UnitType::Hero,
+        color: None,
+        speed: 0.1,
+        mana: Some(0),
+        level: 1,
+        exp: 0,
+    })
+    .expect("Inserting worker")
+}
+
+fn sorted_tasks(db: &DB, worker: &Worker) -> Vec<Task> {
+    let mut tasks = db.worker_tasks(worker.key());
+    tasks.sort_by_key(|t| t.start_time);
+    tasks
+}
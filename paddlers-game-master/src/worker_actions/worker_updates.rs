@@ -1,16 +1,24 @@
-use paddlers_shared_lib::game_mechanics::worker::hero_level_exp;
+use paddlers_shared_lib::game_mechanics::worker::{hero_level_exp, hero_level_ups, HeroLevelUp};
 use paddlers_shared_lib::models::Worker;
 
 pub trait MutWorkerDBEntity {
-    fn add_exp(&mut self, n: i32);
+    /// Adds experience and applies the stat gains of all levels reached.
+    /// Returns the level ups, abilities learned with them have to be added by the caller.
+    fn add_exp(&mut self, n: i32) -> Vec<&'static HeroLevelUp>;
 }
 
 impl MutWorkerDBEntity for Worker {
-    fn add_exp(&mut self, n: i32) {
+    fn add_exp(&mut self, n: i32) -> Vec<&'static HeroLevelUp> {
+        let before = self.level;
         self.exp += n;
         while self.exp >= hero_level_exp(self.level) {
             self.exp -= hero_level_exp(self.level);
             self.level += 1;
         }
+        let level_ups: Vec<_> = hero_level_ups(before, self.level).collect();
+        for l in &level_ups {
+            self.speed += l.speed;
+        }
+        level_ups
     }
 }
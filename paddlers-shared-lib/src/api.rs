@@ -1,11 +1,14 @@
 pub mod attacks;
 pub mod error;
 pub mod keys;
+pub mod market;
 pub mod reports;
+pub mod shipments;
 pub mod shop;
 pub mod statistics;
 pub mod story;
 pub mod tasks;
+pub mod villages;
 
 use serde::*;
 
@@ -13,3 +13,8 @@ pub struct InvitationDescriptor {
     pub to: VillageKey,
     pub nest: BuildingKey,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AttackRecall {
+    pub attack: AttackKey,
+}
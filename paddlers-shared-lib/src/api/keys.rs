@@ -55,7 +55,10 @@ object_key!(Attack, AttackKey);
 object_key!(Building, BuildingKey);
 object_key!(Hobo, HoboKey);
 object_key!(Player, PlayerKey);
+object_key!(ReturnTrip, ReturnTripKey);
+object_key!(Shipment, ShipmentKey);
 object_key!(Stream, StreamKey);
 object_key!(Task, TaskKey);
+object_key!(TradeOffer, TradeOfferKey);
 object_key!(VisitReport, VisitReportKey);
 object_key!(Worker, WorkerKey);
@@ -0,0 +1,25 @@
+use crate::prelude::*;
+use serde::*;
+
+/// Puts resources of a village on the marketplace in exchange for other resources
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TradeOfferCreation {
+    pub village: VillageKey,
+    pub offered_resource: ResourceType,
+    pub offered_amount: i64,
+    pub requested_resource: ResourceType,
+    pub requested_amount: i64,
+}
+
+/// Pays the requested resources of an offer from a village, which receives the offered resources in return
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TradeOfferAcceptance {
+    pub offer: TradeOfferKey,
+    pub village: VillageKey,
+}
+
+/// Removes an own offer from the marketplace, the offered resources are returned to the village
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TradeOfferCancellation {
+    pub offer: TradeOfferKey,
+}
@@ -0,0 +1,11 @@
+use crate::prelude::*;
+use serde::*;
+
+/// Sends resources by boat from an owned village to another village of any player
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShipmentOrder {
+    pub from: VillageKey,
+    pub to: VillageKey,
+    pub resource: ResourceType,
+    pub amount: i64,
+}
@@ -1,4 +1,7 @@
 use crate::api::keys::VillageKey;
+use crate::balancing::balancing;
+#[cfg(feature = "game_mechanics")]
+use crate::game_mechanics::attributes::LeveledBuilding;
 use crate::models::*;
 use serde::{Deserialize, Serialize};
 
@@ -12,24 +15,29 @@ pub trait Cost {
 }
 
 impl Cost for BuildingType {
+    fn cost(&self) -> Vec<(ResourceType, i64)> {
+        balancing().building(*self).cost.resources()
+    }
+}
+
+/// The price for upgrading a building to this level, which grows with the level
+#[cfg(feature = "game_mechanics")]
+impl Cost for LeveledBuilding {
+    fn cost(&self) -> Vec<(ResourceType, i64)> {
+        self.building_type
+            .cost()
+            .into_iter()
+            .map(|(res, n)| (res, n * self.level as i64))
+            .collect()
+    }
+}
+
+impl Cost for UnitType {
     fn cost(&self) -> Vec<(ResourceType, i64)> {
         match self {
-            BuildingType::BlueFlowers => vec![(ResourceType::Feathers, 20)],
-            BuildingType::RedFlowers => {
-                vec![(ResourceType::Feathers, 100), (ResourceType::Sticks, 20)]
-            }
-            BuildingType::Tree => vec![(ResourceType::Feathers, 10)],
-            BuildingType::BundlingStation => vec![(ResourceType::Feathers, 20)],
-            BuildingType::SawMill => vec![(ResourceType::Feathers, 20), (ResourceType::Sticks, 20)],
-            BuildingType::PresentA => vec![(ResourceType::Feathers, 100)],
-            BuildingType::PresentB => vec![(ResourceType::Sticks, 50), (ResourceType::Logs, 50)],
-            BuildingType::Temple => vec![],
-            BuildingType::SingleNest => {
-                vec![(ResourceType::Feathers, 20), (ResourceType::Sticks, 20)]
-            }
-            BuildingType::TripleNest => {
-                vec![(ResourceType::Feathers, 60), (ResourceType::Sticks, 60)]
-            }
+            UnitType::Basic => balancing().workers.basic_cost.resources(),
+            // Heroes are not for sale, every village starts with one
+            UnitType::Hero => vec![],
         }
     }
 }
@@ -49,7 +57,29 @@ pub struct BuildingDeletion {
     pub y: usize,
 }
 
+/// Moves the building anchored at x|y to be anchored at new_x|new_y
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BuildingRelocation {
+    pub village: VillageKey,
+    pub x: usize,
+    pub y: usize,
+    pub new_x: usize,
+    pub new_y: usize,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BuildingUpgrade {
+    pub village: VillageKey,
+    pub x: usize,
+    pub y: usize,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct ProphetPurchase {
     pub village: VillageKey,
 }
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorkerPurchase {
+    pub village: VillageKey,
+}
@@ -9,6 +9,9 @@ use serde::{Deserialize, Serialize};
 pub struct TaskList {
     pub worker_id: WorkerKey,
     pub tasks: Vec<RawTask>,
+    /// Start over with the first task after the last task has finished, until overwritten
+    #[serde(default)]
+    pub repeat: bool,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -17,6 +20,9 @@ pub struct RawTask {
     pub x: usize,
     pub y: usize,
     pub target: Option<PadlId>,
+    /// Seconds the worker stays at the task before starting the next one
+    #[serde(default)]
+    pub duration: Option<u32>,
 }
 
 impl RawTask {
@@ -27,6 +33,7 @@ impl RawTask {
             x: i.0,
             y: i.1,
             target: None,
+            duration: None,
         }
     }
     #[cfg(feature = "game_mechanics")]
@@ -36,6 +43,11 @@ impl RawTask {
             x: i.0,
             y: i.1,
             target: t.1,
+            duration: None,
         }
     }
+    pub fn with_duration(mut self, seconds: u32) -> Self {
+        self.duration = Some(seconds);
+        self
+    }
 }
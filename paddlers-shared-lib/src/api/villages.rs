@@ -0,0 +1,10 @@
+use crate::prelude::*;
+use serde::*;
+
+/// Sends a prophet to rule over another village.
+/// An empty village position on a stream gets a new village, an anarchist village at the position is converted.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VillageFoundation {
+    pub prophet: HoboKey,
+    pub position: (i32, i32),
+}
@@ -0,0 +1,456 @@
+//! Balancing numbers like prices, unlock thresholds and rewards.
+//!
+//! The values are read from `specification/balancing.toml`, which is compiled into all binaries
+//! that use this crate. Backend and frontend therefore always work with the same numbers.
+//! If the specification cannot be parsed or fails validation, the built-in values defined in
+//! [`Balancing::built_in`](Balancing::built_in) are used instead.
+
+#[cfg(test)]
+mod balancing_test;
+
+use crate::models::*;
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+
+/// Version of the specification format that this code understands
+pub const BALANCING_VERSION: u32 = 4;
+pub(crate) const BALANCING_SPEC: &str = include_str!("../specification/balancing.toml");
+
+static BALANCING: Lazy<Balancing> = Lazy::new(|| Balancing::from_toml_or_built_in(BALANCING_SPEC));
+
+/// Buildings that must be specified, each exactly once
+const SPECIFIED_BUILDINGS: [BuildingType; 14] = [
+    BuildingType::BlueFlowers,
+    BuildingType::RedFlowers,
+    BuildingType::Tree,
+    BuildingType::BundlingStation,
+    BuildingType::SawMill,
+    BuildingType::PresentA,
+    BuildingType::PresentB,
+    BuildingType::Temple,
+    BuildingType::SingleNest,
+    BuildingType::TripleNest,
+    BuildingType::Fountain,
+    BuildingType::Sprinkler,
+    BuildingType::Harbour,
+    BuildingType::Shack,
+];
+
+/// The balancing numbers currently in use
+pub fn balancing() -> &'static Balancing {
+    &BALANCING
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Balancing {
+    pub version: u32,
+    #[serde(rename = "building")]
+    pub buildings: Vec<BuildingSpec>,
+    pub prophets: ProphetSpec,
+    pub hobos: HoboSpec,
+    pub rewards: RewardSpec,
+    pub workers: WorkerSpec,
+    pub heroes: HeroSpec,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BuildingSpec {
+    #[serde(rename = "type")]
+    pub building_type: BuildingType,
+    /// Karma required to build it, None if it cannot be unlocked with karma
+    pub karma: Option<i64>,
+    #[serde(default)]
+    pub cost: CostSpec,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct CostSpec {
+    #[serde(default)]
+    pub feathers: i64,
+    #[serde(default)]
+    pub sticks: i64,
+    #[serde(default)]
+    pub logs: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ProphetSpec {
+    /// Karma needed for each additional prophet, in ascending order
+    pub karma_thresholds: Vec<i64>,
+    /// Karma needed for each additional prophet beyond the last threshold
+    pub karma_per_prophet_after_thresholds: i64,
+    /// Price of a prophet is `base_cost * (existing + cost_factor_offset)`
+    pub base_cost: CostSpec,
+    pub cost_factor_offset: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WorkerSpec {
+    /// Price for recruiting a basic worker
+    pub basic_cost: CostSpec,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HeroSpec {
+    pub base_max_mana: i32,
+    /// Satisfaction a single welcome of a hero on level 1 gives to a visitor
+    pub base_welcome_strength: i32,
+    /// Level progression, in ascending order of levels.
+    /// Levels beyond the last entry give experience but nothing else.
+    #[serde(rename = "level", default)]
+    pub levels: Vec<HeroLevelUp>,
+}
+
+/// What a hero gains on reaching a level
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct HeroLevelUp {
+    pub level: i32,
+    /// Ability learned on reaching the level
+    pub ability: Option<AbilityType>,
+    /// Added to the walking speed
+    #[serde(default)]
+    pub speed: f32,
+    /// Added to the maximum mana
+    #[serde(default)]
+    pub max_mana: i32,
+    /// Added to the satisfaction a single welcome gives to a visitor
+    #[serde(default)]
+    pub welcome_strength: i32,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct HoboSpec {
+    /// Right-exclusive HP range for hurried anarchists, indexed by hobo level.
+    /// The last entry is used for all higher levels.
+    pub hurried_anarchist_hp: Vec<(i64, i64)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RewardSpec {
+    /// Feathers for a hurried visitor are `floor(log2(1 + hp * speed / divisor))`
+    pub hurried_feathers_divisor: f32,
+    /// Feathers for an unhurried visitor are `ceil(log2(1 + hp / divisor))`
+    pub unhurried_feathers_divisor: f32,
+    /// Sticks for each effect a satisfied visitor has received
+    pub sticks_per_effect: i64,
+    #[serde(rename = "color", default)]
+    pub colors: Vec<ColorReward>,
+    #[serde(rename = "building", default)]
+    pub buildings: Vec<BuildingReward>,
+}
+
+/// Additional reward for each satisfied visitor of a color
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ColorReward {
+    pub color: UnitColor,
+    pub resource: ResourceType,
+    pub amount: i64,
+}
+
+/// Reward for every `visitors` satisfied visitors, if the town has at least one such building
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct BuildingReward {
+    #[serde(rename = "type")]
+    pub building_type: BuildingType,
+    pub resource: ResourceType,
+    pub visitors: i64,
+    pub amount: i64,
+}
+
+impl Balancing {
+    /// Parse and validate a specification in TOML format
+    pub fn from_toml(spec: &str) -> Result<Self, String> {
+        let balancing: Balancing = toml::from_str(spec).map_err(|e| e.to_string())?;
+        balancing.validate()?;
+        Ok(balancing)
+    }
+    /// Like [`from_toml`](Balancing::from_toml) but falls back to the built-in values on errors
+    fn from_toml_or_built_in(spec: &str) -> Self {
+        match Balancing::from_toml(spec) {
+            Ok(balancing) => balancing,
+            Err(e) => {
+                println!(
+                    "Invalid balancing specification, using built-in values. {}",
+                    e
+                );
+                Balancing::built_in()
+            }
+        }
+    }
+    pub fn validate(&self) -> Result<(), String> {
+        if self.version != BALANCING_VERSION {
+            return Err(format!(
+                "Unsupported balancing version {}, expected {}",
+                self.version, BALANCING_VERSION
+            ));
+        }
+        for required in &SPECIFIED_BUILDINGS {
+            let n = self
+                .buildings
+                .iter()
+                .filter(|b| b.building_type == *required)
+                .count();
+            if n != 1 {
+                return Err(format!(
+                    "{:?} must be specified exactly once, found {}",
+                    required, n
+                ));
+            }
+        }
+        for b in &self.buildings {
+            b.cost
+                .validate()
+                .map_err(|e| format!("{:?}: {}", b.building_type, e))?;
+        }
+        self.prophets.validate()?;
+        self.hobos.validate()?;
+        self.rewards.validate()?;
+        self.workers
+            .basic_cost
+            .validate()
+            .map_err(|e| format!("Basic worker: {}", e))?;
+        self.heroes.validate()?;
+        Ok(())
+    }
+    pub fn building(&self, bt: BuildingType) -> &BuildingSpec {
+        self.buildings
+            .iter()
+            .find(|b| b.building_type == bt)
+            .expect("Validated balancing has all buildings")
+    }
+    /// The balancing numbers used when no valid specification is available
+    pub fn built_in() -> Self {
+        let buildings = SPECIFIED_BUILDINGS
+            .iter()
+            .map(|bt| BuildingSpec::built_in(*bt))
+            .collect();
+        Balancing {
+            version: BALANCING_VERSION,
+            buildings,
+            prophets: ProphetSpec {
+                karma_thresholds: vec![1000, 2000, 3000, 5000, 7500, 10000, 12500, 16000, 20000],
+                karma_per_prophet_after_thresholds: 10000,
+                base_cost: CostSpec::new(500, 350, 150),
+                cost_factor_offset: 2,
+            },
+            hobos: HoboSpec {
+                hurried_anarchist_hp: vec![
+                    (1, 2),
+                    (1, 3),
+                    (1, 4),
+                    (2, 5),
+                    (3, 8),
+                    (4, 9),
+                    (6, 13),
+                    (10, 20),
+                    (20, 40),
+                    (30, 60),
+                    (50, 100),
+                ],
+            },
+            rewards: RewardSpec {
+                hurried_feathers_divisor: 4.0,
+                unhurried_feathers_divisor: 16.0,
+                sticks_per_effect: 1,
+                colors: vec![
+                    ColorReward {
+                        color: UnitColor::White,
+                        resource: ResourceType::Sticks,
+                        amount: 2,
+                    },
+                    ColorReward {
+                        color: UnitColor::Camo,
+                        resource: ResourceType::Logs,
+                        amount: 1,
+                    },
+                ],
+                buildings: vec![
+                    BuildingReward {
+                        building_type: BuildingType::RedFlowers,
+                        resource: ResourceType::Sticks,
+                        visitors: 5,
+                        amount: 1,
+                    },
+                    BuildingReward {
+                        building_type: BuildingType::Tree,
+                        resource: ResourceType::Logs,
+                        visitors: 10,
+                        amount: 1,
+                    },
+                ],
+            },
+            workers: WorkerSpec {
+                basic_cost: CostSpec::new(50, 30, 0),
+            },
+            heroes: HeroSpec {
+                base_max_mana: 100,
+                base_welcome_strength: 1,
+                levels: vec![
+                    HeroLevelUp::new(2, None, 0.0, 20, 0),
+                    HeroLevelUp::new(3, Some(AbilityType::Cheer), 0.1, 0, 0),
+                    HeroLevelUp::new(4, None, 0.0, 0, 1),
+                    HeroLevelUp::new(5, Some(AbilityType::Slow), 0.0, 30, 0),
+                    HeroLevelUp::new(6, None, 0.1, 0, 0),
+                    HeroLevelUp::new(7, Some(AbilityType::Bloom), 0.0, 0, 1),
+                    HeroLevelUp::new(8, None, 0.0, 50, 0),
+                ],
+            },
+        }
+    }
+}
+
+impl BuildingSpec {
+    fn built_in(bt: BuildingType) -> Self {
+        let (karma, cost) = match bt {
+            BuildingType::BlueFlowers => (Some(1), CostSpec::new(20, 0, 0)),
+            BuildingType::RedFlowers => (Some(450), CostSpec::new(100, 20, 0)),
+            BuildingType::Tree => (Some(1), CostSpec::new(10, 0, 0)),
+            BuildingType::BundlingStation => (Some(20), CostSpec::new(20, 0, 0)),
+            BuildingType::SawMill => (Some(150), CostSpec::new(20, 20, 0)),
+            BuildingType::PresentA => (Some(250), CostSpec::new(100, 0, 0)),
+            BuildingType::PresentB => (Some(750), CostSpec::new(0, 50, 50)),
+            BuildingType::Temple => (None, CostSpec::new(0, 0, 0)),
+            BuildingType::SingleNest => (None, CostSpec::new(20, 20, 0)),
+            BuildingType::TripleNest => (None, CostSpec::new(60, 60, 0)),
+            BuildingType::Fountain => (Some(350), CostSpec::new(80, 0, 30)),
+            BuildingType::Sprinkler => (Some(600), CostSpec::new(150, 50, 0)),
+            BuildingType::Harbour => (Some(400), CostSpec::new(0, 40, 40)),
+            BuildingType::Shack => (Some(100), CostSpec::new(30, 30, 0)),
+        };
+        BuildingSpec {
+            building_type: bt,
+            karma,
+            cost,
+        }
+    }
+}
+
+impl CostSpec {
+    pub fn new(feathers: i64, sticks: i64, logs: i64) -> Self {
+        CostSpec {
+            feathers,
+            sticks,
+            logs,
+        }
+    }
+    /// Non-zero resources, in the order they are displayed
+    pub fn resources(&self) -> Vec<(ResourceType, i64)> {
+        [
+            (ResourceType::Feathers, self.feathers),
+            (ResourceType::Sticks, self.sticks),
+            (ResourceType::Logs, self.logs),
+        ]
+        .iter()
+        .filter(|(_, n)| *n != 0)
+        .cloned()
+        .collect()
+    }
+    fn validate(&self) -> Result<(), String> {
+        if self.feathers < 0 || self.sticks < 0 || self.logs < 0 {
+            return Err("Negative cost".to_owned());
+        }
+        Ok(())
+    }
+}
+
+impl ProphetSpec {
+    pub fn prophets_allowed(&self, karma: i64) -> i64 {
+        let passed = self
+            .karma_thresholds
+            .iter()
+            .take_while(|t| **t <= karma)
+            .count() as i64;
+        match self.karma_thresholds.last() {
+            Some(last) if karma >= *last => {
+                passed + (karma - last) / self.karma_per_prophet_after_thresholds
+            }
+            _ => passed,
+        }
+    }
+    fn validate(&self) -> Result<(), String> {
+        if self.karma_thresholds.windows(2).any(|w| w[0] >= w[1]) {
+            return Err("Prophet karma thresholds must be strictly ascending".to_owned());
+        }
+        if self.karma_per_prophet_after_thresholds <= 0 {
+            return Err("Karma per prophet must be positive".to_owned());
+        }
+        self.base_cost
+            .validate()
+            .map_err(|e| format!("Prophet: {}", e))
+    }
+}
+
+impl HeroLevelUp {
+    fn new(
+        level: i32,
+        ability: Option<AbilityType>,
+        speed: f32,
+        max_mana: i32,
+        welcome_strength: i32,
+    ) -> Self {
+        HeroLevelUp {
+            level,
+            ability,
+            speed,
+            max_mana,
+            welcome_strength,
+        }
+    }
+}
+
+impl HeroSpec {
+    fn validate(&self) -> Result<(), String> {
+        if self.base_max_mana < 0 || self.base_welcome_strength < 0 {
+            return Err("Negative base stats for heroes".to_owned());
+        }
+        if self.levels.iter().any(|l| l.level <= 1) {
+            return Err("Heroes start on level 1, level ups must be above".to_owned());
+        }
+        if self.levels.windows(2).any(|w| w[0].level >= w[1].level) {
+            return Err("Hero levels must be strictly ascending".to_owned());
+        }
+        Ok(())
+    }
+}
+
+impl HoboSpec {
+    pub fn hurried_anarchist_hp(&self, level: usize) -> (i64, i64) {
+        let i = level.min(self.hurried_anarchist_hp.len() - 1);
+        self.hurried_anarchist_hp[i]
+    }
+    fn validate(&self) -> Result<(), String> {
+        if self.hurried_anarchist_hp.is_empty() {
+            return Err("No HP range for hurried anarchists".to_owned());
+        }
+        if let Some((min, max)) = self
+            .hurried_anarchist_hp
+            .iter()
+            .find(|(min, max)| *min < 1 || min >= max)
+        {
+            return Err(format!("Invalid anarchist HP range {}..{}", min, max));
+        }
+        Ok(())
+    }
+}
+
+impl RewardSpec {
+    fn validate(&self) -> Result<(), String> {
+        if !(self.hurried_feathers_divisor > 0.0 && self.unhurried_feathers_divisor > 0.0) {
+            return Err("Feather divisors must be positive".to_owned());
+        }
+        if self.sticks_per_effect < 0 {
+            return Err("Negative reward for effects".to_owned());
+        }
+        if let Some(c) = self.colors.iter().find(|c| c.amount < 0) {
+            return Err(format!("Negative reward for {:?} visitors", c.color));
+        }
+        if let Some(b) = self
+            .buildings
+            .iter()
+            .find(|b| b.visitors <= 0 || b.amount < 0)
+        {
+            return Err(format!("Invalid visitor reward for {:?}", b.building_type));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use super::*;
+
+#[test]
+fn specification_is_valid() {
+    let spec = Balancing::from_toml(BALANCING_SPEC).expect("Invalid specification");
+    assert_eq!(&spec, balancing());
+    assert_eq!(spec.building(BuildingType::Harbour).karma, Some(400));
+    assert_eq!(spec.workers.basic_cost, CostSpec::new(50, 30, 0));
+}
+
+#[test]
+fn specification_matches_built_in() {
+    let built_in = Balancing::built_in();
+    assert_eq!(built_in.validate(), Ok(()));
+    assert_eq!(&built_in, balancing());
+}
+
+#[test]
+fn invalid_specification_falls_back_to_built_in() {
+    let broken = BALANCING_SPEC.replacen("[prophets]", "[prophets", 1);
+    assert!(Balancing::from_toml(&broken).is_err());
+    assert_eq!(
+        Balancing::from_toml_or_built_in(&broken),
+        Balancing::built_in()
+    );
+    let wrong_version = BALANCING_SPEC.replacen("version = 4", "version = 3", 1);
+    assert_eq!(
+        Balancing::from_toml_or_built_in(&wrong_version),
+        Balancing::built_in()
+    );
+}
+
+#[test]
+fn invalid_specification_is_rejected() {
+    let wrong_version = BALANCING_SPEC.replacen("version = 4", "version = 3", 1);
+    assert!(Balancing::from_toml(&wrong_version).is_err());
+    let missing_building = BALANCING_SPEC.replacen("\"Harbour\"", "\"Fountain\"", 1);
+    assert!(Balancing::from_toml(&missing_building).is_err());
+    let unsorted_levels = BALANCING_SPEC.replacen("level = 3", "level = 2", 1);
+    assert!(Balancing::from_toml(&unsorted_levels).is_err());
+}
+
+#[test]
+fn prophets_allowed_by_karma() {
+    let prophets = &balancing().prophets;
+    assert_eq!(prophets.prophets_allowed(0), 0);
+    assert_eq!(prophets.prophets_allowed(999), 0);
+    assert_eq!(prophets.prophets_allowed(1000), 1);
+    assert_eq!(prophets.prophets_allowed(19999), 8);
+    assert_eq!(prophets.prophets_allowed(20000), 9);
+    assert_eq!(prophets.prophets_allowed(35000), 10);
+}
+
+#[cfg(feature = "enum_utils")]
+#[test]
+fn all_buildings_are_specified() {
+    use strum::IntoEnumIterator;
+    for bt in BuildingType::iter() {
+        assert!(SPECIFIED_BUILDINGS.contains(&bt));
+        assert!(Balancing::built_in()
+            .buildings
+            .iter()
+            .any(|b| b.building_type == bt));
+        assert!(balancing().buildings.iter().any(|b| b.building_type == bt));
+    }
+}
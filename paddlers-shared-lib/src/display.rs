@@ -14,6 +14,10 @@ impl Display for BuildingType {
             BuildingType::Temple => write!(f, "Temple"),
             BuildingType::SingleNest => write!(f, "SingleNest"),
             BuildingType::TripleNest => write!(f, "TripleNest"),
+            BuildingType::Fountain => write!(f, "fountain"),
+            BuildingType::Sprinkler => write!(f, "sprinkler"),
+            BuildingType::Harbour => write!(f, "harbour"),
+            BuildingType::Shack => write!(f, "shack"),
         }
     }
 }
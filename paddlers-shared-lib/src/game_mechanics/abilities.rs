@@ -1,27 +1,89 @@
+use super::town::{distance2, TileIndex};
+use super::worker::hero_welcome_strength;
 use crate::models::*;
 use chrono::Duration;
 
 impl AbilityType {
-    /// Returns affected attribute and strength of ability
-    pub fn apply(&self) -> (HoboAttributeType, i32) {
+    /// Returns affected attribute and strength of ability, when used by a hero of the given level
+    pub fn apply(&self, level: i32) -> (HoboAttributeType, i32) {
         match self {
             AbilityType::Work => panic!("Cannot apply work to hobo"),
-            AbilityType::Welcome => (HoboAttributeType::Health, 1),
+            AbilityType::Bloom => panic!("Cannot apply bloom to hobo"),
+            AbilityType::Welcome | AbilityType::Cheer => {
+                (HoboAttributeType::Health, hero_welcome_strength(level))
+            }
+            AbilityType::Slow => (HoboAttributeType::Speed, -50),
         }
     }
 
+    /// Strength added to the auras of affected buildings, when used by a hero of the given level
+    pub fn aura_buff(&self, _level: i32) -> Option<i32> {
+        match self {
+            AbilityType::Bloom => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Whether the ability, used on the center tile, affects the building placed on the anchor.
+    /// Only blooms affect buildings, namely flowers with any of their tiles in range.
+    pub fn affects_building(&self, bt: BuildingType, anchor: TileIndex, center: TileIndex) -> bool {
+        let flowers = matches!(bt, BuildingType::BlueFlowers | BuildingType::RedFlowers);
+        let range = self.range();
+        *self == AbilityType::Bloom
+            && flowers
+            && bt
+                .footprint(anchor)
+                .into_iter()
+                .any(|tile| distance2(center, tile) <= range * range)
+    }
+
     pub fn from_task(task: &TaskType) -> Option<AbilityType> {
         match task {
             TaskType::WelcomeAbility => Some(AbilityType::Welcome),
+            TaskType::CheerAbility => Some(AbilityType::Cheer),
+            TaskType::SlowAbility => Some(AbilityType::Slow),
+            TaskType::BloomAbility => Some(AbilityType::Bloom),
             TaskType::ChopTree | TaskType::GatherSticks => Some(AbilityType::Work),
             _ => None,
         }
     }
 
+    /// The task to perform for using the ability, if it can be used directly
+    pub fn task(&self) -> Option<TaskType> {
+        match self {
+            AbilityType::Work => None,
+            AbilityType::Welcome => Some(TaskType::WelcomeAbility),
+            AbilityType::Cheer => Some(TaskType::CheerAbility),
+            AbilityType::Slow => Some(TaskType::SlowAbility),
+            AbilityType::Bloom => Some(TaskType::BloomAbility),
+        }
+    }
+
+    /// Area abilities affect all visitors or buildings in range of the position where they are used.
+    /// All other abilities need a single visitor as target.
+    pub fn area_of_effect(&self) -> bool {
+        match self {
+            AbilityType::Cheer | AbilityType::Bloom => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the ability can be used on the visitor
+    pub fn can_target(&self, hurried: bool) -> bool {
+        match self {
+            AbilityType::Work => false,
+            AbilityType::Slow => hurried,
+            _ => true,
+        }
+    }
+
     /// How long it takes a worker to perform the ability
     pub fn busy_duration(&self) -> Duration {
         let ms = match self {
             AbilityType::Welcome => 1000,
+            AbilityType::Cheer => 2000,
+            AbilityType::Slow => 1000,
+            AbilityType::Bloom => 1000,
             AbilityType::Work => 0,
         };
         Duration::milliseconds(ms)
@@ -31,6 +93,9 @@ impl AbilityType {
     pub fn cooldown(&self) -> Duration {
         let ms = match self {
             AbilityType::Welcome => 30000,
+            AbilityType::Cheer => 90000,
+            AbilityType::Slow => 45000,
+            AbilityType::Bloom => 60000,
             AbilityType::Work => 0,
         };
         Duration::milliseconds(ms)
@@ -39,6 +104,9 @@ impl AbilityType {
     pub fn mana_cost(&self) -> i32 {
         match self {
             AbilityType::Welcome => 5,
+            AbilityType::Cheer => 20,
+            AbilityType::Slow => 10,
+            AbilityType::Bloom => 10,
             AbilityType::Work => 0,
         }
     }
@@ -46,6 +114,9 @@ impl AbilityType {
     pub fn range(&self) -> f32 {
         match self {
             AbilityType::Welcome => 2.0,
+            AbilityType::Cheer => 2.0,
+            AbilityType::Slow => 3.0,
+            AbilityType::Bloom => 2.0,
             AbilityType::Work => 0.0,
         }
     }
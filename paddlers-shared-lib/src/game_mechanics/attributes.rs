@@ -1,5 +1,8 @@
 use crate::models::*;
 
+#[cfg(test)]
+mod attributes_test;
+
 pub trait Attributes {
     fn range(&self) -> Option<f32>;
     fn attack_power(&self) -> Option<i64>;
@@ -7,36 +10,104 @@ pub trait Attributes {
     fn size(&self) -> (usize, usize);
 }
 
+/// A building type at a specific level, starting with level 1 for new buildings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LeveledBuilding {
+    pub building_type: BuildingType,
+    pub level: i32,
+}
+
+/// Range, attack power and attacks per cycle for each level of a building type, starting at level 1.
+/// Buildings that cannot be upgraded have a single level.
+type LevelTable = &'static [(Option<f32>, Option<i64>, Option<i64>)];
+
+fn level_table(bt: BuildingType) -> LevelTable {
+    match bt {
+        BuildingType::BlueFlowers => &[
+            (Some(2.0), Some(1), None),
+            (Some(2.0), Some(2), None),
+            (Some(3.0), Some(2), None),
+        ],
+        BuildingType::RedFlowers => &[
+            (Some(1.0), Some(3), None),
+            (Some(1.0), Some(4), None),
+            (Some(2.0), Some(5), None),
+        ],
+        BuildingType::Fountain => &[
+            (Some(1.0), Some(4), Some(1)),
+            (Some(1.0), Some(5), Some(2)),
+            (Some(2.0), Some(6), Some(2)),
+        ],
+        BuildingType::Sprinkler => &[
+            (Some(2.0), Some(1), Some(3)),
+            (Some(2.0), Some(2), Some(3)),
+            (Some(3.0), Some(2), Some(4)),
+        ],
+        _ => &[(None, None, None)],
+    }
+}
+
+impl BuildingType {
+    /// The highest level a building of this type can be upgraded to
+    pub fn max_level(&self) -> i32 {
+        level_table(*self).len() as i32
+    }
+}
+
+impl LeveledBuilding {
+    pub fn new(building_type: BuildingType, level: i32) -> Self {
+        LeveledBuilding {
+            building_type,
+            level,
+        }
+    }
+    /// The same building one level higher, if the building type allows it
+    pub fn next_level(&self) -> Option<Self> {
+        if self.level < self.building_type.max_level() {
+            Some(Self::new(self.building_type, self.level + 1))
+        } else {
+            None
+        }
+    }
+    fn level_attributes(&self) -> (Option<f32>, Option<i64>, Option<i64>) {
+        let table = level_table(self.building_type);
+        let i = (self.level.max(1) as usize - 1).min(table.len() - 1);
+        table[i]
+    }
+}
+
+impl Attributes for LeveledBuilding {
+    fn range(&self) -> Option<f32> {
+        self.level_attributes().0
+    }
+    fn attack_power(&self) -> Option<i64> {
+        self.level_attributes().1
+    }
+    fn attacks_per_cycle(&self) -> Option<i64> {
+        self.level_attributes().2
+    }
+    fn size(&self) -> (usize, usize) {
+        self.building_type.size()
+    }
+}
+
+/// Attributes of a building type are those of a new building, at level 1
 impl Attributes for BuildingType {
     fn range(&self) -> Option<f32> {
-        match self {
-            BuildingType::BlueFlowers => Some(2.0),
-            BuildingType::RedFlowers => Some(1.0),
-            _ => None,
-        }
+        LeveledBuilding::new(*self, 1).range()
     }
     fn attack_power(&self) -> Option<i64> {
-        match self {
-            BuildingType::BlueFlowers => Some(1),
-            BuildingType::RedFlowers => Some(3),
-            _ => None,
-        }
+        LeveledBuilding::new(*self, 1).attack_power()
     }
     fn attacks_per_cycle(&self) -> Option<i64> {
-        match self {
-            BuildingType::BlueFlowers => None,
-            BuildingType::RedFlowers => None,
-            BuildingType::Tree => None,
-            BuildingType::BundlingStation => None,
-            BuildingType::SawMill => None,
-            BuildingType::PresentA => None,
-            BuildingType::PresentB => None,
-            BuildingType::Temple => None,
-            BuildingType::SingleNest => None,
-            BuildingType::TripleNest => None,
-        }
+        LeveledBuilding::new(*self, 1).attacks_per_cycle()
     }
+    /// Width and height in tiles
     fn size(&self) -> (usize, usize) {
-        (1, 1)
+        match self {
+            BuildingType::Temple => (2, 2),
+            BuildingType::SawMill => (2, 1),
+            _ => (1, 1),
+        }
     }
 }
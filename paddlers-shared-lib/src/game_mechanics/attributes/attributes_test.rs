@@ -0,0 +1,32 @@
+use super::*;
+
+#[test]
+fn new_buildings_have_level_one_attributes() {
+    for bt in &[
+        BuildingType::BlueFlowers,
+        BuildingType::Fountain,
+        BuildingType::Tree,
+    ] {
+        let leveled = LeveledBuilding::new(*bt, 1);
+        assert_eq!(bt.range(), leveled.range());
+        assert_eq!(bt.attack_power(), leveled.attack_power());
+        assert_eq!(bt.attacks_per_cycle(), leveled.attacks_per_cycle());
+    }
+}
+
+#[test]
+fn upgrades_stop_at_max_level() {
+    assert_eq!(BuildingType::Tree.max_level(), 1);
+    assert!(LeveledBuilding::new(BuildingType::Tree, 1)
+        .next_level()
+        .is_none());
+
+    let mut flowers = LeveledBuilding::new(BuildingType::RedFlowers, 1);
+    while let Some(next) = flowers.next_level() {
+        assert!(next.attack_power() >= flowers.attack_power());
+        assert!(next.range() >= flowers.range());
+        flowers = next;
+    }
+    assert_eq!(flowers.level, BuildingType::RedFlowers.max_level());
+    assert!(flowers.level > 1);
+}
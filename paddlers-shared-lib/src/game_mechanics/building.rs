@@ -1,3 +1,6 @@
+use super::attributes::Attributes;
+use super::town::TileIndex;
+use crate::balancing::balancing;
 use crate::models::*;
 use crate::story::story_state::StoryState;
 
@@ -19,6 +22,18 @@ impl BuildingState {
 }
 
 impl BuildingType {
+    /// All tiles covered by the building when its upper left corner is placed on the anchor.
+    /// The anchor is always the first tile in the list.
+    pub fn footprint(&self, anchor: TileIndex) -> Vec<TileIndex> {
+        let (w, h) = self.size();
+        let mut tiles = Vec::with_capacity(w * h);
+        for y in anchor.1..anchor.1 + h {
+            for x in anchor.0..anchor.0 + w {
+                tiles.push((x, y));
+            }
+        }
+        tiles
+    }
     pub fn capacity(&self) -> usize {
         match self {
             BuildingType::BundlingStation => 2,
@@ -26,6 +41,28 @@ impl BuildingType {
             _ => 0,
         }
     }
+    /// Additional visitors that can rest in a town with this building
+    pub fn resting_spots(&self) -> usize {
+        match self {
+            BuildingType::Harbour => 2,
+            _ => 0,
+        }
+    }
+    /// Basic workers that can live in a town with this building
+    pub fn worker_housing(&self) -> usize {
+        match self {
+            BuildingType::Shack => 2,
+            _ => 0,
+        }
+    }
+    /// Hobos that can live in a nest of this type
+    pub fn nest_capacity(&self) -> usize {
+        match self {
+            BuildingType::SingleNest => 1,
+            BuildingType::TripleNest => 3,
+            _ => 0,
+        }
+    }
 }
 
 impl BuildingType {
@@ -43,16 +80,11 @@ impl BuildingType {
     /// Definition of which buildings are available to a player
     pub fn player_can_build(&self, karma: i64, story_state: StoryState) -> bool {
         match self {
-            BuildingType::BlueFlowers => karma >= 1,
-            BuildingType::BundlingStation => karma >= 20,
-            BuildingType::PresentA => karma >= 250,
-            BuildingType::PresentB => karma >= 750,
-            BuildingType::RedFlowers => karma >= 450,
-            BuildingType::SawMill => karma >= 150,
             BuildingType::Temple => story_state == StoryState::ServantAccepted,
-            BuildingType::Tree => karma >= 1,
-            BuildingType::SingleNest => false,
-            BuildingType::TripleNest => false,
+            _ => balancing()
+                .building(*self)
+                .karma
+                .map_or(false, |required| karma >= required),
         }
     }
     /// Buildings that may be available at the default shop, regardless of player restrictions
@@ -68,6 +100,10 @@ impl BuildingType {
             BuildingType::Temple,
             BuildingType::SingleNest,
             BuildingType::TripleNest,
+            BuildingType::Fountain,
+            BuildingType::Sprinkler,
+            BuildingType::Harbour,
+            BuildingType::Shack,
         ]
         .iter()
     }
@@ -82,8 +118,12 @@ impl BuildingType {
             BuildingType::SawMill => true,
             BuildingType::Temple => false,
             BuildingType::Tree => true,
-            BuildingType::SingleNest => false, // false for now, to avoid problems with associated hobos
-            BuildingType::TripleNest => false, // false for now, to avoid problems with associated hobos
+            BuildingType::SingleNest => true,
+            BuildingType::TripleNest => true,
+            BuildingType::Fountain => true,
+            BuildingType::Sprinkler => true,
+            BuildingType::Harbour => true,
+            BuildingType::Shack => false, // false for now, to avoid homeless workers
         }
     }
 }
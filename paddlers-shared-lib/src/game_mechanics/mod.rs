@@ -5,5 +5,6 @@ pub mod forestry;
 pub mod hobos;
 pub mod map;
 pub mod prophets;
+pub mod rewards;
 pub mod town;
 pub mod worker;
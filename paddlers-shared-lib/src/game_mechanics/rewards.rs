@@ -0,0 +1,118 @@
+//! Rewards granted by visitors after they have been satisfied.
+//!
+//! Rewards only depend on attributes of the visitors and on the buildings of the visited town.
+//! The numbers are defined in the [balancing specification](crate::balancing).
+
+#[cfg(test)]
+mod rewards_test;
+
+use crate::balancing::{balancing, RewardSpec};
+use crate::models::*;
+
+/// The attributes of a satisfied visitor which are relevant for rewards
+#[derive(Debug, Clone)]
+pub struct SatisfiedVisitor {
+    pub hp: i64,
+    pub speed: f32,
+    pub hurried: bool,
+    pub color: Option<UnitColor>,
+    /// Number of effects received from workers during the visit
+    pub effects: usize,
+}
+
+/// A part of the total rewards of a visit, together with the reason why it has been granted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RewardItem {
+    pub resource: ResourceType,
+    pub amount: i64,
+    pub reason: RewardReason,
+    pub building_type: Option<BuildingType>,
+}
+
+/// Computes the rewards for a visit with the balancing numbers currently in use.
+/// Items with the same reason and resource are merged, items without any reward are omitted.
+pub fn visit_rewards<'a>(
+    visitors: &[SatisfiedVisitor],
+    buildings: impl IntoIterator<Item = &'a BuildingType>,
+) -> Vec<RewardItem> {
+    visit_rewards_with_spec(&balancing().rewards, visitors, buildings)
+}
+
+pub fn visit_rewards_with_spec<'a>(
+    spec: &RewardSpec,
+    visitors: &[SatisfiedVisitor],
+    buildings: impl IntoIterator<Item = &'a BuildingType>,
+) -> Vec<RewardItem> {
+    let mut items = vec![];
+    for v in visitors {
+        add(
+            &mut items,
+            ResourceType::Feathers,
+            visitor_feathers(spec, v),
+            RewardReason::Visitor,
+            None,
+        );
+        add(
+            &mut items,
+            ResourceType::Sticks,
+            spec.sticks_per_effect * v.effects as i64,
+            RewardReason::Effects,
+            None,
+        );
+        for c in spec.colors.iter().filter(|c| Some(c.color) == v.color) {
+            add(&mut items, c.resource, c.amount, RewardReason::Color, None);
+        }
+    }
+    let buildings: Vec<&BuildingType> = buildings.into_iter().collect();
+    for b in &spec.buildings {
+        if buildings.contains(&&b.building_type) {
+            let amount = visitors.len() as i64 / b.visitors * b.amount;
+            add(
+                &mut items,
+                b.resource,
+                amount,
+                RewardReason::Building,
+                Some(b.building_type),
+            );
+        }
+    }
+    items
+}
+
+fn visitor_feathers(spec: &RewardSpec, v: &SatisfiedVisitor) -> i64 {
+    let f = if v.hurried {
+        (1.0 + v.hp as f32 * v.speed / spec.hurried_feathers_divisor)
+            .log2()
+            .floor()
+    } else {
+        (1.0 + v.hp as f32 / spec.unhurried_feathers_divisor)
+            .log2()
+            .ceil()
+    };
+    f as i64
+}
+
+fn add(
+    items: &mut Vec<RewardItem>,
+    resource: ResourceType,
+    amount: i64,
+    reason: RewardReason,
+    building_type: Option<BuildingType>,
+) {
+    if amount <= 0 {
+        return;
+    }
+    if let Some(item) = items
+        .iter_mut()
+        .find(|i| i.resource == resource && i.reason == reason && i.building_type == building_type)
+    {
+        item.amount += amount;
+    } else {
+        items.push(RewardItem {
+            resource,
+            amount,
+            reason,
+            building_type,
+        });
+    }
+}
@@ -0,0 +1,73 @@
+use super::*;
+use crate::balancing::balancing;
+
+fn visitor(hp: i64, hurried: bool, color: Option<UnitColor>, effects: usize) -> SatisfiedVisitor {
+    SatisfiedVisitor {
+        hp,
+        speed: 0.5,
+        hurried,
+        color,
+        effects,
+    }
+}
+
+fn total(items: &[RewardItem], resource: ResourceType) -> i64 {
+    items
+        .iter()
+        .filter(|i| i.resource == resource)
+        .map(|i| i.amount)
+        .sum()
+}
+
+#[test]
+fn no_visitors_no_rewards() {
+    let spec = &balancing().rewards;
+    let buildings = [BuildingType::Tree, BuildingType::RedFlowers];
+    assert!(visit_rewards_with_spec(spec, &[], buildings.iter()).is_empty());
+}
+
+#[test]
+fn rewards_are_merged_per_reason() {
+    let spec = &balancing().rewards;
+    let visitors = vec![
+        visitor(16, false, Some(UnitColor::Yellow), 0),
+        visitor(16, false, Some(UnitColor::Yellow), 1),
+        visitor(48, false, Some(UnitColor::White), 2),
+    ];
+    let items = visit_rewards_with_spec(spec, &visitors, [].iter());
+    // ceil(log2(2)) + ceil(log2(2)) + ceil(log2(4))
+    assert_eq!(total(&items, ResourceType::Feathers), 4);
+    let effects = items
+        .iter()
+        .find(|i| i.reason == RewardReason::Effects)
+        .unwrap();
+    assert_eq!(effects.amount, 3 * spec.sticks_per_effect);
+    let color = items
+        .iter()
+        .find(|i| i.reason == RewardReason::Color)
+        .unwrap();
+    assert_eq!(color.resource, ResourceType::Sticks);
+    assert_eq!(items.len(), 3);
+}
+
+#[test]
+fn buildings_reward_groups_of_visitors() {
+    let spec = &balancing().rewards;
+    let visitors = vec![visitor(1, true, None, 0); 10];
+    let buildings = [
+        BuildingType::Tree,
+        BuildingType::Tree,
+        BuildingType::SawMill,
+    ];
+    let items = visit_rewards_with_spec(spec, &visitors, buildings.iter());
+    let tree = items
+        .iter()
+        .find(|i| i.building_type == Some(BuildingType::Tree))
+        .expect("No reward for tree");
+    assert_eq!(tree.reason, RewardReason::Building);
+    assert_eq!(tree.amount, 1);
+    assert_eq!(total(&items, ResourceType::Logs), 1);
+
+    let items = visit_rewards_with_spec(spec, &visitors[0..9], buildings.iter());
+    assert_eq!(total(&items, ResourceType::Logs), 0);
+}
@@ -1,11 +1,25 @@
 pub mod defence;
+pub mod placement;
 pub mod town_layout;
 
-pub use defence::{IAttackingHobo, IDefendingTown};
+#[cfg(feature = "sql_db")]
+pub use defence::AttackingHobo;
+pub use defence::{
+    AuraBloom, DefendingBuilding, DefendingTown, IAttackingHobo, IDefendingTown, TimedEffect,
+    Tower, VisitorForecast,
+};
+pub use placement::{
+    find_building_space, validate_building_placement, validate_building_relocation, PlacementError,
+    UnitPosition,
+};
 pub use town_layout::{ITownLayout, ITownLayoutMarker, TownLayout};
 
 #[cfg(test)]
 mod defence_test;
+#[cfg(test)]
+mod placement_test;
+#[cfg(test)]
+mod town_state_test;
 
 use crate::game_mechanics::building::*;
 use crate::models::BuildingType;
@@ -16,14 +30,16 @@ use std::collections::HashMap;
 pub const TOWN_X: usize = 9;
 /// Height of town in unit lengths
 pub const TOWN_Y: usize = 7;
-/// The town Y coordinate where the river flows through
+/// The town Y coordinate where the river flows through in the basic layout. Other layouts bend around it.
 pub const TOWN_LANE_Y: usize = 3;
-/// The town X where resting paddlers will wait
+/// The town X where resting paddlers will wait, in all layouts
 pub const TOWN_RESTING_X: usize = 4;
-/// How many unhurried visitors can be resting in a town
-pub const MAX_VISITOR_QUEUE: usize = 1;
+/// How many unhurried visitors can be resting in a town without any buildings adding resting spots
+pub const BASE_VISITOR_QUEUE: usize = 1;
+/// Duration of one attack cycle in seconds, for buildings which hit visitors a limited number of times per cycle
+pub const DEFENCE_CYCLE_SECONDS: i64 = 10;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct TownMap(pub [[TownTileType; TOWN_Y]; TOWN_X]);
 pub type TileIndex = (usize, usize);
 pub type TownLayoutIndex = (usize, usize);
@@ -40,10 +56,16 @@ pub enum TownTileType {
 /// The frontend may have this state duplicated in components.
 /// State that is only used by the frontend does not belong in here.
 pub struct TownState<I: Eq + std::hash::Hash + Clone + Copy + std::fmt::Debug> {
+    /// States are stored at the anchor tile of buildings
     tiles: HashMap<TileIndex, TileState<I>>,
+    /// Maps each tile covered by a building to its anchor
+    anchors: HashMap<TileIndex, TileIndex>,
     entity_locations: HashMap<I, TileIndex>,
     pub forest_size: usize,
     forest_usage: usize,
+    /// How many basic workers can live in the town
+    pub worker_housing: usize,
+    basic_workers: usize,
 }
 // Note: So far, this has only one use-case which is buildings.
 // Likely, refactoring will become necessary to facilitate other states.
@@ -53,25 +75,106 @@ pub struct TileState<I: Eq + std::hash::Hash + Clone + Copy + std::fmt::Debug> {
     pub building_state: BuildingState,
 }
 
+/// How many unhurried visitors can be resting in a town with the given buildings.
+/// Each building must be listed once, no matter how many tiles it covers.
+/// The layout limits the capacity, as every visitor needs its own resting spot.
+pub fn visitor_queue_capacity<'a>(
+    layout: TownLayout,
+    buildings: impl IntoIterator<Item = &'a BuildingType>,
+) -> usize {
+    let spots = BASE_VISITOR_QUEUE
+        + buildings
+            .into_iter()
+            .map(BuildingType::resting_spots)
+            .sum::<usize>();
+    spots.min(layout.resting_spots())
+}
+
+/// The resting spot for a visitor arriving after all visitors that currently hold a spot.
+///
+/// `taken` lists the spots held by visitors which have not been released, ordered by their arrival.
+/// Usually, the lowest free spot is returned.
+/// If all spots are taken, the visitor arriving first will have to leave to make room and its spot is reused.
+pub fn next_resting_spot(taken: &[usize], capacity: usize) -> usize {
+    (0..capacity)
+        .find(|spot| !taken.contains(spot))
+        .or_else(|| taken.first().copied())
+        .unwrap_or(0)
+}
+
+/// How many basic workers can live in a town with the given buildings
+pub fn worker_housing_capacity<'a>(buildings: impl IntoIterator<Item = &'a BuildingType>) -> usize {
+    buildings
+        .into_iter()
+        .map(BuildingType::worker_housing)
+        .sum::<usize>()
+}
+
 impl TownMap {
     pub fn new(layout: TownLayout) -> TownMap {
-        match layout {
-            TownLayout::Basic => {
-                let mut map = [[TownTileType::EMPTY; TOWN_Y]; TOWN_X];
-                for x in 0..TOWN_X {
-                    for y in 0..TOWN_Y {
-                        if y == TOWN_LANE_Y {
-                            map[x][y] = TownTileType::LANE;
-                        }
-                    }
-                }
-                TownMap(map)
+        let mut map = [[TownTileType::EMPTY; TOWN_Y]; TOWN_X];
+        for (x, y) in layout.lane() {
+            map[x][y] = TownTileType::LANE;
+        }
+        TownMap(map)
+    }
+    /// Marks all tiles covered by a building.
+    /// Buildings that would reach outside the town, cover the lane or another building are refused
+    /// and the map stays unchanged.
+    pub fn insert_building(
+        &mut self,
+        bt: BuildingType,
+        anchor: TileIndex,
+    ) -> Result<(), PlacementError> {
+        let footprint = bt.footprint(anchor);
+        for tile in &footprint {
+            match self.tile_type(*tile) {
+                None => return Err(PlacementError::OutOfBounds),
+                Some(TownTileType::LANE) => return Err(PlacementError::Lane),
+                Some(TownTileType::BUILDING(_)) => return Err(PlacementError::Occupied),
+                Some(TownTileType::EMPTY) => {}
+            }
+        }
+        for tile in footprint {
+            self[tile] = TownTileType::BUILDING(bt);
+        }
+        Ok(())
+    }
+    /// Clears all tiles covered by a building
+    pub fn remove_building(&mut self, bt: BuildingType, anchor: TileIndex) {
+        for tile in bt.footprint(anchor) {
+            if let Some(t) = self.tile_type_mut(tile) {
+                *t = TownTileType::EMPTY;
             }
         }
     }
+    /// Whether all tiles that a new building would cover are free to build on
+    pub fn footprint_is_buildable(&self, bt: BuildingType, anchor: TileIndex) -> bool {
+        bt.footprint(anchor).iter().all(|tile| {
+            self.tile_type(*tile)
+                .map_or(false, TownTileType::is_buildable)
+        })
+    }
+    /// Distance from any tile of a footprint to the closest lane tile, minus one.
+    pub fn footprint_distance_to_lane(&self, footprint: &[TileIndex]) -> f32 {
+        footprint
+            .iter()
+            .map(|i| self.distance_to_lane(*i))
+            .fold(std::f32::INFINITY, f32::min)
+    }
+    /// Distance from a tile to the closest lane tile, minus one. (Neighbours of the lane have distance 0)
     pub fn distance_to_lane(&self, i: TileIndex) -> f32 {
-        // Only works for simple map!
-        let d = (i.1 as f32 - TOWN_LANE_Y as f32).abs() - 1.0;
+        let mut d2 = std::usize::MAX;
+        for (x, col) in self.0.iter().enumerate() {
+            for (y, tile) in col.iter().enumerate() {
+                if *tile == TownTileType::LANE {
+                    let dx = (x as i32 - i.0 as i32).abs() as usize;
+                    let dy = (y as i32 - i.1 as i32).abs() as usize;
+                    d2 = d2.min(dx * dx + dy * dy);
+                }
+            }
+        }
+        let d = (d2 as f32).sqrt() - 1.0;
         d.max(0.0)
     }
 
@@ -99,7 +202,8 @@ impl TownTileType {
                 | BuildingType::PresentA
                 | BuildingType::PresentB
                 | BuildingType::RedFlowers
-                | BuildingType::BlueFlowers => true,
+                | BuildingType::BlueFlowers
+                | BuildingType::Sprinkler => true,
                 _ => false,
             },
         }
@@ -110,30 +214,83 @@ impl<I: Eq + std::hash::Hash + Clone + Copy + std::fmt::Debug> TownState<I> {
     pub fn new() -> Self {
         TownState {
             tiles: HashMap::new(),
+            anchors: HashMap::new(),
             entity_locations: HashMap::new(),
             forest_size: 0,
             forest_usage: 0,
+            worker_housing: 0,
+            basic_workers: 0,
         }
     }
     pub fn forest_usage(&self) -> usize {
         self.forest_usage
     }
+    pub fn basic_workers(&self) -> usize {
+        self.basic_workers
+    }
+    /// Registers a basic worker that already lives in the town
+    pub fn register_basic_worker(&mut self) {
+        self.basic_workers += 1;
+    }
+    /// A newly recruited basic worker moves into the town and starts with the given task.
+    /// Requires free housing and enough supply for the task.
+    pub fn try_recruit_worker(&mut self, task: TaskType) -> Result<(), TownError> {
+        if self.basic_workers >= self.worker_housing {
+            return Err(TownError::NoHousing);
+        }
+        self.register_task_begin(task)?;
+        self.basic_workers += 1;
+        Ok(())
+    }
 
-    pub fn insert(&mut self, tile: TileIndex, state: TileState<I>) {
-        let e = state.entity;
-        self.tiles.insert(tile, state);
-        self.entity_locations.insert(e, tile);
+    /// Inserts the state of a building covering the given tiles.
+    /// The first tile of the footprint is the anchor of the building.
+    pub fn insert(&mut self, footprint: &[TileIndex], state: TileState<I>) {
+        let anchor = footprint[0];
+        for tile in footprint {
+            self.anchors.insert(*tile, anchor);
+        }
+        self.entity_locations.insert(state.entity, anchor);
+        self.tiles.insert(anchor, state);
     }
+    /// Removes the state of the building covering the tile
     pub fn remove(&mut self, tile: &TileIndex) -> TileState<I> {
-        let state = self.tiles.remove(tile).unwrap();
+        let anchor = self.anchors[tile];
+        self.anchors.retain(|_, a| *a != anchor);
+        let state = self.tiles.remove(&anchor).unwrap();
         self.entity_locations.remove(&state.entity);
         state
     }
     pub fn get(&self, tile: &TileIndex) -> Option<&TileState<I>> {
-        self.tiles.get(tile)
+        self.anchors.get(tile).and_then(|a| self.tiles.get(a))
     }
     pub fn get_mut(&mut self, tile: &TileIndex) -> Option<&mut TileState<I>> {
-        self.tiles.get_mut(tile)
+        let tiles = &mut self.tiles;
+        self.anchors.get(tile).and_then(move |a| tiles.get_mut(a))
+    }
+    /// The anchor tiles of all buildings, one per building
+    pub fn building_anchors(&self) -> impl Iterator<Item = &TileIndex> {
+        self.tiles.keys()
+    }
+    /// The tile where the building covering the given tile is anchored
+    pub fn anchor(&self, tile: &TileIndex) -> Option<TileIndex> {
+        self.anchors.get(tile).copied()
+    }
+    /// All tiles covered by the same building as the given tile
+    pub fn footprint(&self, tile: &TileIndex) -> Vec<TileIndex> {
+        match self.anchors.get(tile) {
+            Some(anchor) => {
+                let mut tiles: Vec<TileIndex> = self
+                    .anchors
+                    .iter()
+                    .filter(|(_, a)| *a == anchor)
+                    .map(|(t, _)| *t)
+                    .collect();
+                tiles.sort();
+                tiles
+            }
+            None => vec![],
+        }
     }
     pub fn has_supply_for_additional_worker(&self, task: TaskType) -> bool {
         let supply = self.forest_size - self.forest_usage;
@@ -208,11 +365,12 @@ pub fn distance2(a: TileIndex, b: TileIndex) -> f32 {
     x * x + y * y
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum TownError {
     BuildingFull,
     InvalidState(&'static str),
     NotEnoughSupply,
+    NoHousing,
 }
 
 impl std::error::Error for TownError {}
@@ -222,6 +380,7 @@ impl std::fmt::Display for TownError {
             TownError::BuildingFull => write!(f, "No space in building"),
             TownError::InvalidState(s) => write!(f, "Invalid state: {}", s),
             TownError::NotEnoughSupply => write!(f, "Not enough supplies"),
+            TownError::NoHousing => write!(f, "No housing for another worker"),
         }
     }
 }
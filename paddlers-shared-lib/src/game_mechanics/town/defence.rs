@@ -3,9 +3,25 @@
 //! The traits in here define what information is required to perform the computations.
 //! Based solely on this information, the computation is defined inside the traits.
 //! The frontend and the backend can therefore use his computation by implementing the traits.
-use super::town_layout::ITownLayout;
-use super::{TOWN_RESTING_X, TOWN_X};
+use super::town_layout::{ITownLayout, ITownLayoutMarker, TownLayout};
+use super::{TownLayoutIndex, DEFENCE_CYCLE_SECONDS};
+use crate::game_mechanics::attributes::Attributes;
+use crate::models::{BuildingType, HoboAttributeType};
 use crate::shared_types::*;
+use std::collections::BTreeMap;
+
+/// Slowed down visitors keep at least this share of their base speed, in percent
+pub const MIN_SPEED_PERCENT: i32 = 10;
+/// How long a flower aura stays in bloom
+pub const BLOOM_SECONDS: i64 = 60;
+
+/// An effect on a visitor that only applies from a certain point in time onwards
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimedEffect {
+    pub attribute: HoboAttributeType,
+    pub strength: i32,
+    pub start: Timestamp,
+}
 
 /// Provides information about a hobo currently attacking
 pub trait IAttackingHobo {
@@ -15,16 +31,338 @@ pub trait IAttackingHobo {
     fn hurried(&self) -> bool;
     fn arrival(&self) -> Timestamp;
     fn released(&self) -> Option<Timestamp>;
+    /// The spot where an unhurried visitor rests, see `ITownLayout`
+    fn resting_spot(&self) -> usize;
     fn effects_strength(&self) -> i32;
+    /// Effects that change how the visitor walks through the town
+    fn timed_effects(&self) -> Vec<TimedEffect>;
 
     // PROVIDED
-    /// Returns the duration it takes the hobo to reach the resting place, after having reached the town.
-    fn time_until_resting(&self) -> Timestamp {
-        Self::s_time_until_resting(self.speed())
+    /// Sum of all effects on the given attribute which are active at time t
+    fn effect_at(&self, attribute: HoboAttributeType, t: Timestamp) -> i32 {
+        self.timed_effects()
+            .iter()
+            .filter(|e| e.attribute == attribute && e.start <= t)
+            .fold(0, |acc, e| acc + e.strength)
+    }
+    /// Walking speed in tiles per second at time t
+    fn speed_at(&self, t: Timestamp) -> f32 {
+        let percent = 100 + self.effect_at(HoboAttributeType::Speed, t);
+        self.speed() * percent.max(MIN_SPEED_PERCENT) as f32 / 100.0
+    }
+    /// Points in time after start when the walking speed changes, sorted
+    fn speed_changes_after(&self, start: Timestamp) -> Vec<Timestamp> {
+        let mut changes: Vec<Timestamp> = self
+            .timed_effects()
+            .iter()
+            .filter(|e| e.attribute == HoboAttributeType::Speed && e.start > start)
+            .map(|e| e.start)
+            .collect();
+        changes.sort();
+        changes
     }
-    fn s_time_until_resting(speed: f32) -> Timestamp {
-        let distance_until_resting = TOWN_X - TOWN_RESTING_X;
-        Timestamp::from_float_seconds(distance_until_resting as f32 / speed)
+    /// The time it takes to walk the given number of tiles, when starting at the given point in time
+    fn walking_time(&self, start: Timestamp, distance: f32) -> Timestamp {
+        let mut t = start;
+        let mut left = distance;
+        for change in self.speed_changes_after(start) {
+            let reachable = (change - t).seconds_float() * self.speed_at(t);
+            if reachable >= left {
+                break;
+            }
+            left -= reachable;
+            t = change;
+        }
+        t + Timestamp::from_float_seconds(left / self.speed_at(t))
+    }
+    /// Number of tiles walked between start and now, assuming the visitor did not stop
+    fn walked_distance(&self, start: Timestamp, now: Timestamp) -> f32 {
+        if now <= start {
+            return 0.0;
+        }
+        let mut t = start;
+        let mut distance = 0.0;
+        for change in self.speed_changes_after(start) {
+            if change >= now {
+                break;
+            }
+            distance += (change - t).seconds_float() * self.speed_at(t);
+            t = change;
+        }
+        distance + (now - t).seconds_float() * self.speed_at(t)
+    }
+}
+
+/// A defensive building that hits visitors a limited number of times per cycle.
+///
+/// Unlike an aura, which affects each visitor once, a tower hits once when a visitor enters its range
+/// and then once more for every full interval the visitor spends inside it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tower {
+    pub attack_power: i32,
+    pub attacks_per_cycle: u32,
+}
+
+impl Tower {
+    pub fn new(attack_power: i32, attacks_per_cycle: u32) -> Self {
+        Tower {
+            attack_power,
+            attacks_per_cycle,
+        }
+    }
+    /// Time between two hits of the tower
+    pub fn hit_interval(&self) -> Timestamp {
+        Timestamp::from_us(DEFENCE_CYCLE_SECONDS * 1_000_000 / self.attacks_per_cycle.max(1) as i64)
+    }
+    /// Number of hits on a visitor that has been in range for the given duration
+    pub fn hits(&self, exposure: Timestamp) -> u32 {
+        if self.attacks_per_cycle == 0 {
+            return 0;
+        }
+        (exposure.micros() / self.hit_interval().micros()) as u32 + 1
+    }
+}
+
+/// The predicted outcome of a visit for a single visitor
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VisitorForecast {
+    pub hp_left: u32,
+    /// When the visitor will have left the town.
+    /// None for resting visitors that will not be released before they are satisfied.
+    pub exit_time: Option<Timestamp>,
+}
+
+impl VisitorForecast {
+    pub fn satisfied(&self) -> bool {
+        self.hp_left == 0
+    }
+}
+
+/// A building that affects passing visitors, either with an aura or as a tower
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DefendingBuilding {
+    pub id: i64,
+    /// Upper left tile of the building
+    pub position: TownLayoutIndex,
+    /// Width and height in tiles, the range is measured from the closest tile
+    pub size: (usize, usize),
+    pub range: f32,
+    pub attack_power: i32,
+    /// Only set for towers, auras affect each visitor once
+    pub attacks_per_cycle: Option<i32>,
+    pub built: Timestamp,
+}
+
+impl DefendingBuilding {
+    /// A building that does not exist (yet), with the attributes of its type.
+    /// Returns None for building types which do not affect visitors.
+    pub fn planned(
+        id: i64,
+        building_type: BuildingType,
+        position: TownLayoutIndex,
+        built: Timestamp,
+    ) -> Option<Self> {
+        Some(DefendingBuilding {
+            id,
+            position,
+            size: building_type.size(),
+            range: building_type.range()?,
+            attack_power: building_type.attack_power()? as i32,
+            attacks_per_cycle: building_type.attacks_per_cycle().map(|n| n as i32),
+            built,
+        })
+    }
+    fn in_range(&self, index: &TownLayoutIndex, time: Timestamp) -> bool {
+        let dx = axis_distance(self.position.0, self.size.0, index.0) as f32;
+        let dy = axis_distance(self.position.1, self.size.1, index.1) as f32;
+        time >= self.built && dx * dx + dy * dy <= self.range * self.range
+    }
+    /// Returns None for buildings which do not affect visitors
+    #[cfg(feature = "sql_db")]
+    pub fn from_db(b: &crate::models::Building) -> Option<Self> {
+        Some(DefendingBuilding {
+            id: b.id,
+            position: (b.x as usize, b.y as usize),
+            size: b.building_type.size(),
+            range: b.building_range?,
+            attack_power: b.attack_power?,
+            attacks_per_cycle: b.attacks_per_cycle,
+            built: b.creation.into(),
+        })
+    }
+}
+
+/// A flower aura in bloom is stronger for a limited time, see `AbilityType::Bloom`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AuraBloom {
+    pub start: Timestamp,
+    pub strength: i32,
+}
+
+impl AuraBloom {
+    pub fn active_at(&self, t: Timestamp) -> bool {
+        self.start <= t && t < self.start + Timestamp::from_seconds(BLOOM_SECONDS)
+    }
+    /// Added to the strength of an aura at time t.
+    /// Overlapping blooms on the same aura do not add up, only the strongest counts.
+    pub fn boost_at<'a>(blooms: impl IntoIterator<Item = &'a AuraBloom>, t: Timestamp) -> i32 {
+        blooms
+            .into_iter()
+            .filter(|b| b.active_at(t))
+            .map(|b| b.strength)
+            .max()
+            .unwrap_or(0)
+    }
+    #[cfg(feature = "sql_db")]
+    pub fn from_db(b: &crate::models::Bloom) -> Self {
+        AuraBloom {
+            start: b.start_time.into(),
+            strength: b.strength,
+        }
+    }
+}
+
+/// Distance along one axis from a coordinate to a building that starts at `start` and spans `len` tiles
+fn axis_distance(start: usize, len: usize, i: usize) -> usize {
+    let end = start + len.max(1) - 1;
+    if i < start {
+        start - i
+    } else if i > end {
+        i - end
+    } else {
+        0
+    }
+}
+
+/// Town information for hp computations, as plain data.
+/// Can be extended with planned buildings to predict visits.
+#[derive(Clone, Debug)]
+pub struct DefendingTown {
+    pub layout: TownLayout,
+    pub buildings: Vec<DefendingBuilding>,
+    /// Blooms on the auras of the buildings, by building id
+    pub blooms: Vec<(i64, AuraBloom)>,
+}
+
+impl DefendingTown {
+    fn bloom_at(&self, building: i64, time: Timestamp) -> i32 {
+        let blooms = self
+            .blooms
+            .iter()
+            .filter(|(id, _)| *id == building)
+            .map(|(_, bloom)| bloom);
+        AuraBloom::boost_at(blooms, time)
+    }
+}
+
+impl ITownLayoutMarker for DefendingTown {
+    fn town_layout(&self) -> TownLayout {
+        self.layout
+    }
+}
+impl IDefendingTown for DefendingTown {
+    type AuraId = i64;
+    fn auras_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, i32)> {
+        self.buildings
+            .iter()
+            .filter(|b| b.attacks_per_cycle.is_none())
+            .filter(|b| b.in_range(index, time))
+            .map(|b| (b.id, b.attack_power + self.bloom_at(b.id, time)))
+            .collect()
+    }
+    fn towers_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, Tower)> {
+        self.buildings
+            .iter()
+            .filter(|b| b.in_range(index, time))
+            .filter_map(|b| {
+                b.attacks_per_cycle
+                    .map(|apc| (b.id, Tower::new(b.attack_power, apc as u32)))
+            })
+            .collect()
+    }
+}
+
+/// A visitor of an attack, as stored in the database
+#[cfg(feature = "sql_db")]
+pub struct AttackingHobo<'a> {
+    pub hobo: &'a crate::models::Hobo,
+    pub attack_to_hobo: &'a crate::models::AttackToHobo,
+    pub effects: &'a [crate::models::Effect],
+    pub attack: &'a crate::models::Attack,
+}
+
+#[cfg(feature = "sql_db")]
+impl<'a> IAttackingHobo for AttackingHobo<'a> {
+    fn max_hp(&self) -> u32 {
+        self.hobo.hp as u32
+    }
+    fn speed(&self) -> f32 {
+        self.hobo.speed
+    }
+    fn hurried(&self) -> bool {
+        self.hobo.hurried
+    }
+    fn arrival(&self) -> Timestamp {
+        self.attack.arrival.into()
+    }
+    fn released(&self) -> Option<Timestamp> {
+        self.attack_to_hobo.released.map(|t| t.into())
+    }
+    fn resting_spot(&self) -> usize {
+        self.attack_to_hobo.resting_spot as usize
+    }
+    fn effects_strength(&self) -> i32 {
+        self.effects
+            .iter()
+            .filter(|e| e.attribute == crate::models::HoboAttributeType::Health)
+            .filter(|e| e.strength.is_some())
+            .fold(0, |acc, e| acc + e.strength.unwrap() as i64) as i32
+    }
+    fn timed_effects(&self) -> Vec<TimedEffect> {
+        self.effects
+            .iter()
+            .filter(|e| e.attribute != crate::models::HoboAttributeType::Health)
+            .filter_map(|e| {
+                Some(TimedEffect {
+                    attribute: e.attribute,
+                    strength: e.strength?,
+                    start: e.start_time.into(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// A visitor with a known (or assumed) time of release from the resting place
+struct ReleasedHobo<'a, HOBO: IAttackingHobo> {
+    hobo: &'a HOBO,
+    released: Timestamp,
+}
+
+impl<'a, HOBO: IAttackingHobo> IAttackingHobo for ReleasedHobo<'a, HOBO> {
+    fn max_hp(&self) -> u32 {
+        self.hobo.max_hp()
+    }
+    fn speed(&self) -> f32 {
+        self.hobo.speed()
+    }
+    fn hurried(&self) -> bool {
+        self.hobo.hurried()
+    }
+    fn arrival(&self) -> Timestamp {
+        self.hobo.arrival()
+    }
+    fn released(&self) -> Option<Timestamp> {
+        Some(self.released)
+    }
+    fn resting_spot(&self) -> usize {
+        self.hobo.resting_spot()
+    }
+    fn effects_strength(&self) -> i32 {
+        self.hobo.effects_strength()
+    }
+    fn timed_effects(&self) -> Vec<TimedEffect> {
+        self.hobo.timed_effects()
     }
 }
 
@@ -33,6 +371,7 @@ pub trait IDefendingTown: ITownLayout {
     // TO IMPLEMENT
     type AuraId: Ord + PartialEq;
     fn auras_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, i32)>;
+    fn towers_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, Tower)>;
 
     // PROVIDED
     fn hp_left<HOBO: IAttackingHobo>(&self, attacker: &HOBO, now: Timestamp) -> u32 {
@@ -41,24 +380,58 @@ pub trait IDefendingTown: ITownLayout {
             .saturating_sub(self.total_damage(attacker, now) as u32)
     }
     fn total_damage<HOBO: IAttackingHobo>(&self, attacker: &HOBO, now: Timestamp) -> i32 {
-        self.aura_damage(attacker, now) + attacker.effects_strength()
+        self.aura_damage(attacker, now)
+            + self.tower_damage(attacker, now)
+            + attacker.effects_strength()
     }
 
     fn hobo_left_town<HOBO: IAttackingHobo>(&self, attacker: &HOBO, now: Timestamp) -> bool {
+        self.time_of_leaving(attacker)
+            .map(|t| now >= t)
+            .unwrap_or(false)
+    }
+    /// The point in time when the hobo will have left the town.
+    /// Returns None for resting hobos which have not been released, yet.
+    fn time_of_leaving<HOBO: IAttackingHobo>(&self, attacker: &HOBO) -> Option<Timestamp> {
+        // +1 for swimming out of sight
         if attacker.hurried() {
-            let time_since_arrival = now - attacker.arrival();
-            // +1 for swimming out of sight
             let distance = self.path_straight_through().len() + 1;
-            time_since_arrival.seconds_float() >= distance as f32 / attacker.speed()
+            Some(attacker.walking_time(attacker.arrival(), distance as f32))
         } else {
-            if let Some(released) = self.left_rest_place(attacker) {
-                let time_since_release = now - released;
-                // +1 for swimming out of sight
-                let distance = self.path_from_rest_place().len() + 1;
-                time_since_release.seconds_float() >= distance as f32 / attacker.speed()
-            } else {
-                false
-            }
+            self.left_rest_place(attacker).map(|released| {
+                let distance = self.path_from_rest_place(attacker.resting_spot()).len() + 1;
+                attacker.walking_time(released, distance as f32)
+            })
+        }
+    }
+    /// The point in time when a non-hurried hobo reaches the resting place
+    fn started_resting<HOBO: IAttackingHobo>(&self, attacker: &HOBO) -> Timestamp {
+        let distance = self.path_to_rest_place(attacker.resting_spot()).len();
+        attacker.walking_time(attacker.arrival(), distance as f32)
+    }
+    /// The tile a visitor is on at the given time.
+    /// Returns None if the visitor has not arrived yet or has already left the town.
+    fn visitor_position<HOBO: IAttackingHobo>(
+        &self,
+        attacker: &HOBO,
+        t: Timestamp,
+    ) -> Option<&'static Self::Index> {
+        if t < attacker.arrival() {
+            return None;
+        }
+        let walked = |start: Timestamp| attacker.walked_distance(start, t) as usize;
+        if attacker.hurried() {
+            return self.path_straight_through().get(walked(attacker.arrival()));
+        }
+        let to_rest_place = self.path_to_rest_place(attacker.resting_spot());
+        if t < self.started_resting(attacker) {
+            return to_rest_place.get(walked(attacker.arrival()));
+        }
+        match self.left_rest_place(attacker) {
+            Some(released) if t >= released => self
+                .path_from_rest_place(attacker.resting_spot())
+                .get(walked(released)),
+            _ => to_rest_place.last(),
         }
     }
     fn aura_damage<HOBO: IAttackingHobo>(&self, attacker: &HOBO, now: Timestamp) -> i32 {
@@ -85,7 +458,7 @@ pub trait IDefendingTown: ITownLayout {
                 &tiles,
             ));
         } else {
-            let tiles = self.path_to_rest_place();
+            let tiles = self.path_to_rest_place(attacker.resting_spot());
             auras.append(&mut self.touched_auras_on_path(
                 attacker.arrival(),
                 now,
@@ -93,14 +466,18 @@ pub trait IDefendingTown: ITownLayout {
                 &tiles,
             ));
             if let Some(released) = self.left_rest_place(attacker) {
-                let tiles = self.path_from_rest_place();
+                let tiles = self.path_from_rest_place(attacker.resting_spot());
                 auras.append(&mut self.touched_auras_on_path(released, now, attacker, &tiles));
             }
         }
-        auras.sort();
-        auras.dedup();
+        Self::dedup_auras(&mut auras);
         auras
     }
+    /// Keeps only the strongest touch of each aura
+    fn dedup_auras(auras: &mut Vec<(Self::AuraId, i32)>) {
+        auras.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        auras.dedup_by(|a, b| a.0 == b.0);
+    }
     fn touched_auras_on_path<HOBO: IAttackingHobo>(
         &self,
         start: Timestamp,
@@ -109,25 +486,113 @@ pub trait IDefendingTown: ITownLayout {
         tiles: &[Self::Index],
     ) -> Vec<(Self::AuraId, i32)> {
         let mut out = vec![];
-        let mut t = start;
-        let t_per_tile = Timestamp::from_float_seconds(1.0 / attacker.speed());
-        for tile in tiles {
+        for (i, tile) in tiles.iter().enumerate() {
+            let t = attacker.walking_time(start, i as f32);
             if t > max_t {
                 break;
             }
             out.append(&mut self.auras_in_range(tile, t));
-            t = t + t_per_tile;
         }
-        out.sort();
-        out.dedup();
+        Self::dedup_auras(&mut out);
         out
     }
+    fn tower_damage<HOBO: IAttackingHobo>(&self, attacker: &HOBO, now: Timestamp) -> i32 {
+        self.tower_hits(attacker, now)
+            .iter()
+            .fold(0, |acc, (_, tower, hits)| {
+                acc + tower.attack_power * *hits as i32
+            })
+    }
+    /// All towers that have hit the visitor so far, together with the number of hits.
+    fn tower_hits<HOBO: IAttackingHobo>(
+        &self,
+        attacker: &HOBO,
+        now: Timestamp,
+    ) -> Vec<(Self::AuraId, Tower, u32)> {
+        self.tower_exposure(attacker, now)
+            .into_iter()
+            .map(|(id, tower, t)| (id, tower, tower.hits(t)))
+            .collect()
+    }
+    /// All towers that have had the visitor in range so far, together with the time spent in range.
+    ///
+    /// Only time spent moving along the lane counts, a resting visitor is out of reach for towers.
+    fn tower_exposure<HOBO: IAttackingHobo>(
+        &self,
+        attacker: &HOBO,
+        now: Timestamp,
+    ) -> Vec<(Self::AuraId, Tower, Timestamp)> {
+        let mut exposure = BTreeMap::new();
+        if attacker.hurried() {
+            let tiles = self.path_straight_through();
+            self.tower_exposure_on_path(attacker.arrival(), now, attacker, &tiles, &mut exposure);
+        } else {
+            let tiles = self.path_to_rest_place(attacker.resting_spot());
+            self.tower_exposure_on_path(attacker.arrival(), now, attacker, &tiles, &mut exposure);
+            if let Some(released) = self.left_rest_place(attacker) {
+                let tiles = self.path_from_rest_place(attacker.resting_spot());
+                self.tower_exposure_on_path(released, now, attacker, &tiles, &mut exposure);
+            }
+        }
+        exposure
+            .into_iter()
+            .map(|(id, (tower, t))| (id, tower, t))
+            .collect()
+    }
+    /// Adds up the time spent in range of each tower along the path, until max_t.
+    fn tower_exposure_on_path<HOBO: IAttackingHobo>(
+        &self,
+        start: Timestamp,
+        max_t: Timestamp,
+        attacker: &HOBO,
+        tiles: &[Self::Index],
+        exposure: &mut BTreeMap<Self::AuraId, (Tower, Timestamp)>,
+    ) {
+        for (i, tile) in tiles.iter().enumerate() {
+            let t = attacker.walking_time(start, i as f32);
+            if t > max_t {
+                break;
+            }
+            let next = attacker.walking_time(start, (i + 1) as f32);
+            let dt = if next > max_t { max_t - t } else { next - t };
+            for (id, tower) in self.towers_in_range(tile, t) {
+                let entry = exposure.entry(id).or_insert((tower, Timestamp::from_us(0)));
+                entry.1 = entry.1 + dt;
+            }
+        }
+    }
+    /// Predicts how a visit ends for a visitor, assuming the town does not change until then.
+    ///
+    /// Resting visitors that have not been released are assumed to stay until they are satisfied.
+    /// If they are already satisfied when they reach the resting place, they are released right away.
+    fn forecast<HOBO: IAttackingHobo>(&self, attacker: &HOBO) -> VisitorForecast {
+        if let Some(exit_time) = self.time_of_leaving(attacker) {
+            return VisitorForecast {
+                hp_left: self.hp_left(attacker, exit_time),
+                exit_time: Some(exit_time),
+            };
+        }
+        let resting = self.started_resting(attacker);
+        let hp_left = self.hp_left(attacker, resting);
+        if hp_left > 0 {
+            return VisitorForecast {
+                hp_left,
+                exit_time: None,
+            };
+        }
+        let released = ReleasedHobo {
+            hobo: attacker,
+            released: resting,
+        };
+        VisitorForecast {
+            hp_left: 0,
+            exit_time: self.time_of_leaving(&released),
+        }
+    }
     /// The timestamp when the resting place was left by a non-hurried hobo. May differ from hobo.released
     fn left_rest_place<HOBO: IAttackingHobo>(&self, attacker: &HOBO) -> Option<Timestamp> {
         attacker.released().map(|released| {
-            let f = self.path_to_rest_place().len() as f32 / attacker.speed();
-            let t = Timestamp::from_float_seconds(f);
-            let started_resting = attacker.arrival() + t;
+            let started_resting = self.started_resting(attacker);
             if released > started_resting {
                 released
             } else {
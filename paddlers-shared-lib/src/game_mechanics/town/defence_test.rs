@@ -1,6 +1,7 @@
 use super::defence::*;
 use super::town_layout::*;
 use super::*;
+use crate::models::{BuildingType, HoboAttributeType};
 use crate::shared_types::Timestamp;
 use std::collections::HashMap;
 
@@ -10,16 +11,26 @@ struct TestHobo {
     hurried: bool,
     arrival: Timestamp,
     released: Option<Timestamp>,
+    resting_spot: usize,
     effects_strength: i32,
+    effects: Vec<TimedEffect>,
 }
 struct TestTown {
     building_auras: HashMap<TownLayoutIndex, Vec<TestAura>>,
+    towers: HashMap<TownLayoutIndex, Vec<TestTower>>,
+    layout: TownLayout,
 }
 #[derive(Copy, Clone, Debug)]
 struct TestAura {
     id: usize,
     strength: i32,
 }
+#[derive(Copy, Clone, Debug)]
+struct TestTower {
+    id: usize,
+    tower: Tower,
+    built: Timestamp,
+}
 
 const Y: usize = TOWN_LANE_Y;
 
@@ -125,6 +136,428 @@ fn unhurried_hobo_released() {
     assert_eq!(hobo_hp_left, 97);
 }
 
+#[test]
+fn time_of_leaving() {
+    let town = TestTown::new();
+    let mut hobo = TestHobo::new();
+
+    let t = town
+        .time_of_leaving(&hobo)
+        .expect("hurried hobos always leave");
+    assert!(!town.hobo_left_town(&hobo, t - Timestamp::from_seconds(1)));
+    assert!(town.hobo_left_town(&hobo, t));
+
+    hobo.hurried = false;
+    assert!(town.time_of_leaving(&hobo).is_none());
+
+    hobo.released = Some(Timestamp::from_seconds(100));
+    let t = town.time_of_leaving(&hobo).expect("released hobos leave");
+    assert!(!town.hobo_left_town(&hobo, t - Timestamp::from_seconds(1)));
+    assert!(town.hobo_left_town(&hobo, t));
+}
+
+#[test]
+fn hurried_hobo_hit_by_tower() {
+    let hobo = TestHobo::new();
+    let mut town = TestTown::new();
+    // Hobo spends 6s in range, a hit every 2.5s
+    let tower = TestTower::new(2, 4);
+    town.add_tower(tower, &[(1, Y), (2, Y), (3, Y)]);
+
+    let hits = town.tower_hits(&hobo, Timestamp::from_seconds(100));
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].2, 3);
+
+    let dmg = town.tower_damage(&hobo, Timestamp::from_seconds(100));
+    assert_eq!(dmg, 6);
+
+    // Entering the range is enough for the first hit
+    let dmg = town.tower_damage(&hobo, Timestamp::from_seconds(11));
+    assert_eq!(dmg, 2);
+
+    let dmg = town.tower_damage(&hobo, Timestamp::from_seconds(9));
+    assert_eq!(dmg, 0);
+}
+
+#[test]
+fn tower_exposure_is_time_in_range() {
+    let hobo = TestHobo::new();
+    let mut town = TestTown::new();
+    town.add_tower(TestTower::new(2, 4), &[(1, Y), (2, Y), (3, Y)]);
+
+    assert!(town
+        .tower_exposure(&hobo, Timestamp::from_seconds(9))
+        .is_empty());
+    // The range is entered after 10s and left after 16s
+    for &(now, exposure, hits) in &[(11, 1, 1), (13, 3, 2), (15, 5, 3), (100, 6, 3)] {
+        let now = Timestamp::from_seconds(now);
+        let exposed = town.tower_exposure(&hobo, now);
+        assert_eq!(exposed.len(), 1);
+        assert_eq!(exposed[0].2, Timestamp::from_seconds(exposure));
+        assert_eq!(exposed[0].1.hits(exposed[0].2), hits);
+        assert_eq!(town.tower_hits(&hobo, now)[0].2, hits);
+    }
+}
+
+#[test]
+fn towers_and_auras_combined() {
+    let mut hobo = TestHobo::new();
+    hobo.effects_strength = 1;
+    let mut town = TestTown::new();
+    town.add_aura(TestAura::new(3), &[(5, Y), (6, Y)]);
+    town.add_tower(TestTower::new(2, 1), &[(5, Y), (6, Y)]);
+    town.add_tower(TestTower::new(1, 10), &[(1, Y)]);
+
+    let now = Timestamp::from_seconds(100);
+    // 3 from the aura, 2 from the slow tower, 1 + 2 from the fast tower, 1 from effects
+    assert_eq!(town.total_damage(&hobo, now), 9);
+    assert_eq!(town.hp_left(&hobo, now), 91);
+}
+
+#[test]
+fn resting_hobo_out_of_tower_reach() {
+    let mut hobo = TestHobo::new();
+    hobo.hurried = false;
+    let mut town = TestTown::new();
+    // 4s in range before resting, a hit every 2.5s
+    town.add_tower(TestTower::new(1, 4), &[(5, Y), (TOWN_RESTING_X, Y)]);
+
+    let dmg = town.tower_damage(&hobo, Timestamp::from_seconds(10));
+    assert_eq!(dmg, 2);
+    let dmg = town.tower_damage(&hobo, Timestamp::from_seconds(1000));
+    assert_eq!(dmg, 2);
+
+    hobo.released = Some(Timestamp::from_seconds(1000));
+    town.add_tower(TestTower::new(5, 1), &[(3, Y)]);
+    let dmg = town.tower_damage(&hobo, Timestamp::from_seconds(1001));
+    assert_eq!(dmg, 7);
+}
+
+#[test]
+fn tower_built_after_passage() {
+    let hobo = TestHobo::new();
+    let mut town = TestTown::new();
+    let mut tower = TestTower::new(3, 2);
+    tower.built = Timestamp::from_seconds(13);
+    town.add_tower(tower, &[(1, Y), (2, Y), (3, Y)]);
+
+    // Only the last tile is reached after the tower has been built
+    let hits = town.tower_hits(&hobo, Timestamp::from_seconds(100));
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].2, 1);
+}
+
+#[test]
+fn layout_paths_are_connected() {
+    for layout in &[
+        TownLayout::Basic,
+        TownLayout::Bend,
+        TownLayout::Meander,
+        TownLayout::Fork,
+    ] {
+        for branch in 0..layout.branches() {
+            let path = layout.branch(branch);
+            assert_eq!(path[0].0, TOWN_X - 1);
+            assert_eq!(path[path.len() - 1].0, 0);
+            for step in path.windows(2) {
+                let (a, b) = (step[0], step[1]);
+                let dx = a.0 as i32 - b.0 as i32;
+                let dy = (a.1 as i32 - b.1 as i32).abs();
+                assert!(
+                    (dx == 1 && dy == 0) || (dx == 0 && dy == 1),
+                    "Invalid step from {:?} to {:?}",
+                    a,
+                    b
+                );
+            }
+        }
+        assert_eq!(layout.path_straight_through(), layout.branch(0));
+        assert_eq!(layout.rest_place(0).0, TOWN_RESTING_X);
+        let mut rest_places = vec![];
+        for spot in 0..layout.resting_spots() {
+            let path = [
+                layout.path_to_rest_place(spot),
+                layout.path_from_rest_place(spot),
+            ]
+            .concat();
+            assert!(
+                (0..layout.branches()).any(|branch| layout.branch(branch) == &path[..]),
+                "{:?} spot {} is not on a branch",
+                layout,
+                spot
+            );
+            assert_eq!(
+                *layout.path_to_rest_place(spot).last().unwrap(),
+                layout.rest_place(spot)
+            );
+            assert!(!rest_places.contains(&layout.rest_place(spot)));
+            rest_places.push(layout.rest_place(spot));
+        }
+    }
+}
+
+#[test]
+fn fork_has_a_resting_place_on_each_branch() {
+    let layout = TownLayout::Fork;
+    assert_eq!(layout.branches(), 2);
+    let side = layout.branch(1);
+    assert!(side
+        .iter()
+        .any(|tile| !layout.path_straight_through().contains(tile)));
+    // The first two visitors rest on different branches
+    assert_eq!(layout.rest_place(0), (TOWN_RESTING_X, Y));
+    assert_eq!(layout.rest_place(1).0, TOWN_RESTING_X);
+    assert!(!layout
+        .path_straight_through()
+        .contains(&layout.rest_place(1)));
+    assert_eq!(layout.path_from_rest_place(1), &side[7..]);
+    // Both branches are part of the lane
+    let lane = layout.lane();
+    assert_eq!(lane.len(), 9 + 7);
+    for tile in side {
+        assert!(lane.contains(tile));
+    }
+}
+
+#[test]
+fn visitor_rests_on_side_branch_of_fork() {
+    let mut first = TestHobo::new();
+    first.hurried = false;
+    let mut second = TestHobo::new();
+    second.hurried = false;
+    second.resting_spot = 1;
+    let mut town = TestTown::new();
+    town.layout = TownLayout::Fork;
+    // Only reaches the side branch
+    town.add_aura(TestAura::new(1), &[(4, Y - 2)]);
+    let now = Timestamp::from_seconds(100);
+    assert_eq!(town.aura_damage(&first, now), 0);
+    assert_eq!(town.aura_damage(&second, now), 1);
+    assert_eq!(
+        town.visitor_position(&second, now),
+        Some(&(TOWN_RESTING_X, Y - 2))
+    );
+}
+
+#[test]
+fn aura_on_bent_lane() {
+    let hobo = TestHobo::new();
+    let mut town = TestTown::new();
+    town.add_aura(TestAura::new(3), &[(7, Y - 1)]);
+    let now = Timestamp::from_seconds(100);
+
+    assert_eq!(town.aura_damage(&hobo, now), 0);
+    town.layout = TownLayout::Bend;
+    assert_eq!(town.aura_damage(&hobo, now), 3);
+
+    // Longer path, longer stay
+    let basic = TownLayout::Basic.time_until_resting(hobo.speed, 0);
+    let bend = town.time_until_resting(hobo.speed, 0);
+    assert!(bend > basic);
+}
+
+#[test]
+fn forecast_hurried_hobo() {
+    let mut hobo = TestHobo::new();
+    hobo.max_hp = 10;
+    let mut town = TestTown::new();
+    town.add_aura(TestAura::new(3), &[(1, Y), (2, Y), (3, Y)]);
+
+    let forecast = town.forecast(&hobo);
+    assert_eq!(forecast.hp_left, 7);
+    assert!(!forecast.satisfied());
+    // +1 for swimming out of sight
+    let t = Timestamp::from_float_seconds(10.0 / hobo.speed);
+    assert_eq!(forecast.exit_time, Some(t));
+}
+
+#[test]
+fn forecast_resting_hobo() {
+    let mut hobo = TestHobo::new();
+    hobo.max_hp = 3;
+    hobo.hurried = false;
+    let mut town = TestTown::new();
+    town.add_aura(TestAura::new(3), &[(7, Y), (8, Y)]);
+
+    // Satisfied before reaching the resting place, therefore released right away
+    let forecast = town.forecast(&hobo);
+    assert!(forecast.satisfied());
+    let swim_out = (town.path_from_rest_place(0).len() + 1) as f32 / hobo.speed;
+    let exit = town.time_until_resting(hobo.speed, 0) + Timestamp::from_float_seconds(swim_out);
+    assert_eq!(forecast.exit_time, Some(exit));
+
+    hobo.max_hp = 4;
+    let forecast = town.forecast(&hobo);
+    assert_eq!(forecast.hp_left, 1);
+    assert_eq!(forecast.exit_time, None);
+}
+
+#[test]
+fn forecast_with_planned_buildings() {
+    let hobo = TestHobo::new();
+    let mut town = DefendingTown {
+        layout: TownLayout::Basic,
+        buildings: vec![],
+        blooms: vec![],
+    };
+    let t0 = Timestamp::from_seconds(0);
+    assert!(DefendingBuilding::planned(0, BuildingType::Tree, (2, Y - 1), t0).is_none());
+
+    let flowers = DefendingBuilding::planned(1, BuildingType::BlueFlowers, (2, Y - 1), t0);
+    town.buildings.push(flowers.unwrap());
+    assert_eq!(town.forecast(&hobo).hp_left, hobo.max_hp - 1);
+
+    // Built after the visitor has passed
+    let late = Timestamp::from_seconds(100);
+    let flowers = DefendingBuilding::planned(2, BuildingType::RedFlowers, (2, Y - 1), late);
+    town.buildings.push(flowers.unwrap());
+    assert_eq!(town.forecast(&hobo).hp_left, hobo.max_hp - 1);
+}
+
+#[test]
+fn slowed_hobo_stays_longer() {
+    let mut hobo = TestHobo::new();
+    let town = TestTown::new();
+    let unaffected = town.time_of_leaving(&hobo).unwrap();
+    assert_eq!(unaffected, Timestamp::from_seconds(20));
+
+    // Half speed after 4 of 10 tiles
+    hobo.effects.push(TimedEffect {
+        attribute: HoboAttributeType::Speed,
+        strength: -50,
+        start: Timestamp::from_seconds(8),
+    });
+    let slowed = town.time_of_leaving(&hobo).unwrap();
+    assert_eq!(slowed, Timestamp::from_seconds(32));
+    assert!(!town.hobo_left_town(&hobo, Timestamp::from_seconds(30)));
+}
+
+#[test]
+fn slowed_hobo_exposed_longer_to_tower() {
+    let mut hobo = TestHobo::new();
+    let mut town = TestTown::new();
+    let tower = TestTower::new(1, 4);
+    town.add_tower(tower, &[(3, Y)]);
+    let now = Timestamp::from_seconds(100);
+    assert_eq!(town.tower_damage(&hobo, now), 1);
+
+    hobo.effects.push(TimedEffect {
+        attribute: HoboAttributeType::Speed,
+        strength: -50,
+        start: Timestamp::from_seconds(0),
+    });
+    // Four seconds in range instead of two
+    assert_eq!(town.tower_damage(&hobo, now), 2);
+}
+
+#[test]
+fn bloom_strengthens_aura_while_active() {
+    let hobo = TestHobo::new();
+    let t0 = Timestamp::from_seconds(0);
+    let flowers = DefendingBuilding::planned(1, BuildingType::BlueFlowers, (2, Y - 1), t0).unwrap();
+    let mut town = DefendingTown {
+        layout: TownLayout::Basic,
+        buildings: vec![flowers],
+        blooms: vec![],
+    };
+    let now = Timestamp::from_seconds(100);
+    let ap = flowers.attack_power;
+    assert_eq!(town.aura_damage(&hobo, now), ap);
+
+    // Faded before the visitor has arrived
+    let faded = Timestamp::from_seconds(-BLOOM_SECONDS - 1);
+    town.blooms.push((flowers.id, bloom(faded, 1)));
+    assert_eq!(town.aura_damage(&hobo, now), ap);
+
+    // Blooms on the same aura do not add up
+    town.blooms.push((flowers.id, bloom(t0, 1)));
+    town.blooms.push((flowers.id, bloom(t0, 2)));
+    assert_eq!(town.aura_damage(&hobo, now), ap + 2);
+    // Each aura still only counts once
+    assert_eq!(town.touched_auras(&hobo, now).len(), 1);
+
+    // Other auras are not affected
+    town.blooms.clear();
+    town.blooms.push((flowers.id + 1, bloom(t0, 1)));
+    assert_eq!(town.aura_damage(&hobo, now), ap);
+}
+
+#[test]
+fn bloom_after_passage_changes_nothing() {
+    let hobo = TestHobo::new();
+    let t0 = Timestamp::from_seconds(0);
+    let flowers = DefendingBuilding::planned(1, BuildingType::BlueFlowers, (6, Y - 1), t0).unwrap();
+    let late = hobo.walking_time(t0, 10.0);
+    let town = DefendingTown {
+        layout: TownLayout::Basic,
+        buildings: vec![flowers],
+        blooms: vec![(flowers.id, bloom(late, 1))],
+    };
+    let now = Timestamp::from_seconds(100);
+    assert_eq!(town.aura_damage(&hobo, now), flowers.attack_power);
+}
+
+fn bloom(start: Timestamp, strength: i32) -> AuraBloom {
+    AuraBloom { start, strength }
+}
+
+#[test]
+fn visitor_position_on_path() {
+    let mut hobo = TestHobo::new();
+    let town = TestTown::new();
+    assert_eq!(
+        town.visitor_position(&hobo, Timestamp::from_seconds(1)),
+        Some(&(8, Y))
+    );
+    assert_eq!(
+        town.visitor_position(&hobo, Timestamp::from_seconds(5)),
+        Some(&(6, Y))
+    );
+    assert_eq!(
+        town.visitor_position(&hobo, Timestamp::from_seconds(19)),
+        None
+    );
+
+    hobo.hurried = false;
+    let resting = town.visitor_position(&hobo, Timestamp::from_seconds(50));
+    assert_eq!(resting, Some(&TownLayout::Basic.rest_place(0)));
+    hobo.released = Some(Timestamp::from_seconds(60));
+    assert_eq!(
+        town.visitor_position(&hobo, Timestamp::from_seconds(61)),
+        Some(&(3, Y))
+    );
+}
+
+#[test]
+fn visitors_rest_on_their_own_spot() {
+    let mut first = TestHobo::new();
+    first.hurried = false;
+    let mut second = TestHobo::new();
+    second.hurried = false;
+    second.resting_spot = 1;
+    let mut town = TestTown::new();
+    town.add_aura(TestAura::new(2), &[(TOWN_RESTING_X, Y)]);
+    let now = Timestamp::from_seconds(100);
+
+    assert_eq!(
+        town.visitor_position(&first, now),
+        Some(&(TOWN_RESTING_X, Y))
+    );
+    assert_eq!(
+        town.visitor_position(&second, now),
+        Some(&(TOWN_RESTING_X + 1, Y))
+    );
+    // The second spot is in front of the first, outside of the aura
+    assert_eq!(town.aura_damage(&first, now), 2);
+    assert_eq!(town.aura_damage(&second, now), 0);
+    assert!(town.started_resting(&second) < town.started_resting(&first));
+
+    // After its release, the visitor passes the other spot
+    second.released = Some(now);
+    let later = Timestamp::from_seconds(110);
+    assert_eq!(town.aura_damage(&second, later), 2);
+}
+
 impl TestHobo {
     fn new() -> Self {
         TestHobo {
@@ -133,7 +566,9 @@ impl TestHobo {
             hurried: true,
             arrival: Timestamp::from_seconds(0),
             released: None,
+            resting_spot: 0,
             effects_strength: 0,
+            effects: vec![],
         }
     }
 }
@@ -154,12 +589,20 @@ impl IAttackingHobo for TestHobo {
     fn released(&self) -> Option<Timestamp> {
         self.released
     }
+    fn resting_spot(&self) -> usize {
+        self.resting_spot
+    }
     fn effects_strength(&self) -> i32 {
         self.effects_strength
     }
+    fn timed_effects(&self) -> Vec<TimedEffect> {
+        self.effects.clone()
+    }
 }
 impl ITownLayoutMarker for TestTown {
-    const LAYOUT: TownLayout = TownLayout::Basic;
+    fn town_layout(&self) -> TownLayout {
+        self.layout
+    }
 }
 impl IDefendingTown for TestTown {
     type AuraId = usize;
@@ -170,6 +613,17 @@ impl IDefendingTown for TestTown {
             Vec::new()
         }
     }
+    fn towers_in_range(&self, index: &Self::Index, time: Timestamp) -> Vec<(Self::AuraId, Tower)> {
+        if let Some(towers) = self.towers.get(index) {
+            towers
+                .iter()
+                .filter(|t| t.built <= time)
+                .map(|t| (t.id, t.tower))
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
 }
 impl TestAura {
     pub fn new(strength: i32) -> Self {
@@ -181,10 +635,28 @@ impl TestAura {
         TestAura { id, strength }
     }
 }
+impl TestTower {
+    pub fn new(attack_power: i32, attacks_per_cycle: u32) -> Self {
+        // Share the id space with auras
+        let id = TestAura::new(0).id;
+        TestTower {
+            id,
+            tower: Tower::new(attack_power, attacks_per_cycle),
+            built: Timestamp::from_seconds(0),
+        }
+    }
+}
 impl TestTown {
     pub fn new() -> Self {
         TestTown {
             building_auras: HashMap::new(),
+            towers: HashMap::new(),
+            layout: TownLayout::Basic,
+        }
+    }
+    fn add_tower(&mut self, tower: TestTower, idx: &[TownLayoutIndex]) {
+        for i in idx {
+            self.towers.entry(*i).or_insert_with(Vec::new).push(tower);
         }
     }
     fn add_aura(&mut self, aura: TestAura, idx: &[TownLayoutIndex]) {
@@ -196,3 +668,21 @@ impl TestTown {
         }
     }
 }
+
+#[test]
+fn aura_range_measured_from_footprint() {
+    let t0 = Timestamp::from_seconds(0);
+    let town = |b: &DefendingBuilding| DefendingTown {
+        layout: TownLayout::Basic,
+        buildings: vec![*b],
+        blooms: vec![],
+    };
+    let mut flowers = DefendingBuilding::planned(1, BuildingType::RedFlowers, (2, 0), t0).unwrap();
+    assert!(town(&flowers).auras_in_range(&(4, 1), t0).is_empty());
+
+    flowers.size = (2, 2);
+    assert_eq!(town(&flowers).auras_in_range(&(4, 1), t0).len(), 1);
+    assert_eq!(town(&flowers).auras_in_range(&(1, 0), t0).len(), 1);
+    assert!(town(&flowers).auras_in_range(&(5, 1), t0).is_empty());
+    assert!(town(&flowers).auras_in_range(&(4, 3), t0).is_empty());
+}
@@ -0,0 +1,164 @@
+//! Validation of building placement, shared between game-master and frontend.
+
+use super::*;
+use crate::game_mechanics::attributes::Attributes;
+
+/// Buildings must stay above this row, the bottom row of the town is kept free.
+pub const TOWN_BUILDABLE_Y: usize = TOWN_Y - 1;
+
+/// A unit in the town, as far as it matters for placing buildings
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnitPosition {
+    /// The tile the unit stands on or starts walking from
+    pub tile: TileIndex,
+    /// Tiles where the unit changes direction or stops, in the order they are reached.
+    /// Consecutive tiles must lie on a straight line. Empty for stationary units.
+    pub path: Vec<TileIndex>,
+}
+
+/// Reasons why a building cannot be placed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlacementError {
+    /// Part of the building would be outside the town
+    OutOfBounds,
+    /// Part of the building would be in the reserved bottom row
+    ReservedRow,
+    /// The visitor lane goes through the footprint
+    Lane,
+    /// Another building already covers part of the footprint
+    Occupied,
+    /// A unit stands on the footprint
+    UnitBlocks,
+    /// A unit is walking through the footprint
+    WalkingUnitBlocks,
+    /// Units are inside the building or on their way to it
+    BuildingInUse,
+}
+
+impl UnitPosition {
+    pub fn stationary(tile: TileIndex) -> Self {
+        UnitPosition { tile, path: vec![] }
+    }
+    pub fn walking(tile: TileIndex, path: Vec<TileIndex>) -> Self {
+        UnitPosition { tile, path }
+    }
+    fn visits(&self, tile: TileIndex) -> bool {
+        self.tile == tile || self.path.contains(&tile)
+    }
+    fn walks_through(&self, tile: TileIndex) -> bool {
+        let mut start = self.tile;
+        for end in &self.path {
+            if is_between(tile.0, start.0, end.0) && is_between(tile.1, start.1, end.1) {
+                return true;
+            }
+            start = *end;
+        }
+        false
+    }
+}
+
+/// Checks if a building of the given type can be anchored at the given tile.
+/// Both, game-master and frontend, should call this before placing a building.
+pub fn validate_building_placement(
+    map: &TownMap,
+    layout: TownLayout,
+    units: &[UnitPosition],
+    bt: BuildingType,
+    anchor: TileIndex,
+) -> Result<(), PlacementError> {
+    let (w, h) = bt.size();
+    if anchor.0 + w > TOWN_X || anchor.1 + h > TOWN_Y {
+        return Err(PlacementError::OutOfBounds);
+    }
+    if anchor.1 + h > TOWN_BUILDABLE_Y {
+        return Err(PlacementError::ReservedRow);
+    }
+    let footprint = bt.footprint(anchor);
+    let lane = layout.lane();
+    for tile in &footprint {
+        if lane.contains(tile) {
+            return Err(PlacementError::Lane);
+        }
+        if !map[*tile].is_buildable() {
+            return Err(PlacementError::Occupied);
+        }
+    }
+    for unit in units {
+        if footprint.contains(&unit.tile) {
+            return Err(PlacementError::UnitBlocks);
+        }
+    }
+    for unit in units {
+        if footprint.iter().any(|tile| unit.walks_through(*tile)) {
+            return Err(PlacementError::WalkingUnitBlocks);
+        }
+    }
+    Ok(())
+}
+
+/// Checks if the building of the given type anchored at `from` can be moved to be anchored at `to`.
+/// The building does not block itself, hence it can be moved by less than its own size.
+/// Buildings can only be moved while no unit is inside or heading to it.
+pub fn validate_building_relocation<I: Eq + std::hash::Hash + Clone + Copy + std::fmt::Debug>(
+    map: &TownMap,
+    state: &TownState<I>,
+    layout: TownLayout,
+    units: &[UnitPosition],
+    bt: BuildingType,
+    from: TileIndex,
+    to: TileIndex,
+) -> Result<(), PlacementError> {
+    let occupied = state
+        .get(&from)
+        .map_or(false, |s| s.building_state.entity_count > 0);
+    let old_footprint = bt.footprint(from);
+    let visited = units
+        .iter()
+        .any(|unit| old_footprint.iter().any(|tile| unit.visits(*tile)));
+    if occupied || visited {
+        return Err(PlacementError::BuildingInUse);
+    }
+    let mut map = map.clone();
+    map.remove_building(bt, from);
+    validate_building_placement(&map, layout, units, bt, to)
+}
+
+/// Finds the anchor closest to `preferred` where a building of the given type can be placed
+pub fn find_building_space(
+    map: &TownMap,
+    layout: TownLayout,
+    units: &[UnitPosition],
+    bt: BuildingType,
+    preferred: TileIndex,
+) -> Option<TileIndex> {
+    let mut anchors: Vec<TileIndex> = (0..TOWN_X)
+        .flat_map(|x| (0..TOWN_Y).map(move |y| (x, y)))
+        .collect();
+    anchors.sort_by_key(|anchor| {
+        let dx = anchor.0 as i32 - preferred.0 as i32;
+        let dy = anchor.1 as i32 - preferred.1 as i32;
+        dx * dx + dy * dy
+    });
+    anchors
+        .into_iter()
+        .find(|anchor| validate_building_placement(map, layout, units, bt, *anchor).is_ok())
+}
+
+fn is_between(x: usize, a: usize, b: usize) -> bool {
+    a.min(b) <= x && x <= a.max(b)
+}
+
+impl std::error::Error for PlacementError {}
+impl std::fmt::Display for PlacementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            PlacementError::OutOfBounds => write!(f, "Outside of town"),
+            PlacementError::ReservedRow => write!(f, "Cannot build here"),
+            PlacementError::Lane => write!(f, "Cannot build on the lane"),
+            PlacementError::Occupied => write!(f, "Space occupied"),
+            PlacementError::UnitBlocks => write!(f, "Unit blocks space"),
+            PlacementError::WalkingUnitBlocks => write!(f, "Walking unit blocks space"),
+            PlacementError::BuildingInUse => write!(f, "Building is in use"),
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use super::*;
+
+fn empty_map() -> TownMap {
+    TownMap::new(TownLayout::Basic)
+}
+
+#[test]
+fn buildings_stay_inside_town() {
+    let map = empty_map();
+    assert_eq!(
+        validate_building_placement(
+            &map,
+            TownLayout::Basic,
+            &[],
+            BuildingType::SawMill,
+            (TOWN_X - 1, 0)
+        ),
+        Err(PlacementError::OutOfBounds)
+    );
+    assert_eq!(
+        validate_building_placement(
+            &map,
+            TownLayout::Basic,
+            &[],
+            BuildingType::Tree,
+            (0, TOWN_Y - 1)
+        ),
+        Err(PlacementError::ReservedRow)
+    );
+    assert_eq!(
+        validate_building_placement(
+            &map,
+            TownLayout::Basic,
+            &[],
+            BuildingType::Temple,
+            (0, TOWN_Y - 2)
+        ),
+        Err(PlacementError::ReservedRow)
+    );
+    assert!(validate_building_placement(
+        &map,
+        TownLayout::Basic,
+        &[],
+        BuildingType::Temple,
+        (0, TOWN_Y - 3)
+    )
+    .is_ok());
+}
+
+#[test]
+fn buildings_avoid_lane_and_other_buildings() {
+    let mut map = empty_map();
+    assert_eq!(
+        validate_building_placement(
+            &map,
+            TownLayout::Basic,
+            &[],
+            BuildingType::Tree,
+            (0, TOWN_LANE_Y)
+        ),
+        Err(PlacementError::Lane)
+    );
+    assert_eq!(
+        validate_building_placement(
+            &map,
+            TownLayout::Basic,
+            &[],
+            BuildingType::Temple,
+            (0, TOWN_LANE_Y - 1)
+        ),
+        Err(PlacementError::Lane)
+    );
+    map.insert_building(BuildingType::SawMill, (1, 0)).unwrap();
+    assert_eq!(
+        validate_building_placement(&map, TownLayout::Basic, &[], BuildingType::Tree, (2, 0)),
+        Err(PlacementError::Occupied)
+    );
+    assert_eq!(
+        validate_building_placement(&map, TownLayout::Basic, &[], BuildingType::SawMill, (0, 0)),
+        Err(PlacementError::Occupied)
+    );
+    assert!(
+        validate_building_placement(&map, TownLayout::Basic, &[], BuildingType::Tree, (0, 0))
+            .is_ok()
+    );
+}
+
+#[test]
+fn units_block_placement() {
+    let map = empty_map();
+    let standing = [UnitPosition::stationary((1, 1))];
+    assert_eq!(
+        validate_building_placement(
+            &map,
+            TownLayout::Basic,
+            &standing,
+            BuildingType::Temple,
+            (0, 0)
+        ),
+        Err(PlacementError::UnitBlocks)
+    );
+    assert!(validate_building_placement(
+        &map,
+        TownLayout::Basic,
+        &standing,
+        BuildingType::Tree,
+        (2, 1)
+    )
+    .is_ok());
+
+    let walking = [UnitPosition::walking((0, 1), vec![(3, 1), (3, 0)])];
+    assert_eq!(
+        validate_building_placement(
+            &map,
+            TownLayout::Basic,
+            &walking,
+            BuildingType::Tree,
+            (2, 1)
+        ),
+        Err(PlacementError::WalkingUnitBlocks)
+    );
+    assert_eq!(
+        validate_building_placement(
+            &map,
+            TownLayout::Basic,
+            &walking,
+            BuildingType::Tree,
+            (3, 0)
+        ),
+        Err(PlacementError::WalkingUnitBlocks)
+    );
+    // Tiles next to the path are not affected
+    assert!(validate_building_placement(
+        &map,
+        TownLayout::Basic,
+        &walking,
+        BuildingType::Tree,
+        (2, 0)
+    )
+    .is_ok());
+    assert!(validate_building_placement(
+        &map,
+        TownLayout::Basic,
+        &walking,
+        BuildingType::Tree,
+        (4, 1)
+    )
+    .is_ok());
+}
+
+#[test]
+fn buildings_move_over_their_own_footprint() {
+    let mut map = empty_map();
+    let mut state: TownState<usize> = TownState::new();
+    let bt = BuildingType::SawMill;
+    map.insert_building(bt, (0, 0)).unwrap();
+    state.insert(&bt.footprint((0, 0)), TileState::new_building(1, 1, 0));
+
+    assert!(
+        validate_building_relocation(&map, &state, TownLayout::Basic, &[], bt, (0, 0), (1, 0))
+            .is_ok()
+    );
+    assert_eq!(
+        validate_building_relocation(&map, &state, TownLayout::Basic, &[], bt, (0, 0), (0, 3)),
+        Err(PlacementError::Lane)
+    );
+    // The original map is not changed by the check
+    assert_eq!(map[(0, 0)], TownTileType::BUILDING(bt));
+}
+
+#[test]
+fn buildings_in_use_cannot_move() {
+    let mut map = empty_map();
+    let mut state: TownState<usize> = TownState::new();
+    let bt = BuildingType::BundlingStation;
+    map.insert_building(bt, (0, 0)).unwrap();
+    state.insert(&bt.footprint((0, 0)), TileState::new_building(1, 1, 1));
+    assert_eq!(
+        validate_building_relocation(&map, &state, TownLayout::Basic, &[], bt, (0, 0), (1, 0)),
+        Err(PlacementError::BuildingInUse)
+    );
+
+    state.get_mut(&(0, 0)).unwrap().try_remove_entity().unwrap();
+    let heading_there = [UnitPosition::walking((2, 1), vec![(0, 1), (0, 0)])];
+    assert_eq!(
+        validate_building_relocation(
+            &map,
+            &state,
+            TownLayout::Basic,
+            &heading_there,
+            bt,
+            (0, 0),
+            (1, 0)
+        ),
+        Err(PlacementError::BuildingInUse)
+    );
+}
+
+#[test]
+fn found_space_keeps_lane_walkable() {
+    let hero = [UnitPosition::stationary((5, 2))];
+    for layout in &[
+        TownLayout::Basic,
+        TownLayout::Bend,
+        TownLayout::Meander,
+        TownLayout::Fork,
+    ] {
+        let mut map = TownMap::new(*layout);
+        let anchor =
+            find_building_space(&map, *layout, &hero, BuildingType::Temple, (4, 2)).unwrap();
+        map.insert_building(BuildingType::Temple, anchor).unwrap();
+        assert!(!BuildingType::Temple.footprint(anchor).contains(&(5, 2)));
+        for tile in layout.lane() {
+            assert_eq!(map[tile], TownTileType::LANE, "{:?}", layout);
+        }
+    }
+}
+
+#[test]
+fn found_space_is_closest_to_preference() {
+    let map = empty_map();
+    assert_eq!(
+        find_building_space(&map, TownLayout::Basic, &[], BuildingType::Tree, (1, 1)),
+        Some((1, 1))
+    );
+    // The lane pushes the building to the closest free row
+    assert_eq!(
+        find_building_space(
+            &map,
+            TownLayout::Basic,
+            &[],
+            BuildingType::Tree,
+            (1, TOWN_LANE_Y)
+        ),
+        Some((1, TOWN_LANE_Y - 1))
+    );
+}
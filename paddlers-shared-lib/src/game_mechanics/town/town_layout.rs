@@ -1,4 +1,5 @@
 use super::*;
+use crate::shared_types::Timestamp;
 
 /// The town layout defines where the lane goes through the tile-grid.
 /// Using this abstract view, it's possible to determine in which tile a unit is after walking a certain distance.
@@ -6,37 +7,49 @@ use super::*;
 /// A path is defined as list of tiles.
 /// At t=0, a unit walking that path is just walking into path_tile[0].
 /// It then has to walk a full tile before path_tile[1] is reached.
+///
+/// Unhurried visitors each rest on their own spot, numbered from 0.
+/// Every spot is a separate tile of the lane.
+/// Where the lane forks, the path to and from a spot may take another branch than the path straight through.
 pub trait ITownLayout {
     type Index: 'static + std::fmt::Debug;
-    fn path_to_rest_place(&self) -> &'static [Self::Index];
-    fn path_from_rest_place(&self) -> &'static [Self::Index];
+    fn path_to_rest_place(&self, spot: usize) -> &'static [Self::Index];
+    fn path_from_rest_place(&self, spot: usize) -> &'static [Self::Index];
     fn path_straight_through(&self) -> &'static [Self::Index];
+
+    /// Returns the duration it takes a hobo to reach its resting spot, after having reached the town.
+    fn time_until_resting(&self, speed: f32, spot: usize) -> Timestamp {
+        let distance_until_resting = self.path_to_rest_place(spot).len();
+        Timestamp::from_float_seconds(distance_until_resting as f32 / speed)
+    }
 }
-/// Implementing this marker trait, which only involves picking a TownLayout, will auto-implement ITownLayout
+/// Implementing this marker trait, which only involves returning the TownLayout of the town, will auto-implement ITownLayout
 pub trait ITownLayoutMarker {
-    const LAYOUT: TownLayout;
+    fn town_layout(&self) -> TownLayout;
 }
 
-pub enum TownLayout {
-    Basic,
-}
+pub use crate::models::TownLayout;
 
 impl<T: ITownLayoutMarker> ITownLayout for T {
     type Index = TownLayoutIndex;
     #[inline(always)]
-    fn path_to_rest_place(&self) -> &'static [Self::Index] {
-        Self::LAYOUT.path_to_rest_place()
+    fn path_to_rest_place(&self, spot: usize) -> &'static [Self::Index] {
+        self.town_layout().path_to_rest_place(spot)
     }
     #[inline(always)]
-    fn path_from_rest_place(&self) -> &'static [Self::Index] {
-        Self::LAYOUT.path_from_rest_place()
+    fn path_from_rest_place(&self, spot: usize) -> &'static [Self::Index] {
+        self.town_layout().path_from_rest_place(spot)
     }
     #[inline(always)]
     fn path_straight_through(&self) -> &'static [Self::Index] {
-        Self::LAYOUT.path_straight_through()
+        self.town_layout().path_straight_through()
     }
 }
 
+// All paths start at the right border and end at the left border, without ever going right.
+// The first resting spot is always at TOWN_RESTING_X, further spots queue up behind it.
+// Layouts with a fork have a second resting place on the side branch, also at TOWN_RESTING_X.
+
 static BASIC_PATH: [TownLayoutIndex; 9] = [
     (8, TOWN_LANE_Y),
     (7, TOWN_LANE_Y),
@@ -48,22 +61,118 @@ static BASIC_PATH: [TownLayoutIndex; 9] = [
     (1, TOWN_LANE_Y),
     (0, TOWN_LANE_Y),
 ];
+// The side branch of the fork, the main branch is the basic path
+static FORK_SIDE_PATH: [TownLayoutIndex; 13] = [
+    (8, TOWN_LANE_Y),
+    (7, TOWN_LANE_Y),
+    (6, TOWN_LANE_Y),
+    (6, TOWN_LANE_Y - 1),
+    (6, TOWN_LANE_Y - 2),
+    (5, TOWN_LANE_Y - 2),
+    (TOWN_RESTING_X, TOWN_LANE_Y - 2),
+    (3, TOWN_LANE_Y - 2),
+    (2, TOWN_LANE_Y - 2),
+    (2, TOWN_LANE_Y - 1),
+    (2, TOWN_LANE_Y),
+    (1, TOWN_LANE_Y),
+    (0, TOWN_LANE_Y),
+];
+static BEND_PATH: [TownLayoutIndex; 11] = [
+    (8, TOWN_LANE_Y - 1),
+    (7, TOWN_LANE_Y - 1),
+    (6, TOWN_LANE_Y - 1),
+    (6, TOWN_LANE_Y),
+    (5, TOWN_LANE_Y),
+    (TOWN_RESTING_X, TOWN_LANE_Y),
+    (3, TOWN_LANE_Y),
+    (2, TOWN_LANE_Y),
+    (2, TOWN_LANE_Y + 1),
+    (1, TOWN_LANE_Y + 1),
+    (0, TOWN_LANE_Y + 1),
+];
+static MEANDER_PATH: [TownLayoutIndex; 13] = [
+    (8, TOWN_LANE_Y + 1),
+    (7, TOWN_LANE_Y + 1),
+    (7, TOWN_LANE_Y),
+    (6, TOWN_LANE_Y),
+    (6, TOWN_LANE_Y - 1),
+    (5, TOWN_LANE_Y - 1),
+    (TOWN_RESTING_X, TOWN_LANE_Y - 1),
+    (3, TOWN_LANE_Y - 1),
+    (3, TOWN_LANE_Y),
+    (2, TOWN_LANE_Y),
+    (2, TOWN_LANE_Y + 1),
+    (1, TOWN_LANE_Y + 1),
+    (0, TOWN_LANE_Y + 1),
+];
 
 impl ITownLayout for TownLayout {
     type Index = TownLayoutIndex;
-    fn path_to_rest_place(&self) -> &'static [Self::Index] {
+    fn path_to_rest_place(&self, spot: usize) -> &'static [Self::Index] {
+        let (branch, position) = self.rest_place_position(spot);
+        &self.branch(branch)[0..position + 1]
+    }
+    fn path_from_rest_place(&self, spot: usize) -> &'static [Self::Index] {
+        let (branch, position) = self.rest_place_position(spot);
+        &self.branch(branch)[position + 1..]
+    }
+    fn path_straight_through(&self) -> &'static [Self::Index] {
+        self.branch(0)
+    }
+}
+
+impl TownLayout {
+    /// The tile where an unhurried visitor on the given spot waits
+    pub fn rest_place(&self, spot: usize) -> TownLayoutIndex {
+        let (branch, position) = self.rest_place_position(spot);
+        self.branch(branch)[position]
+    }
+    /// How many different paths lead through the town
+    pub fn branches(&self) -> usize {
         match self {
-            Self::Basic => &BASIC_PATH[0..5],
+            Self::Fork => 2,
+            Self::Basic | Self::Bend | Self::Meander => 1,
         }
     }
-    fn path_from_rest_place(&self) -> &'static [Self::Index] {
-        match self {
-            Self::Basic => &BASIC_PATH[5..],
+    /// A full path through the town, the first branch is the path straight through
+    pub fn branch(&self, branch: usize) -> &'static [TownLayoutIndex] {
+        match (self, branch) {
+            (Self::Basic, _) => &BASIC_PATH,
+            (Self::Bend, _) => &BEND_PATH,
+            (Self::Meander, _) => &MEANDER_PATH,
+            (Self::Fork, 0) => &BASIC_PATH,
+            (Self::Fork, _) => &FORK_SIDE_PATH,
         }
     }
-    fn path_straight_through(&self) -> &'static [Self::Index] {
+    /// All tiles covered by the river, each listed once
+    pub fn lane(&self) -> Vec<TownLayoutIndex> {
+        let mut tiles = vec![];
+        for branch in 0..self.branches() {
+            for tile in self.branch(branch) {
+                if !tiles.contains(tile) {
+                    tiles.push(*tile);
+                }
+            }
+        }
+        tiles
+    }
+    /// How many visitors can rest in the town at the same time, at most
+    pub fn resting_spots(&self) -> usize {
+        self.rest_place_positions().len()
+    }
+    /// Spots outside the layout are mapped to the last spot
+    fn rest_place_position(&self, spot: usize) -> (usize, usize) {
+        let positions = self.rest_place_positions();
+        positions[spot.min(positions.len() - 1)]
+    }
+    /// Branch and index into the branch for each resting spot.
+    /// With two resting places, the spots alternate between them.
+    fn rest_place_positions(&self) -> &'static [(usize, usize)] {
         match self {
-            Self::Basic => &BASIC_PATH,
+            Self::Basic => &[(0, 4), (0, 3), (0, 2)],
+            Self::Bend => &[(0, 5), (0, 4), (0, 3)],
+            Self::Meander => &[(0, 6), (0, 5), (0, 4)],
+            Self::Fork => &[(0, 4), (1, 6), (0, 3), (1, 5)],
         }
     }
 }
@@ -0,0 +1,151 @@
+use super::*;
+
+#[test]
+fn recruitment_requires_housing() {
+    let mut state: TownState<usize> = TownState::new();
+    assert_eq!(
+        state.try_recruit_worker(TaskType::Idle),
+        Err(TownError::NoHousing)
+    );
+
+    state.worker_housing = worker_housing_capacity(&[BuildingType::Shack, BuildingType::Tree]);
+    assert_eq!(state.worker_housing, 2);
+    assert!(state.try_recruit_worker(TaskType::Idle).is_ok());
+    state.register_basic_worker();
+    assert_eq!(state.basic_workers(), 2);
+    assert_eq!(
+        state.try_recruit_worker(TaskType::Idle),
+        Err(TownError::NoHousing)
+    );
+}
+
+#[test]
+fn recruitment_respects_forest_supply() {
+    let mut state: TownState<usize> = TownState::new();
+    state.worker_housing = 1;
+    let required = TaskType::ChopTree.required_forest_size();
+    assert!(required > 0);
+    state.forest_size = required - 1;
+    assert_eq!(
+        state.try_recruit_worker(TaskType::ChopTree),
+        Err(TownError::NotEnoughSupply)
+    );
+    assert_eq!(state.basic_workers(), 0);
+
+    state.forest_size = required;
+    assert!(state.try_recruit_worker(TaskType::ChopTree).is_ok());
+    assert_eq!(state.forest_usage(), required);
+}
+
+#[test]
+fn buildings_cover_their_footprint() {
+    let mut state: TownState<usize> = TownState::new();
+    let footprint = BuildingType::Temple.footprint((3, 1));
+    assert_eq!(footprint, vec![(3, 1), (4, 1), (3, 2), (4, 2)]);
+
+    state.insert(&footprint, TileState::new_building(7, 0, 0));
+    assert_eq!(state.get(&(4, 2)).map(|s| s.entity), Some(7));
+    assert_eq!(state.anchor(&(4, 2)), Some((3, 1)));
+    assert_eq!(state.footprint(&(4, 1)).len(), 4);
+    assert!(state.get(&(5, 2)).is_none());
+
+    assert_eq!(state.remove(&(3, 2)).entity, 7);
+    assert!(state.get(&(3, 1)).is_none());
+    assert!(state.footprint(&(4, 2)).is_empty());
+}
+
+#[test]
+fn footprint_must_fit_on_map() {
+    let mut map = TownMap::new(TownLayout::Basic);
+    let lane_free_row = (0..TOWN_Y)
+        .find(|y| (0..TOWN_X).all(|x| map[(x, *y)] == TownTileType::EMPTY))
+        .unwrap();
+    assert!(map.footprint_is_buildable(BuildingType::SawMill, (0, lane_free_row)));
+    assert!(!map.footprint_is_buildable(BuildingType::SawMill, (TOWN_X - 1, lane_free_row)));
+
+    map.insert_building(BuildingType::SawMill, (0, lane_free_row))
+        .unwrap();
+    assert_eq!(
+        map[(1, lane_free_row)],
+        TownTileType::BUILDING(BuildingType::SawMill)
+    );
+    assert!(!map.footprint_is_buildable(BuildingType::Tree, (1, lane_free_row)));
+}
+
+#[test]
+fn buildings_do_not_cover_lane_or_other_buildings() {
+    let mut map = TownMap::new(TownLayout::Basic);
+    assert_eq!(
+        map.insert_building(BuildingType::Temple, (4, TOWN_LANE_Y - 1)),
+        Err(PlacementError::Lane)
+    );
+    assert_eq!(
+        map.insert_building(BuildingType::SawMill, (TOWN_X - 1, 0)),
+        Err(PlacementError::OutOfBounds)
+    );
+    map.insert_building(BuildingType::Tree, (5, 1)).unwrap();
+    assert_eq!(
+        map.insert_building(BuildingType::Temple, (4, 0)),
+        Err(PlacementError::Occupied)
+    );
+    // Refused buildings leave no trace
+    assert_eq!(map[(4, 0)], TownTileType::EMPTY);
+    assert_eq!(map[(4, TOWN_LANE_Y - 1)], TownTileType::EMPTY);
+    assert_eq!(map[(4, TOWN_LANE_Y)], TownTileType::LANE);
+}
+
+#[test]
+fn resting_capacity_counts_each_building_once() {
+    let mut map = TownMap::new(TownLayout::Basic);
+    let mut state: TownState<usize> = TownState::new();
+    for (i, (bt, anchor)) in [
+        (BuildingType::Temple, (0, 0)),
+        (BuildingType::Harbour, (3, 0)),
+    ]
+    .iter()
+    .enumerate()
+    {
+        map.insert_building(*bt, *anchor).unwrap();
+        state.insert(&bt.footprint(*anchor), TileState::new_building(i, 0, 0));
+    }
+    let buildings: Vec<&BuildingType> = state
+        .building_anchors()
+        .filter_map(|anchor| match &map[*anchor] {
+            TownTileType::BUILDING(bt) => Some(bt),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(buildings.len(), 2);
+    assert_eq!(
+        visitor_queue_capacity(TownLayout::Basic, buildings),
+        BASE_VISITOR_QUEUE + BuildingType::Harbour.resting_spots()
+    );
+}
+
+#[test]
+fn resting_capacity_is_limited_by_layout() {
+    let harbours = vec![BuildingType::Harbour; 10];
+    for layout in &[
+        TownLayout::Basic,
+        TownLayout::Bend,
+        TownLayout::Meander,
+        TownLayout::Fork,
+    ] {
+        assert_eq!(visitor_queue_capacity(*layout, &[]), BASE_VISITOR_QUEUE);
+        assert_eq!(
+            visitor_queue_capacity(*layout, &harbours),
+            layout.resting_spots()
+        );
+    }
+}
+
+#[test]
+fn each_visitor_gets_its_own_resting_spot() {
+    assert_eq!(next_resting_spot(&[], 3), 0);
+    assert_eq!(next_resting_spot(&[0, 2], 3), 1);
+    assert_eq!(next_resting_spot(&[1], 3), 0);
+    // All taken, the spot of the visitor that arrived first is reused
+    assert_eq!(next_resting_spot(&[2, 0, 1], 3), 2);
+    // The capacity may have shrunk since the other spots were assigned
+    assert_eq!(next_resting_spot(&[2], 1), 0);
+}
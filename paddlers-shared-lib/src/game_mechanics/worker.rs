@@ -1,11 +1,23 @@
-use crate::models::{ResourceType, TaskType};
+#[cfg(test)]
+mod worker_test;
+
+use crate::balancing::balancing;
+pub use crate::balancing::HeroLevelUp;
+use crate::models::{ResourceType, TaskType, UnitType};
 
 pub const fn unit_speed_to_worker_tiles_per_second(base_speed: f32) -> f32 {
     base_speed
 }
 
-pub const fn hero_max_mana() -> i32 {
-    100
+/// Maximum mana of a hero at the given level
+pub fn hero_max_mana(level: i32) -> i32 {
+    balancing().heroes.base_max_mana + hero_level_ups(1, level).map(|l| l.max_mana).sum::<i32>()
+}
+
+/// Maximum mana of a hero for each level where the index is `level - 1`.
+/// The last entry applies to all higher levels.
+pub fn hero_max_mana_by_level() -> Vec<i32> {
+    (1..=hero_max_level()).map(hero_max_mana).collect()
 }
 
 pub const fn hero_mana_regeneration_per_hour() -> i32 {
@@ -20,6 +32,59 @@ pub fn hero_resource_collection_per_hour(task: TaskType) -> Option<(ResourceType
     }
 }
 
+/// Basic workers collect resources at a lower rate than heroes
+pub fn worker_resource_collection_per_hour(
+    unit_type: UnitType,
+    task: TaskType,
+) -> Option<(ResourceType, i32)> {
+    match unit_type {
+        UnitType::Hero => hero_resource_collection_per_hour(task),
+        UnitType::Basic => match task {
+            TaskType::ChopTree => Some((ResourceType::Logs, 3)),
+            TaskType::GatherSticks => Some((ResourceType::Sticks, 40)),
+            _ => None,
+        },
+    }
+}
+
+impl TaskType {
+    /// Whether the task can be part of a repeating task list.
+    /// Tasks with visitors as targets or with one-time effects cannot be repeated.
+    pub fn repeatable(&self) -> bool {
+        match self {
+            TaskType::Idle | TaskType::Walk | TaskType::GatherSticks | TaskType::ChopTree => true,
+            _ => false,
+        }
+    }
+}
+
 pub const fn hero_level_exp(now: i32) -> i32 {
     now * 100
 }
+
+/// Everything a hero gains when going from level `from` up to level `to`
+pub fn hero_level_ups(from: i32, to: i32) -> impl Iterator<Item = &'static HeroLevelUp> {
+    balancing()
+        .heroes
+        .levels
+        .iter()
+        .filter(move |l| l.level > from && l.level <= to)
+}
+
+/// Highest level at which a hero still gains something
+pub fn hero_max_level() -> i32 {
+    balancing()
+        .heroes
+        .levels
+        .last()
+        .map(|l| l.level)
+        .unwrap_or(1)
+}
+
+/// Satisfaction a single welcome of a hero at the given level gives to a visitor
+pub fn hero_welcome_strength(level: i32) -> i32 {
+    balancing().heroes.base_welcome_strength
+        + hero_level_ups(1, level)
+            .map(|l| l.welcome_strength)
+            .sum::<i32>()
+}
@@ -0,0 +1,57 @@
+use super::*;
+use crate::models::AbilityType;
+
+#[test]
+fn progression_is_sorted() {
+    let levels = &balancing().heroes.levels;
+    for pair in levels.windows(2) {
+        assert!(pair[0].level < pair[1].level);
+    }
+    assert!(levels.iter().all(|l| l.level > 1));
+}
+
+#[test]
+fn level_ups_cover_only_new_levels() {
+    assert_eq!(hero_level_ups(1, 1).count(), 0);
+    assert_eq!(
+        hero_level_ups(2, 4).map(|l| l.level).collect::<Vec<_>>(),
+        vec![3, 4]
+    );
+    assert_eq!(
+        hero_level_ups(1, 1000).count(),
+        balancing().heroes.levels.len()
+    );
+}
+
+#[test]
+fn stats_grow_with_level() {
+    assert_eq!(hero_max_mana(1), balancing().heroes.base_max_mana);
+    assert_eq!(
+        hero_welcome_strength(1),
+        balancing().heroes.base_welcome_strength
+    );
+    for level in 1..hero_max_level() {
+        assert!(hero_max_mana(level) <= hero_max_mana(level + 1));
+        assert!(hero_welcome_strength(level) <= hero_welcome_strength(level + 1));
+    }
+    assert_eq!(hero_max_mana(1000), hero_max_mana(hero_max_level()));
+    let table = hero_max_mana_by_level();
+    assert_eq!(table.len() as i32, hero_max_level());
+    assert_eq!(table[0], hero_max_mana(1));
+}
+
+#[test]
+fn abilities_cannot_be_repeated() {
+    let abilities = [
+        AbilityType::Welcome,
+        AbilityType::Cheer,
+        AbilityType::Slow,
+        AbilityType::Bloom,
+    ];
+    for ability in &abilities {
+        assert!(!ability.task().unwrap().repeatable());
+    }
+    assert!(!TaskType::CollectReward.repeatable());
+    assert!(TaskType::GatherSticks.repeatable());
+    assert!(TaskType::ChopTree.repeatable());
+}
@@ -21,6 +21,7 @@ pub extern crate strum;
 pub mod macros;
 
 pub mod api;
+pub mod balancing;
 pub mod config;
 pub mod display;
 pub mod graphql_types;
@@ -16,8 +16,9 @@ use ::diesel_derive_enum::DbEnum;
 
 #[cfg(feature = "sql_db")]
 use super::schema::{
-    abilities, attacks, attacks_to_hobos, buildings, effects, hobos, players, resources, rewards,
-    streams, tasks, villages, visit_reports, worker_flags, workers,
+    abilities, attacks, attacks_to_hobos, blooms, buildings, effects, hobos, players,
+    repeating_tasks, resources, return_trips, return_trips_to_hobos, rewards, shipments, streams,
+    tasks, trade_offers, villages, visit_reports, worker_flags, workers,
 };
 
 #[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
@@ -145,6 +146,40 @@ pub struct AttackToHobo {
     pub satisfied: Option<bool>,
     /// if None (NULL), the unit is waiting in town unless it is hurried
     pub released: Option<NaiveDateTime>,
+    /// Where the unit waits in town, unless it is hurried
+    pub resting_spot: i32,
+}
+
+#[cfg(feature = "sql_db")]
+#[derive(Debug, Queryable, Identifiable)]
+/// Visitors swimming back to their home village after a visit
+pub struct ReturnTrip {
+    pub id: i64,
+    pub departure: NaiveDateTime,
+    pub arrival: NaiveDateTime,
+    pub home_village_id: i64,
+    pub visited_village_id: Option<i64>,
+}
+
+#[derive(Debug)]
+#[cfg(feature = "sql_db")]
+#[derive(Insertable)]
+#[table_name = "return_trips"]
+pub struct NewReturnTrip {
+    pub departure: NaiveDateTime,
+    pub arrival: NaiveDateTime,
+    pub home_village_id: i64,
+    pub visited_village_id: Option<i64>,
+}
+
+#[cfg(feature = "sql_db")]
+#[derive(Debug, Queryable, Insertable)]
+#[table_name = "return_trips_to_hobos"]
+pub struct ReturnTripToHobo {
+    pub return_trip_id: i64,
+    pub hobo_id: i64,
+    /// Whether the visitor has been satisfied in the visited town
+    pub satisfied: bool,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
@@ -162,6 +197,28 @@ pub enum BuildingType {
     Temple,
     SingleNest,
     TripleNest,
+    Fountain,
+    Sprinkler,
+    Harbour,
+    Shack,
+}
+
+/// Defines where the river flows through a town
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "enum_utils", derive(EnumIter, Display))]
+#[cfg_attr(feature = "graphql", derive(juniper::GraphQLEnum))]
+#[cfg_attr(feature = "sql_db", derive(DbEnum), DieselType = "Town_layout_type")]
+pub enum TownLayout {
+    Basic,
+    Bend,
+    Meander,
+    /// The river forks around an island, visitors can rest on both branches
+    Fork,
+}
+impl Default for TownLayout {
+    fn default() -> Self {
+        TownLayout::Basic
+    }
 }
 
 #[cfg(feature = "sql_db")]
@@ -176,6 +233,7 @@ pub struct Building {
     pub attacks_per_cycle: Option<i32>,
     pub creation: NaiveDateTime,
     pub village_id: i64,
+    pub level: i32,
 }
 
 #[cfg(feature = "sql_db")]
@@ -190,6 +248,7 @@ pub struct NewBuilding {
     pub attacks_per_cycle: Option<i32>,
     pub creation: NaiveDateTime,
     pub village_id: i64,
+    pub level: i32,
 }
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
@@ -202,6 +261,57 @@ pub enum ResourceType {
     Feathers,
 }
 
+#[cfg(feature = "sql_db")]
+#[derive(Debug, Queryable, Identifiable)]
+/// Resources on a boat travelling from one village to another
+pub struct Shipment {
+    pub id: i64,
+    pub departure: NaiveDateTime,
+    pub arrival: NaiveDateTime,
+    pub origin_village_id: Option<i64>,
+    pub destination_village_id: i64,
+    pub resource_type: ResourceType,
+    pub amount: i64,
+}
+
+#[derive(Debug)]
+#[cfg(feature = "sql_db")]
+#[derive(Insertable)]
+#[table_name = "shipments"]
+pub struct NewShipment {
+    pub departure: NaiveDateTime,
+    pub arrival: NaiveDateTime,
+    pub origin_village_id: Option<i64>,
+    pub destination_village_id: i64,
+    pub resource_type: ResourceType,
+    pub amount: i64,
+}
+
+#[cfg(feature = "sql_db")]
+#[derive(Debug, Queryable, Identifiable)]
+/// An open offer on the marketplace, the offered resources are held back until it is closed
+pub struct TradeOffer {
+    pub id: i64,
+    pub village_id: i64,
+    pub offered_resource: ResourceType,
+    pub offered_amount: i64,
+    pub requested_resource: ResourceType,
+    pub requested_amount: i64,
+    pub created: NaiveDateTime,
+}
+
+#[derive(Debug)]
+#[cfg(feature = "sql_db")]
+#[derive(Insertable)]
+#[table_name = "trade_offers"]
+pub struct NewTradeOffer {
+    pub village_id: i64,
+    pub offered_resource: ResourceType,
+    pub offered_amount: i64,
+    pub requested_resource: ResourceType,
+    pub requested_amount: i64,
+}
+
 #[cfg(feature = "sql_db")]
 #[derive(Identifiable, Insertable, Queryable, Debug)]
 #[table_name = "resources"]
@@ -224,6 +334,9 @@ pub enum TaskType {
     ChopTree,
     WelcomeAbility,
     CollectReward,
+    CheerAbility,
+    SlowAbility,
+    BloomAbility,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Identifiable, AsChangeset)]
@@ -250,6 +363,30 @@ pub struct NewTask {
     pub target_hobo_id: Option<i64>,
 }
 
+#[derive(Debug, Clone, Queryable, Identifiable)]
+#[cfg(feature = "sql_db")]
+/// One step of a task list that a worker repeats until it gets new orders.
+/// The steps of a worker are performed in the order of their ids.
+pub struct RepeatingTask {
+    pub id: i64,
+    pub worker_id: i64,
+    pub task_type: TaskType,
+    pub x: i32,
+    pub y: i32,
+    pub duration_ms: i64,
+}
+
+#[cfg(feature = "sql_db")]
+#[derive(Insertable, Debug)]
+#[table_name = "repeating_tasks"]
+pub struct NewRepeatingTask {
+    pub worker_id: i64,
+    pub task_type: TaskType,
+    pub x: i32,
+    pub y: i32,
+    pub duration_ms: i64,
+}
+
 #[derive(Debug, Clone, Queryable, Identifiable)]
 #[cfg(feature = "sql_db")]
 pub struct Stream {
@@ -275,6 +412,7 @@ pub struct Village {
     pub stream_id: i64,
     pub player_id: Option<i64>,
     pub faith: i16,
+    pub town_layout: TownLayout,
 }
 
 #[derive(Insertable, Debug)]
@@ -286,6 +424,7 @@ pub struct NewVillage {
     pub stream_id: i64,
     pub player_id: Option<i64>,
     pub faith: Option<i16>,
+    pub town_layout: TownLayout,
 }
 
 #[derive(Debug, Clone, Copy, Queryable, AsChangeset)]
@@ -314,6 +453,12 @@ pub struct NewAbility {
 pub enum AbilityType {
     Work,
     Welcome,
+    /// Welcomes all visitors in an area
+    Cheer,
+    /// Slows down a hurried visitor, to keep it longer in range of auras
+    Slow,
+    /// Makes flower auras more effective on a visitor
+    Bloom,
 }
 
 #[derive(Debug, Clone, Copy, Queryable)]
@@ -336,6 +481,25 @@ pub struct NewEffect {
     pub start_time: Option<NaiveDateTime>,
 }
 
+/// A flower building in bloom, its aura is stronger for a limited time
+#[derive(Debug, Clone, Copy, Queryable)]
+#[cfg(feature = "sql_db")]
+pub struct Bloom {
+    pub id: i64,
+    pub building_id: i64,
+    pub strength: i32,
+    pub start_time: NaiveDateTime,
+}
+
+#[derive(Insertable, Debug)]
+#[cfg(feature = "sql_db")]
+#[table_name = "blooms"]
+pub struct NewBloom {
+    pub building_id: i64,
+    pub strength: i32,
+    pub start_time: NaiveDateTime,
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Serialize, Deserialize)]
 #[cfg_attr(feature = "enum_utils", derive(EnumIter, Display))]
 #[cfg_attr(feature = "graphql", derive(juniper::GraphQLEnum))]
@@ -343,6 +507,7 @@ pub struct NewEffect {
 /// Describes an attribute of a hobo
 pub enum HoboAttributeType {
     Health,
+    /// Speed change in percent
     Speed,
 }
 
@@ -371,6 +536,11 @@ pub struct VisitReport {
     pub village_id: i64,
     pub reported: NaiveDateTime,
     pub karma: i64,
+    pub report_type: VisitReportType,
+    /// The village on the other side of the visit, if it was a village of a player
+    pub other_village_id: Option<i64>,
+    /// Number of visitors, satisfied or not
+    pub visitors: i64,
     // letter text, icon, ...
 }
 
@@ -380,6 +550,19 @@ pub struct VisitReport {
 pub struct NewVisitReport {
     pub village_id: i64,
     pub karma: i64,
+    pub report_type: VisitReportType,
+    pub other_village_id: Option<i64>,
+    pub visitors: i64,
+}
+
+/// Reports are written for the visited town (inbound) and for the town sending the visitors (outbound)
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "enum_utils", derive(EnumIter, Display))]
+#[cfg_attr(feature = "graphql", derive(juniper::GraphQLEnum))]
+#[cfg_attr(feature = "sql_db", derive(DbEnum), DieselType = "Visit_report_type")]
+pub enum VisitReportType {
+    Inbound,
+    Outbound,
 }
 
 #[derive(Debug, Clone, Copy, Queryable)]
@@ -389,6 +572,8 @@ pub struct Reward {
     pub visit_report_id: i64,
     pub resource_type: ResourceType,
     pub amount: i64,
+    pub reason: RewardReason,
+    pub building_type: Option<BuildingType>,
 }
 
 #[derive(Insertable, Debug)]
@@ -398,4 +583,22 @@ pub struct NewReward {
     pub visit_report_id: i64,
     pub resource_type: ResourceType,
     pub amount: i64,
+    pub reason: RewardReason,
+    pub building_type: Option<BuildingType>,
+}
+
+/// Why a reward has been granted after a visit
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "enum_utils", derive(EnumIter, Display))]
+#[cfg_attr(feature = "graphql", derive(juniper::GraphQLEnum))]
+#[cfg_attr(feature = "sql_db", derive(DbEnum), DieselType = "Reward_reason")]
+pub enum RewardReason {
+    /// Base reward for each satisfied visitor
+    Visitor,
+    /// Visitors that received effects from workers
+    Effects,
+    /// Visitors of a special color
+    Color,
+    /// Buildings in the town, the building type is stored with the reward
+    Building,
 }
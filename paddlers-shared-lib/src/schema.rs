@@ -31,6 +31,19 @@ table! {
         hobo_id -> Int8,
         satisfied -> Nullable<Bool>,
         released -> Nullable<Timestamp>,
+        resting_spot -> Int4,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    blooms (id) {
+        id -> Int8,
+        building_id -> Int8,
+        strength -> Int4,
+        start_time -> Timestamp,
     }
 }
 
@@ -48,6 +61,7 @@ table! {
         attacks_per_cycle -> Nullable<Int4>,
         creation -> Timestamp,
         village_id -> Int8,
+        level -> Int4,
     }
 }
 
@@ -92,6 +106,20 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    repeating_tasks (id) {
+        id -> Int8,
+        worker_id -> Int8,
+        task_type -> Task_type,
+        x -> Int4,
+        y -> Int4,
+        duration_ms -> Int8,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::models::*;
@@ -103,6 +131,30 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    return_trips (id) {
+        id -> Int8,
+        departure -> Timestamp,
+        arrival -> Timestamp,
+        home_village_id -> Int8,
+        visited_village_id -> Nullable<Int8>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    return_trips_to_hobos (return_trip_id, hobo_id) {
+        return_trip_id -> Int8,
+        hobo_id -> Int8,
+        satisfied -> Bool,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::models::*;
@@ -112,6 +164,23 @@ table! {
         visit_report_id -> Int8,
         resource_type -> Resource_type,
         amount -> Int8,
+        reason -> Reward_reason,
+        building_type -> Nullable<Building_type>,
+    }
+}
+
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    shipments (id) {
+        id -> Int8,
+        departure -> Timestamp,
+        arrival -> Timestamp,
+        origin_village_id -> Nullable<Int8>,
+        destination_village_id -> Int8,
+        resource_type -> Resource_type,
+        amount -> Int8,
     }
 }
 
@@ -141,6 +210,21 @@ table! {
     }
 }
 
+table! {
+    use diesel::sql_types::*;
+    use crate::models::*;
+
+    trade_offers (id) {
+        id -> Int8,
+        village_id -> Int8,
+        offered_resource -> Resource_type,
+        offered_amount -> Int8,
+        requested_resource -> Resource_type,
+        requested_amount -> Int8,
+        created -> Timestamp,
+    }
+}
+
 table! {
     use diesel::sql_types::*;
     use crate::models::*;
@@ -152,6 +236,7 @@ table! {
         stream_id -> Int8,
         player_id -> Nullable<Int8>,
         faith -> Int2,
+        town_layout -> Town_layout_type,
     }
 }
 
@@ -164,6 +249,9 @@ table! {
         village_id -> Int8,
         reported -> Timestamp,
         karma -> Int8,
+        report_type -> Visit_report_type,
+        other_village_id -> Nullable<Int8>,
+        visitors -> Int8,
     }
 }
 
@@ -199,17 +287,21 @@ table! {
 joinable!(abilities -> workers (worker_id));
 joinable!(attacks_to_hobos -> attacks (attack_id));
 joinable!(attacks_to_hobos -> hobos (hobo_id));
+joinable!(blooms -> buildings (building_id));
 joinable!(buildings -> villages (village_id));
 joinable!(effects -> hobos (hobo_id));
 joinable!(hobos -> buildings (nest));
 joinable!(hobos -> villages (home));
+joinable!(repeating_tasks -> workers (worker_id));
 joinable!(resources -> villages (village_id));
+joinable!(return_trips_to_hobos -> hobos (hobo_id));
+joinable!(return_trips_to_hobos -> return_trips (return_trip_id));
 joinable!(rewards -> visit_reports (visit_report_id));
 joinable!(tasks -> hobos (target_hobo_id));
 joinable!(tasks -> workers (worker_id));
+joinable!(trade_offers -> villages (village_id));
 joinable!(villages -> players (player_id));
 joinable!(villages -> streams (stream_id));
-joinable!(visit_reports -> villages (village_id));
 joinable!(worker_flags -> workers (worker_id));
 joinable!(workers -> villages (home));
 
@@ -217,14 +309,20 @@ allow_tables_to_appear_in_same_query!(
     abilities,
     attacks,
     attacks_to_hobos,
+    blooms,
     buildings,
     effects,
     hobos,
     players,
+    repeating_tasks,
     resources,
+    return_trips,
+    return_trips_to_hobos,
     rewards,
+    shipments,
     streams,
     tasks,
+    trade_offers,
     villages,
     visit_reports,
     worker_flags,
@@ -4,7 +4,7 @@
 /// The default ID Type for referencing objects across the Paddlers services.
 pub type PadlId = i64;
 
-#[derive(Default, Debug, Copy, Clone, PartialEq, PartialOrd)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 /// Micro second precision
 pub struct Timestamp(i64);
 #[allow(dead_code)]
@@ -88,3 +88,10 @@ impl From<NaiveDateTime> for Timestamp {
         Timestamp::from_us(other.timestamp() * 1_000_000 + other.timestamp_subsec_micros() as i64)
     }
 }
+impl Into<NaiveDateTime> for Timestamp {
+    fn into(self) -> NaiveDateTime {
+        let seconds = self.0.div_euclid(1_000_000);
+        let micros = self.0.rem_euclid(1_000_000);
+        NaiveDateTime::from_timestamp(seconds, micros as u32 * 1000)
+    }
+}
@@ -37,6 +37,27 @@ impl Attack {
     }
 }
 
+impl Shipment {
+    pub fn destination(&self) -> VillageKey {
+        VillageKey(self.destination_village_id)
+    }
+    pub fn origin(&self) -> Option<VillageKey> {
+        self.origin_village_id.map(VillageKey)
+    }
+}
+
+impl TradeOffer {
+    pub fn village(&self) -> VillageKey {
+        VillageKey(self.village_id)
+    }
+}
+
+impl ReturnTrip {
+    pub fn home(&self) -> VillageKey {
+        VillageKey(self.home_village_id)
+    }
+}
+
 impl Building {
     pub fn village(&self) -> VillageKey {
         VillageKey(self.village_id)
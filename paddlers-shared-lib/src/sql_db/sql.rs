@@ -101,6 +101,33 @@ pub trait GameDB {
             .first::<i64>(self.dbconn())
             .expect("Error loading data") as usize
     }
+    fn attack(&self, atk: AttackKey) -> Option<Attack> {
+        attacks::table
+            .find(atk.num())
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading data")
+    }
+    fn all_attacks(&self) -> Vec<Attack> {
+        attacks::table
+            .order_by(attacks::arrival)
+            .load::<Attack>(self.dbconn())
+            .expect("Error loading data")
+    }
+    /// Attacks that arrived before the given time, ordered by key to page through them
+    fn attacks_arrived_before(
+        &self,
+        time: chrono::NaiveDateTime,
+        after: Option<AttackKey>,
+    ) -> Vec<Attack> {
+        attacks::table
+            .filter(attacks::arrival.lt(time))
+            .filter(attacks::id.gt(after.map(|k| k.num()).unwrap_or(0)))
+            .order_by(attacks::id)
+            .limit(500)
+            .load::<Attack>(self.dbconn())
+            .expect("Error loading data")
+    }
     fn attacks(&self, village: VillageKey, min_id: Option<i64>) -> Vec<Attack> {
         let results = attacks::table
             .filter(attacks::destination_village_id.eq(village.num()))
@@ -111,6 +138,16 @@ pub trait GameDB {
             .expect("Error loading data");
         results
     }
+    /// Attacks sent out by the village which arrive after the given time
+    fn outgoing_attacks(&self, village: VillageKey, after: chrono::NaiveDateTime) -> Vec<Attack> {
+        attacks::table
+            .filter(attacks::origin_village_id.eq(village.num()))
+            .filter(attacks::arrival.gt(after))
+            .order_by(attacks::arrival)
+            .limit(500)
+            .load::<Attack>(self.dbconn())
+            .expect("Error loading data")
+    }
     fn attacks_count(&self, village: VillageKey, min_id: Option<i64>) -> usize {
         let results = attacks::table
             .filter(attacks::destination_village_id.eq(village.num()))
@@ -165,6 +202,89 @@ pub trait GameDB {
     fn attack_done(&self, atk: &Attack) -> bool {
         self.attack_hobos_active_with_attack_info(atk).len() == 0
     }
+    fn return_trip(&self, trip: ReturnTripKey) -> Option<ReturnTrip> {
+        return_trips::table
+            .find(trip.num())
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading data")
+    }
+    fn all_return_trips(&self) -> Vec<ReturnTrip> {
+        return_trips::table
+            .order_by(return_trips::arrival)
+            .load::<ReturnTrip>(self.dbconn())
+            .expect("Error loading data")
+    }
+    fn return_trips_arrived_before(&self, time: chrono::NaiveDateTime) -> Vec<ReturnTrip> {
+        return_trips::table
+            .filter(return_trips::arrival.lt(time))
+            .order_by(return_trips::arrival)
+            .limit(500)
+            .load::<ReturnTrip>(self.dbconn())
+            .expect("Error loading data")
+    }
+    fn return_trip_hobos(&self, trip: ReturnTripKey) -> Vec<(Hobo, ReturnTripToHobo)> {
+        return_trips_to_hobos::table
+            .inner_join(hobos::table)
+            .filter(return_trips_to_hobos::return_trip_id.eq(trip.num()))
+            .select((hobos::all_columns, return_trips_to_hobos::all_columns))
+            .limit(500)
+            .load::<(Hobo, ReturnTripToHobo)>(self.dbconn())
+            .expect("Error loading data")
+    }
+    fn shipment(&self, id: ShipmentKey) -> Option<Shipment> {
+        shipments::table
+            .find(id.num())
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading data")
+    }
+    fn all_shipments(&self) -> Vec<Shipment> {
+        shipments::table
+            .order_by(shipments::arrival)
+            .load::<Shipment>(self.dbconn())
+            .expect("Error loading data")
+    }
+    fn shipments_arrived_before(&self, time: chrono::NaiveDateTime) -> Vec<Shipment> {
+        shipments::table
+            .filter(shipments::arrival.lt(time))
+            .order_by(shipments::arrival)
+            .limit(500)
+            .load::<Shipment>(self.dbconn())
+            .expect("Error loading data")
+    }
+    /// Shipments travelling to the village, ordered by arrival
+    fn incoming_shipments(&self, village: VillageKey) -> Vec<Shipment> {
+        shipments::table
+            .filter(shipments::destination_village_id.eq(village.num()))
+            .order_by(shipments::arrival)
+            .load::<Shipment>(self.dbconn())
+            .expect("Error loading data")
+    }
+    /// Shipments sent out by the village, ordered by arrival
+    fn outgoing_shipments(&self, village: VillageKey) -> Vec<Shipment> {
+        shipments::table
+            .filter(shipments::origin_village_id.eq(village.num()))
+            .order_by(shipments::arrival)
+            .load::<Shipment>(self.dbconn())
+            .expect("Error loading data")
+    }
+    fn trade_offer(&self, id: TradeOfferKey) -> Option<TradeOffer> {
+        trade_offers::table
+            .find(id.num())
+            .first(self.dbconn())
+            .optional()
+            .expect("Error loading data")
+    }
+    /// Open offers on the marketplace, newest first
+    fn trade_offers(&self, offset: i64, limit: i64) -> Vec<TradeOffer> {
+        trade_offers::table
+            .order_by(trade_offers::id.desc())
+            .offset(offset)
+            .limit(limit)
+            .load::<TradeOffer>(self.dbconn())
+            .expect("Error loading data")
+    }
     /// Visitors resting in town right now
     fn resting_visitors(&self, village_id: VillageKey) -> Vec<(Hobo, AttackKey)> {
         attacks_to_hobos::table
@@ -175,6 +295,7 @@ pub trait GameDB {
             // condition for "resting"
             .filter(hobos::hurried.eq(false))
             .filter(attacks_to_hobos::satisfied.is_null())
+            .filter(attacks_to_hobos::released.is_null())
             .filter(attacks::arrival.le(diesel::dsl::now.at_time_zone("UTC")))
             //
             .order_by(attacks::arrival.asc())
@@ -186,6 +307,26 @@ pub trait GameDB {
             .map(|(hobo, key)| (hobo, AttackKey(key)))
             .collect()
     }
+    /// Unhurried visitors of all villages that have not been evaluated, yet, with their resting spot.
+    /// (Includes visitors that are still on their way.)
+    fn unhurried_visitors_pending(&self) -> Vec<(Attack, Hobo, usize)> {
+        attacks_to_hobos::table
+            .inner_join(attacks::table)
+            .inner_join(hobos::table)
+            .filter(hobos::hurried.eq(false))
+            .filter(attacks_to_hobos::satisfied.is_null())
+            .order_by((attacks::arrival.asc(), attacks::id.asc()))
+            .select((
+                attacks::all_columns,
+                hobos::all_columns,
+                attacks_to_hobos::resting_spot,
+            ))
+            .load::<(Attack, Hobo, i32)>(self.dbconn())
+            .expect("Error loading data")
+            .into_iter()
+            .map(|(atk, hobo, spot)| (atk, hobo, spot as usize))
+            .collect()
+    }
 
     fn building(&self, building: BuildingKey) -> Option<Building> {
         buildings::table
@@ -202,6 +343,21 @@ pub trait GameDB {
             .expect("Error loading data");
         results
     }
+    /// All blooms on buildings of the village, including those that have faded already
+    fn blooms(&self, village: VillageKey) -> Vec<Bloom> {
+        blooms::table
+            .inner_join(buildings::table)
+            .filter(buildings::village_id.eq(village.num()))
+            .select(blooms::all_columns)
+            .load::<Bloom>(self.dbconn())
+            .expect("Error loading data")
+    }
+    fn blooms_on_building(&self, building: BuildingKey) -> Vec<Bloom> {
+        blooms::table
+            .filter(blooms::building_id.eq(building.num()))
+            .load::<Bloom>(self.dbconn())
+            .expect("Error loading data")
+    }
     fn find_building_by_coordinates(
         &self,
         x: i32,
@@ -273,6 +429,13 @@ pub trait GameDB {
             .optional()
             .expect("Error loading data")
     }
+    fn repeating_tasks(&self, worker_id: WorkerKey) -> Vec<RepeatingTask> {
+        repeating_tasks::table
+            .filter(repeating_tasks::worker_id.eq(worker_id.num()))
+            .order(repeating_tasks::id.asc())
+            .load(self.dbconn())
+            .expect("Error loading data")
+    }
     fn current_and_next_task(&self, worker_id: WorkerKey) -> (Option<Task>, Option<Task>) {
         let mut results = tasks::table
             .filter(tasks::worker_id.eq(worker_id.num()))
@@ -346,9 +509,11 @@ pub trait GameDB {
             .expect("Error loading data");
         results
     }
+    /// All villages of a player, the home village with the lowest id comes first
     fn player_villages(&self, player_id: PlayerKey) -> Vec<Village> {
         let results = villages::table
             .filter(villages::player_id.eq(player_id.num()))
+            .order_by(villages::id)
             .load::<Village>(self.dbconn())
             .expect("Error loading data");
         results
@@ -402,9 +567,30 @@ pub trait GameDB {
         .get_result(self.dbconn())
         .expect("Error in lookup")
     }
+    fn hobo_is_returning(&self, hid: HoboKey) -> bool {
+        diesel::select(diesel::dsl::exists(
+            return_trips_to_hobos::table.filter(return_trips_to_hobos::hobo_id.eq(hid.num())),
+        ))
+        .get_result(self.dbconn())
+        .expect("Error in lookup")
+    }
+    /// All hobos living in the nest, including those visiting another town
+    fn hobos_in_nest(&self, bid: BuildingKey) -> Vec<Hobo> {
+        hobos::table
+            .filter(hobos::nest.eq(Some(bid.num())))
+            .select(hobos::all_columns)
+            .limit(500)
+            .load::<Hobo>(self.dbconn())
+            .expect("Error loading data")
+    }
+    /// Hobos in the nest which are neither visiting another town nor on their way back
     fn idle_hobos_in_nest(&self, bid: BuildingKey) -> Vec<Hobo> {
         hobos::table
             .filter(hobos::nest.eq(Some(bid.num())))
+            .filter(diesel::dsl::sql::<diesel::sql_types::Bool>(
+                "NOT EXISTS (SELECT 1 FROM attacks_to_hobos a WHERE a.hobo_id = hobos.id) \
+                 AND NOT EXISTS (SELECT 1 FROM return_trips_to_hobos r WHERE r.hobo_id = hobos.id)",
+            )) // Diesel has no support for correlated subqueries
             .select(hobos::all_columns)
             .limit(500)
             .load::<Hobo>(self.dbconn())